@@ -183,7 +183,12 @@ fn main() {
 
     for text in lines {
         let mut buffer = harfrust::UnicodeBuffer::new();
-        buffer.push_str(text);
+        if args.utf8_clusters {
+            buffer.add_utf8(text, 0, text.len());
+        } else {
+            let codepoints: Vec<u32> = text.chars().map(u32::from).collect();
+            buffer.add_codepoints(&codepoints, 0, codepoints.len());
+        }
 
         if let Some(d) = args.direction {
             buffer.set_direction(d);
@@ -197,10 +202,6 @@ fn main() {
 
         buffer.set_cluster_level(args.cluster_level);
 
-        if !args.utf8_clusters {
-            buffer.reset_clusters();
-        }
-
         if let Some(g) = args.not_found_variation_selector_glyph {
             buffer.set_not_found_variation_selector_glyph(g);
         }
@@ -0,0 +1,56 @@
+#!/usr/bin/env python3
+
+"""
+Generator of the Adobe Glyph List For New Fonts table.
+
+    python3 gen-agl-table.py > ../src/hb/glyph_names_table.rs
+
+aglfn.txt is downloaded from https://github.com/adobe-type-tools/agl-aglfn
+if it is not in the current directory.
+
+Only the AGLFN is included, not the full Adobe Glyph List (glyphlist.txt).
+In particular, the legacy `afii` names of the AGL (Cyrillic, Hebrew, Arabic
+and a few symbols) are left out on purpose: the AGLFN dropped them, new
+fonts name those glyphs `uniXXXX`, and older fonts that use them carry them
+in their own `post` or CFF names, which glyph lookups search first.
+"""
+
+import os
+import urllib.request
+
+FILE_NAME = "aglfn.txt"
+
+if not os.path.exists(FILE_NAME):
+    urllib.request.urlretrieve(
+        "https://raw.githubusercontent.com/adobe-type-tools/agl-aglfn/master/"
+        + FILE_NAME,
+        FILE_NAME,
+    )
+
+version = None
+entries = []
+with open(FILE_NAME, encoding="utf-8") as f:
+    for line in f:
+        line = line.strip()
+        if line.startswith("# Table version:"):
+            version = line.split(":", 1)[1].strip()
+        if not line or line.startswith("#"):
+            continue
+        uv, name, _ = line.split(";")
+        entries.append((name, int(uv, 16)))
+
+# Glyph lookups binary search the names by their bytes.
+entries.sort()
+names = [name for name, _ in entries]
+assert len(set(names)) == len(names), "duplicate glyph names"
+assert not any(name.startswith("afii") for name in names)
+
+print("// WARNING: this file was generated by scripts/gen-agl-table.py")
+print()
+print("/// The Adobe Glyph List For New Fonts %s, as glyph names and the" % version)
+print("/// characters they stand for, sorted by name.")
+print("#[rustfmt::skip]")
+print("pub const AGLFN: &[(&str, char)] = &[")
+for name, uv in entries:
+    print("    (\"%s\", '\\u{%04X}')," % (name, uv))
+print("];")
@@ -0,0 +1,187 @@
+#!/usr/bin/env python3
+
+"""
+Generator of tests/fonts/rb_custom/TestGPOSFour-LigCarets.ttf.
+
+Adds a GDEF LigCaretList to TestGPOSFour.ttf from the text-rendering-tests,
+covering every caret value format:
+
+- glyph 1: a format 2 caret at the point of the outline with the largest x,
+  and a format 1 caret at 500;
+- glyph 2: a format 3 caret at 300 with variation index (0, 0), which varies
+  along `wdth`, and a format 3 caret at 400 with a device table adding 3 at
+  12 ppem;
+- glyph 5: a format 2 caret at the point of the second component with the
+  largest y, and a format 2 caret at a point that doesn't exist.
+
+Uses only the standard library, so the table is built by hand:
+
+    python3 gen-lig-carets-font.py \\
+        ../tests/fonts/text-rendering-tests/TestGPOSFour.ttf \\
+        ../tests/fonts/rb_custom/TestGPOSFour-LigCarets.ttf
+"""
+
+import struct
+import sys
+
+if len(sys.argv) != 3:
+    sys.exit(__doc__)
+
+src, dst = sys.argv[1:]
+data = open(src, "rb").read()
+num_tables = struct.unpack(">H", data[4:6])[0]
+tables = {}
+for i in range(num_tables):
+    tag, _, offset, length = struct.unpack(">4sIII", data[12 + 16 * i : 28 + 16 * i])
+    tables[tag] = data[offset : offset + length]
+
+
+def glyph_points(gid):
+    """The points of a simple glyph."""
+    index_to_loc_format = struct.unpack(">h", tables[b"head"][50:52])[0]
+    loca = tables[b"loca"]
+    if index_to_loc_format:
+        start, end = struct.unpack(">II", loca[4 * gid : 4 * gid + 8])
+    else:
+        start, end = [2 * x for x in struct.unpack(">HH", loca[2 * gid : 2 * gid + 4])]
+    glyph = tables[b"glyf"][start:end]
+    num_contours = struct.unpack(">h", glyph[:2])[0]
+    assert num_contours > 0
+    end_points = struct.unpack(">%dH" % num_contours, glyph[10 : 10 + 2 * num_contours])
+    num_points = end_points[-1] + 1
+    p = 10 + 2 * num_contours
+    p += 2 + struct.unpack(">H", glyph[p : p + 2])[0]
+    flags = []
+    while len(flags) < num_points:
+        flag = glyph[p]
+        p += 1
+        flags.append(flag)
+        if flag & 8:
+            flags += [flag] * glyph[p]
+            p += 1
+
+    def coordinates(short_flag, same_flag):
+        nonlocal p
+        values = []
+        value = 0
+        for flag in flags:
+            if flag & short_flag:
+                delta = glyph[p]
+                p += 1
+                value += delta if flag & same_flag else -delta
+            elif not flag & same_flag:
+                value += struct.unpack(">h", glyph[p : p + 2])[0]
+                p += 2
+            values.append(value)
+        return values
+
+    xs = coordinates(2, 16)
+    ys = coordinates(4, 32)
+    return list(zip(xs, ys))
+
+
+points_1 = glyph_points(1)
+points_11 = glyph_points(11)
+max_x = max(range(len(points_1)), key=lambda i: points_1[i][0])
+max_y = max(range(len(points_11)), key=lambda i: points_11[i][1])
+
+
+def caret_format_1(coordinate):
+    return struct.pack(">Hh", 1, coordinate)
+
+
+def caret_format_2(point):
+    return struct.pack(">HH", 2, point)
+
+
+def caret_format_3(coordinate, device):
+    return struct.pack(">HhH", 3, coordinate, 6) + device
+
+
+variation_index = struct.pack(">HHH", 0, 0, 0x8000)
+# An 8-bit device table for 12 ppem only.
+device = struct.pack(">HHH", 12, 12, 3) + struct.pack(">bb", 3, 0)
+lig_glyphs = [
+    [caret_format_2(max_x), caret_format_1(500)],
+    [caret_format_3(300, variation_index), caret_format_3(400, device)],
+    # Glyph 5 is composed of glyphs 1 and 11.
+    [caret_format_2(len(points_1) + max_y), caret_format_2(1000)],
+]
+
+
+def offsets_and_data(header_size, items):
+    offsets = []
+    body = b""
+    for item in items:
+        offsets.append(header_size + len(body))
+        body += item
+    return b"".join(struct.pack(">H", offset) for offset in offsets) + body, len(body)
+
+
+def lig_glyph(carets):
+    array, _ = offsets_and_data(2 + 2 * len(carets), carets)
+    return struct.pack(">H", len(carets)) + array
+
+
+lig_glyphs = [lig_glyph(carets) for carets in lig_glyphs]
+coverage = struct.pack(">HH", 1, 3) + struct.pack(">3H", 1, 2, 5)
+header_size = 4 + 2 * len(lig_glyphs)
+array, body_size = offsets_and_data(header_size, lig_glyphs)
+lig_caret_list = struct.pack(">HH", header_size + body_size, len(lig_glyphs)) + array + coverage
+while len(lig_caret_list) % 4:
+    lig_caret_list += b"\0"
+
+# Insert the list after the GDEF 1.3 header and shift the other offsets.
+gdef = tables[b"GDEF"]
+shift = len(lig_caret_list)
+major, minor, class_def, attach_list, lig_caret, mark_attach_class_def = struct.unpack(
+    ">HHHHHH", gdef[:12]
+)
+assert lig_caret == 0
+mark_glyph_sets, var_store = struct.unpack(">HI", gdef[12:18])
+header = struct.pack(
+    ">HHHHHHHI",
+    major,
+    minor,
+    class_def + shift,
+    attach_list,
+    18,
+    mark_attach_class_def,
+    mark_glyph_sets + shift,
+    var_store + shift,
+)
+tables[b"GDEF"] = header + lig_caret_list + gdef[18:]
+
+
+def checksum(table):
+    table += b"\0" * ((4 - len(table) % 4) % 4)
+    return sum(struct.unpack(">%dI" % (len(table) // 4), table)) & 0xFFFFFFFF
+
+
+head = bytearray(tables[b"head"])
+head[8:12] = b"\0\0\0\0"
+tables[b"head"] = bytes(head)
+search_power = 1
+while search_power * 2 <= num_tables:
+    search_power *= 2
+header = struct.pack(
+    ">IHHHH",
+    0x00010000,
+    num_tables,
+    search_power * 16,
+    search_power.bit_length() - 1,
+    num_tables * 16 - search_power * 16,
+)
+offset = 12 + 16 * num_tables
+records = b""
+table_data = b""
+for tag in sorted(tables):
+    table = tables[tag]
+    if tag == b"head":
+        head_offset = offset + len(table_data)
+    records += struct.pack(">4sIII", tag, checksum(table), offset + len(table_data), len(table))
+    table_data += table + b"\0" * ((4 - len(table) % 4) % 4)
+font = bytearray(header + records + table_data)
+adjustment = (0xB1B0AFBA - checksum(bytes(font))) & 0xFFFFFFFF
+font[head_offset + 8 : head_offset + 12] = struct.pack(">I", adjustment)
+open(dst, "wb").write(font)
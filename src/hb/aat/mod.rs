@@ -1,3 +1,8 @@
+use super::aat_layout_common::hb_aat_class_cache_t;
+use super::persist::{Reader, Writer};
+use super::set_digest::hb_set_digest_t;
+use super::{aat_layout_kerx_table, aat_layout_morx_table};
+use alloc::vec::Vec;
 use read_fonts::{
     tables::{ankr::Ankr, feat::Feat, kern::Kern, kerx::Kerx, morx::Morx, trak::Trak},
     FontRef, TableProvider,
@@ -11,10 +16,12 @@ pub struct AatTables<'a> {
     pub kerx: Option<Kerx<'a>>,
     pub trak: Option<Trak<'a>>,
     pub feat: Option<Feat<'a>>,
+    pub morx_cache: &'a [Vec<MachineCache>],
+    pub kerx_cache: &'a [MachineCache],
 }
 
 impl<'a> AatTables<'a> {
-    pub fn new(font: &FontRef<'a>) -> Self {
+    pub fn new(font: &FontRef<'a>, cache: &'a AatCache) -> Self {
         Self {
             morx: font.morx().ok(),
             ankr: font.ankr().ok(),
@@ -22,6 +29,119 @@ impl<'a> AatTables<'a> {
             kerx: font.kerx().ok(),
             trak: font.trak().ok(),
             feat: font.feat().ok(),
+            morx_cache: &cache.morx,
+            kerx_cache: &cache.kerx,
         }
     }
 }
+
+/// Per-subtable acceleration data for AAT state machines.
+///
+/// The glyph set holds all glyphs that can move a machine out of its
+/// start state, so subtables that can't act on a buffer are skipped.
+pub struct MachineCache {
+    pub glyph_set: hb_set_digest_t,
+    pub class_cache: hb_aat_class_cache_t,
+}
+
+impl MachineCache {
+    pub fn new(glyph_set: hb_set_digest_t) -> Self {
+        Self {
+            glyph_set,
+            class_cache: hb_aat_class_cache_t::new(),
+        }
+    }
+}
+
+impl Default for MachineCache {
+    fn default() -> Self {
+        Self::new(hb_set_digest_t::full())
+    }
+}
+
+/// Machine caches for every `morx` chain subtable and `kerx` subtable,
+/// in table order.
+#[derive(Default)]
+pub struct AatCache {
+    pub morx: Vec<Vec<MachineCache>>,
+    pub kerx: Vec<MachineCache>,
+}
+
+impl AatCache {
+    pub fn new(font: &FontRef) -> Self {
+        let num_glyphs = font
+            .maxp()
+            .map(|maxp| maxp.num_glyphs() as u32)
+            .unwrap_or_default();
+        let mut cache = Self::default();
+        if let Ok(morx) = font.morx() {
+            for chain in morx.chains().iter() {
+                let Ok(chain) = chain else {
+                    cache.morx.push(Vec::new());
+                    continue;
+                };
+                cache.morx.push(
+                    chain
+                        .subtables()
+                        .iter()
+                        .map(|subtable| {
+                            subtable
+                                .and_then(|subtable| subtable.kind())
+                                .map(|kind| {
+                                    MachineCache::new(
+                                        aat_layout_morx_table::collect_initial_glyphs(
+                                            &kind, num_glyphs,
+                                        ),
+                                    )
+                                })
+                                .unwrap_or_default()
+                        })
+                        .collect(),
+                );
+            }
+        }
+        if let Ok(kerx) = font.kerx() {
+            cache.kerx.extend(kerx.subtables().iter().map(|subtable| {
+                subtable
+                    .and_then(|subtable| subtable.kind())
+                    .map(|kind| {
+                        MachineCache::new(aat_layout_kerx_table::collect_initial_glyphs(
+                            &kind, num_glyphs,
+                        ))
+                    })
+                    .unwrap_or_default()
+            }));
+        }
+        cache
+    }
+
+    /// Writes the initiating glyph sets. Class caches are not persisted.
+    pub fn write(&self, writer: &mut Writer) {
+        writer.write_len(self.morx.len());
+        for chain in &self.morx {
+            writer.write_len(chain.len());
+            for machine in chain {
+                machine.glyph_set.write(writer);
+            }
+        }
+        writer.write_len(self.kerx.len());
+        for machine in &self.kerx {
+            machine.glyph_set.write(writer);
+        }
+    }
+
+    pub fn read(reader: &mut Reader) -> Option<Self> {
+        let read_machines = |reader: &mut Reader| {
+            let count = reader.read_len(3 * 8)?;
+            (0..count)
+                .map(|_| hb_set_digest_t::read(reader).map(MachineCache::new))
+                .collect::<Option<Vec<_>>>()
+        };
+        let chains = reader.read_len(4)?;
+        let morx = (0..chains)
+            .map(|_| read_machines(reader))
+            .collect::<Option<Vec<_>>>()?;
+        let kerx = read_machines(reader)?;
+        Some(Self { morx, kerx })
+    }
+}
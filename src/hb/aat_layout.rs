@@ -1,10 +1,10 @@
 #![allow(dead_code)]
 
 use super::buffer::{hb_buffer_t, hb_glyph_info_t};
+use super::hb_font_t;
 use super::hb_tag_t;
 use super::ot_shape_plan::hb_ot_shape_plan_t;
 use super::{aat_layout_kerx_table, aat_layout_morx_table, aat_layout_trak_table};
-use super::{aat_map, hb_font_t};
 use crate::hb::aat_layout_common::hb_aat_apply_context_t;
 use crate::hb::aat_layout_common::HB_BUFFER_SCRATCH_FLAG_AAT_HAS_DELETED;
 use crate::hb::ot_layout::_hb_glyph_info_is_aat_deleted;
@@ -500,19 +500,29 @@ pub fn hb_aat_layout_substitute(
     buffer: &mut hb_buffer_t,
     features: &[Feature],
 ) {
-    let mut builder = aat_map::hb_aat_map_builder_t::default();
+    // The builder and map live in the buffer so that their storage is
+    // reused across shaping calls.
+    let mut builder = core::mem::take(&mut buffer.aat_map_builder);
+    builder.clear();
 
     for feature in features {
         builder.add_feature(face, feature);
     }
 
-    let mut aat_map = aat_map::hb_aat_map_t::default();
+    let mut aat_map = core::mem::take(&mut buffer.aat_map);
     if plan.apply_morx {
         builder.compile(face, &mut aat_map);
+    } else {
+        aat_map.clear();
     }
 
-    let mut c = hb_aat_apply_context_t::new(face, buffer);
-    aat_layout_morx_table::apply(&mut c, &mut aat_map);
+    {
+        let mut c = hb_aat_apply_context_t::new(face, buffer);
+        aat_layout_morx_table::apply(&mut c, &mut aat_map);
+    }
+
+    buffer.aat_map_builder = builder;
+    buffer.aat_map = aat_map;
 }
 
 pub fn hb_aat_layout_zero_width_deleted_glyphs(buffer: &mut hb_buffer_t) {
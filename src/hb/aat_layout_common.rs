@@ -1,18 +1,31 @@
+use crate::hb::aat::MachineCache;
 use crate::hb::aat_layout::AAT::DELETED_GLYPH;
 use crate::hb::aat_map::range_flags_t;
 use crate::hb::buffer::{hb_buffer_t, HB_BUFFER_SCRATCH_FLAG_SHAPER0};
+use crate::hb::cache::hb_cache_t;
 use crate::hb::face::hb_font_t;
 use crate::hb::hb_mask_t;
 use crate::hb::ot_layout::_hb_glyph_info_set_aat_deleted;
+use crate::hb::set_digest::hb_set_digest_t;
+use alloc::vec::Vec;
+use read_fonts::tables::aat::{class, ExtendedStateTable, StateEntry};
+use read_fonts::types::{FixedSize, GlyphId};
 
 pub const HB_BUFFER_SCRATCH_FLAG_AAT_HAS_DELETED: u32 = HB_BUFFER_SCRATCH_FLAG_SHAPER0;
 
+pub const START_OF_TEXT: u16 = 0;
+
+// Keys are limited to 15 bits so that an empty slot never matches a key.
+pub type hb_aat_class_cache_t = hb_cache_t<15, 8, 256, 16>;
+
 pub struct hb_aat_apply_context_t<'a> {
     pub face: &'a hb_font_t<'a>,
     pub buffer: &'a mut hb_buffer_t,
     pub range_flags: Option<&'a mut [range_flags_t]>,
     pub subtable_flags: hb_mask_t,
     pub has_glyph_classes: bool,
+    pub buffer_glyph_set: hb_set_digest_t,
+    pub machine_cache: Option<&'a MachineCache>,
 }
 
 impl<'a> hb_aat_apply_context_t<'a> {
@@ -23,10 +36,35 @@ impl<'a> hb_aat_apply_context_t<'a> {
             range_flags: None,
             subtable_flags: 0,
             has_glyph_classes: face.ot_tables.has_glyph_classes(),
+            buffer_glyph_set: hb_set_digest_t::new(),
+            machine_cache: None,
         }
     }
 
+    pub fn setup_buffer_glyph_set(&mut self) {
+        self.buffer_glyph_set = hb_set_digest_t::new();
+        self.buffer_glyph_set.add_array(
+            self.buffer.info[..self.buffer.len]
+                .iter()
+                .map(|info| info.as_glyph()),
+        );
+    }
+
+    pub fn buffer_intersects_machine(&self) -> bool {
+        self.machine_cache
+            .map(|cache| cache.glyph_set.may_intersect(&self.buffer_glyph_set))
+            .unwrap_or(true)
+    }
+
+    pub fn machine_class<T>(&self, machine: &ExtendedStateTable<T>, glyph: GlyphId) -> u16
+    where
+        T: FixedSize + bytemuck::AnyBitPattern,
+    {
+        get_class(machine, glyph, self.machine_cache)
+    }
+
     pub fn output_glyph(&mut self, glyph: u32) {
+        self.buffer_glyph_set.add(glyph.into());
         if glyph == DELETED_GLYPH {
             self.buffer.scratch_flags |= HB_BUFFER_SCRATCH_FLAG_AAT_HAS_DELETED;
             _hb_glyph_info_set_aat_deleted(self.buffer.cur_mut(0));
@@ -41,6 +79,7 @@ impl<'a> hb_aat_apply_context_t<'a> {
     }
 
     pub fn replace_glyph(&mut self, glyph: u32) {
+        self.buffer_glyph_set.add(glyph.into());
         if glyph == DELETED_GLYPH {
             self.buffer.scratch_flags |= HB_BUFFER_SCRATCH_FLAG_AAT_HAS_DELETED;
             _hb_glyph_info_set_aat_deleted(self.buffer.cur_mut(0));
@@ -55,15 +94,77 @@ impl<'a> hb_aat_apply_context_t<'a> {
     }
 
     pub fn delete_glyph(&mut self) {
+        self.buffer_glyph_set.add(DELETED_GLYPH.into());
         self.buffer.scratch_flags |= HB_BUFFER_SCRATCH_FLAG_AAT_HAS_DELETED;
         _hb_glyph_info_set_aat_deleted(self.buffer.cur_mut(0));
         self.buffer.replace_glyph(DELETED_GLYPH);
     }
 
     pub fn replace_glyph_inplace(&mut self, i: usize, glyph: u32) {
+        self.buffer_glyph_set.add(glyph.into());
         self.buffer.info[i].glyph_id = glyph;
         if self.has_glyph_classes {
             self.buffer.info[i].set_glyph_props(self.face.glyph_props(glyph.into()));
         }
     }
 }
+
+/// Looks up the class of a glyph, going through the machine class cache
+/// when there is one.
+pub fn get_class<T>(
+    machine: &ExtendedStateTable<T>,
+    glyph: GlyphId,
+    cache: Option<&MachineCache>,
+) -> u16
+where
+    T: FixedSize + bytemuck::AnyBitPattern,
+{
+    let Some(cache) = cache else {
+        return machine.class(glyph).unwrap_or(1);
+    };
+    if let Some(class) = cache.class_cache.get(glyph.to_u32()) {
+        return class as u16;
+    }
+    let class = machine.class(glyph).unwrap_or(1);
+    cache.class_cache.set(glyph.to_u32(), u32::from(class));
+    class
+}
+
+/// Collects all glyphs that can make the machine leave the start state or
+/// trigger an action from it.
+///
+/// `is_initial` is called with the start state entries and should return
+/// true for entries that are actionable or that initiate an action.
+pub fn collect_initial_glyphs<T>(
+    machine: &ExtendedStateTable<T>,
+    num_glyphs: u32,
+    is_initial: impl Fn(&StateEntry<T>) -> bool,
+) -> hb_set_digest_t
+where
+    T: FixedSize + bytemuck::AnyBitPattern,
+{
+    // Filter of classes going out from the start state, filled lazily.
+    let mut filter: Vec<Option<bool>> = Vec::new();
+    let mut class_filter = |class: u16| -> bool {
+        let index = usize::from(class);
+        if index >= filter.len() {
+            filter.resize(index + 1, None);
+        }
+        *filter[index].get_or_insert_with(|| match machine.entry(START_OF_TEXT, class) {
+            Ok(entry) => entry.new_state != START_OF_TEXT || is_initial(&entry),
+            Err(_) => true,
+        })
+    };
+
+    let mut glyphs = hb_set_digest_t::new();
+    if class_filter(u16::from(class::DELETED_GLYPH)) {
+        glyphs.add(DELETED_GLYPH.into());
+    }
+    for gid in 0..num_glyphs.min(DELETED_GLYPH) {
+        let glyph = GlyphId::new(gid);
+        if class_filter(machine.class(glyph).unwrap_or(1)) {
+            glyphs.add(glyph);
+        }
+    }
+    glyphs
+}
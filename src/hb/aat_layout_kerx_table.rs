@@ -1,5 +1,7 @@
 use core::convert::TryFrom;
 
+use super::aat::MachineCache;
+use super::aat_layout_common::{collect_initial_glyphs as collect_machine_glyphs, get_class};
 use super::buffer::*;
 use super::hb_font_t;
 use super::ot_layout::TableIndex;
@@ -7,6 +9,7 @@ use super::ot_layout_common::lookup_flags;
 use super::ot_layout_gpos_table::attach_type;
 use super::ot_layout_gsubgpos::{skipping_iterator_t, OT::hb_ot_apply_context_t};
 use super::ot_shape_plan::hb_ot_shape_plan_t;
+use super::set_digest::hb_set_digest_t;
 use read_fonts::tables::{
     aat,
     ankr::Ankr,
@@ -17,12 +20,6 @@ use read_fonts::tables::{
 };
 use read_fonts::types::{BigEndian, FixedSize, GlyphId};
 
-// TODO: Use set_t, similarly to how it's used in harfbuzz.
-// HarfBuzz commit 9a4601b06b50cb0197c02203b6b19467ad4b4da8
-
-// TODO: Use a machine class cache.
-// HarfBuzz commit 83e0944f0f6cfbb46b63e2627e6ee076f4bfd489
-
 pub(crate) fn apply(
     plan: &hb_ot_shape_plan_t,
     face: &hb_font_t,
@@ -30,8 +27,18 @@ pub(crate) fn apply(
 ) -> Option<()> {
     buffer.unsafe_to_concat(None, None);
 
+    let mut buffer_glyph_set = hb_set_digest_t::new();
+    buffer_glyph_set.add_array(buffer.info[..buffer.len].iter().map(|info| info.as_glyph()));
+
     let mut seen_cross_stream = false;
-    for subtable in face.aat_tables.kerx.as_ref()?.subtables().iter() {
+    for (subtable_index, subtable) in face
+        .aat_tables
+        .kerx
+        .as_ref()?
+        .subtables()
+        .iter()
+        .enumerate()
+    {
         let Ok(subtable) = subtable else {
             continue;
         };
@@ -48,6 +55,13 @@ pub(crate) fn apply(
             continue;
         };
 
+        let machine_cache = face.aat_tables.kerx_cache.get(subtable_index);
+        if let Some(cache) = machine_cache {
+            if !cache.glyph_set.may_intersect(&buffer_glyph_set) {
+                continue;
+            }
+        }
+
         let reverse = buffer.direction.is_backward();
 
         if !seen_cross_stream && subtable.is_cross_stream() {
@@ -83,6 +97,7 @@ pub(crate) fn apply(
                     &subtable,
                     format1,
                     &format1.state_table,
+                    machine_cache,
                     &mut driver,
                     plan,
                     buffer,
@@ -105,6 +120,7 @@ pub(crate) fn apply(
                     &subtable,
                     format4,
                     &format4.state_table,
+                    machine_cache,
                     &mut driver,
                     plan,
                     buffer,
@@ -126,6 +142,33 @@ pub(crate) fn apply(
     Some(())
 }
 
+/// Collects the glyphs that can initiate kerning in the subtable.
+///
+/// For state machine subtables these are the glyphs that can trigger an
+/// action from the start state; for pair subtables, the left glyphs.
+pub fn collect_initial_glyphs(kind: &SubtableKind, num_glyphs: u32) -> hb_set_digest_t {
+    match kind {
+        SubtableKind::Format0(format0) => {
+            let mut glyphs = hb_set_digest_t::new();
+            glyphs.add_array(format0.pairs().iter().map(|pair| pair.left().into()));
+            glyphs
+        }
+        SubtableKind::Format1(format1) => {
+            collect_machine_glyphs(&format1.state_table, num_glyphs, |entry| {
+                entry.has_push() || entry.is_actionable()
+            })
+        }
+        SubtableKind::Format4(format4) => {
+            collect_machine_glyphs(&format4.state_table, num_glyphs, |entry| {
+                entry.has_mark() || entry.is_actionable()
+            })
+        }
+        // Glyphs missing from the class lookups still map to a kerning
+        // value, so any glyph may be kerned.
+        SubtableKind::Format2(_) | SubtableKind::Format6(_) => hb_set_digest_t::full(),
+    }
+}
+
 pub trait SimpleKerning {
     fn simple_kerning(&self, left: GlyphId, right: GlyphId) -> Option<i32>;
 }
@@ -260,6 +303,7 @@ fn apply_state_machine_kerning<T, E>(
     subtable: &Subtable,
     kind: &T,
     state_table: &aat::ExtendedStateTable<E>,
+    machine_cache: Option<&MachineCache>,
     driver: &mut dyn StateTableDriver<T, E>,
     plan: &hb_ot_shape_plan_t,
     buffer: &mut hb_buffer_t,
@@ -271,9 +315,11 @@ fn apply_state_machine_kerning<T, E>(
     buffer.idx = 0;
     loop {
         let class = if buffer.idx < buffer.len {
-            state_table
-                .class(buffer.info[buffer.idx].as_glyph())
-                .unwrap_or(1)
+            get_class(
+                state_table,
+                buffer.info[buffer.idx].as_glyph(),
+                machine_cache,
+            )
         } else {
             u16::from(aat::class::END_OF_TEXT)
         };
@@ -0,0 +1,51 @@
+use alloc::vec::Vec;
+use read_fonts::tables::aat::LookupU16;
+use read_fonts::types::{GlyphId, Tag};
+use read_fonts::FontRead;
+
+use super::common::Direction;
+use super::hb_font_t;
+
+/// Appends the carets of a ligature glyph from the AAT `lcar` table to
+/// `carets`. Returns `false` if the glyph has no caret values.
+pub(crate) fn get_lig_carets(
+    face: &hb_font_t,
+    direction: Direction,
+    glyph: GlyphId,
+    carets: &mut Vec<i32>,
+) -> bool {
+    let start = carets.len();
+    if read_lig_carets(face, direction, glyph, carets).is_none() {
+        carets.truncate(start);
+    }
+    carets.len() > start
+}
+
+fn read_lig_carets(
+    face: &hb_font_t,
+    direction: Direction,
+    glyph: GlyphId,
+    carets: &mut Vec<i32>,
+) -> Option<()> {
+    let data = face.font.table_data(Tag::new(b"lcar"))?;
+    // Version (Fixed), format (uint16) and a lookup of offsets to the caret
+    // class entries of the glyphs.
+    let format = data.read_at::<u16>(4).ok()?;
+    let lookup = LookupU16::read(data.split_off(6)?).ok()?;
+    let offset = usize::from(lookup.value(u16::try_from(glyph.to_u32()).ok()?).ok()?);
+    let count = data.read_at::<u16>(offset).ok()?;
+
+    for i in 0..usize::from(count) {
+        let value = data.read_at::<i16>(offset + 2 + i * 2).ok()?;
+        let position = match format {
+            0 => i32::from(value),
+            1 => face
+                .glyph_contour_point_for_origin(glyph, value as u16, direction)
+                .map(|(x, y)| if direction.is_horizontal() { x } else { y })
+                .unwrap_or_default(),
+            _ => return None,
+        };
+        carets.push(position);
+    }
+    Some(())
+}
@@ -1,24 +1,21 @@
 use super::aat_layout::*;
 use super::aat_map::{hb_aat_map_builder_t, hb_aat_map_t, range_flags_t};
 use super::{hb_font_t, hb_glyph_info_t};
-use crate::hb::aat_layout_common::hb_aat_apply_context_t;
+use crate::hb::aat_layout_common::{
+    collect_initial_glyphs as collect_machine_glyphs, hb_aat_apply_context_t, START_OF_TEXT,
+};
 use crate::hb::ot_layout::MAX_CONTEXT_LENGTH;
+use crate::hb::set_digest::hb_set_digest_t;
 use alloc::vec;
 use read_fonts::tables::aat::{ExtendedStateTable, NoPayload, StateEntry};
 use read_fonts::tables::morx::{
     ContextualEntryData, ContextualSubtable, InsertionEntryData, LigatureSubtable, SubtableKind,
 };
-use read_fonts::types::{BigEndian, FixedSize, GlyphId16};
-
-// TODO: Use class cache, similarly to how it's used in harfbuzz.
-// See https://github.com/harfbuzz/harfbuzz/pull/5031
+use read_fonts::types::{BigEndian, FixedSize, GlyphId, GlyphId16};
 
 // TODO: [morx] Blocklist dysfunctional morx table of AALMAGHRIBI.ttf font
 // HarfBuzz commit 1e629c35113e2460fd4a77b4fa9ae3ff6ec876ba
 
-// TODO: [morx] Only collect glyphs that can initiate action in the machine
-// https://github.com/harfbuzz/harfbuzz/pull/5041
-
 // Chain::compile_flags in harfbuzz
 pub fn compile_flags(
     face: &hb_font_t,
@@ -89,12 +86,17 @@ pub fn apply<'a>(c: &mut hb_aat_apply_context_t<'a>, map: &'a mut hb_aat_map_t)
     let chain_len = chains.iter().count();
     map.chain_flags.resize(chain_len, vec![]);
 
-    for (chain, chain_flags) in chains.iter().zip(map.chain_flags.iter_mut()) {
+    c.setup_buffer_glyph_set();
+
+    for (chain_index, (chain, chain_flags)) in
+        chains.iter().zip(map.chain_flags.iter_mut()).enumerate()
+    {
         let Ok(chain) = chain else {
             continue;
         };
+        let chain_cache = c.face.aat_tables.morx_cache.get(chain_index);
         c.range_flags = Some(chain_flags.as_mut_slice());
-        for subtable in chain.subtables().iter() {
+        for (subtable_index, subtable) in chain.subtables().iter().enumerate() {
             let Ok(subtable) = subtable else {
                 continue;
             };
@@ -113,6 +115,11 @@ pub fn apply<'a>(c: &mut hb_aat_apply_context_t<'a>, map: &'a mut hb_aat_map_t)
                 continue;
             }
 
+            c.machine_cache = chain_cache.and_then(|cache| cache.get(subtable_index));
+            if !c.buffer_intersects_machine() {
+                continue;
+            }
+
             // Buffer contents is always in logical direction.  Determine if
             // we need to reverse before applying this subtable.  We reverse
             // back after if we did reverse indeed.
@@ -163,6 +170,46 @@ pub fn apply<'a>(c: &mut hb_aat_apply_context_t<'a>, map: &'a mut hb_aat_map_t)
     Some(())
 }
 
+/// Collects the glyphs that can initiate an action in the subtable.
+pub fn collect_initial_glyphs(kind: &SubtableKind, num_glyphs: u32) -> hb_set_digest_t {
+    match kind {
+        SubtableKind::Rearrangement(table) => collect_machine_glyphs(table, num_glyphs, |entry| {
+            entry.flags & (RearrangementCtx::MARK_FIRST | RearrangementCtx::VERB) != 0
+        }),
+        SubtableKind::Contextual(table) => {
+            collect_machine_glyphs(&table.state_table, num_glyphs, |entry| {
+                entry.flags & ContextualCtx::SET_MARK != 0
+                    || entry.payload.mark_index.get() != 0xFFFF
+                    || entry.payload.current_index.get() != 0xFFFF
+            })
+        }
+        SubtableKind::Ligature(table) => {
+            collect_machine_glyphs(&table.state_table, num_glyphs, |entry| {
+                entry.flags & (LigatureCtx::SET_COMPONENT | LigatureCtx::PERFORM_ACTION) != 0
+            })
+        }
+        SubtableKind::NonContextual(lookup) => {
+            let mut glyphs = hb_set_digest_t::new();
+            for gid in 0..num_glyphs.min(0xFFFF) {
+                if lookup.value(gid as u16).is_ok() {
+                    glyphs.add(GlyphId::new(gid));
+                }
+            }
+            glyphs
+        }
+        SubtableKind::Insertion(table) => {
+            collect_machine_glyphs(&table.state_table, num_glyphs, |entry| {
+                entry.flags & InsertionCtx::SET_MARK != 0
+                    || (entry.flags
+                        & (InsertionCtx::CURRENT_INSERT_COUNT | InsertionCtx::MARKED_INSERT_COUNT)
+                        != 0
+                        && (entry.payload.current_insert_index.get() != 0xFFFF
+                            || entry.payload.marked_insert_index.get() != 0xFFFF))
+            })
+        }
+    }
+}
+
 trait driver_context_t<T> {
     fn in_place(&self) -> bool;
     fn can_advance(&self, entry: &StateEntry<T>) -> bool;
@@ -170,8 +217,6 @@ trait driver_context_t<T> {
     fn transition(&mut self, entry: &StateEntry<T>, ac: &mut hb_aat_apply_context_t) -> Option<()>;
 }
 
-const START_OF_TEXT: u16 = 0;
-
 fn drive<T: bytemuck::AnyBitPattern + FixedSize + core::fmt::Debug>(
     machine: &ExtendedStateTable<'_, T>,
     c: &mut dyn driver_context_t<T>,
@@ -223,7 +268,7 @@ fn drive<T: bytemuck::AnyBitPattern + FixedSize + core::fmt::Debug>(
         }
 
         let class = if ac.buffer.idx < ac.buffer.len {
-            machine.class(ac.buffer.cur(0).as_glyph()).unwrap_or(1)
+            ac.machine_class(machine, ac.buffer.cur(0).as_glyph())
         } else {
             u16::from(read_fonts::tables::aat::class::END_OF_TEXT)
         };
@@ -1,6 +1,5 @@
 use crate::hb::common::{HB_FEATURE_GLOBAL_END, HB_FEATURE_GLOBAL_START};
 use crate::Feature;
-use alloc::vec;
 use alloc::vec::Vec;
 use core::cmp::Ordering;
 
@@ -12,6 +11,15 @@ pub struct hb_aat_map_t {
     pub chain_flags: Vec<Vec<range_flags_t>>,
 }
 
+impl hb_aat_map_t {
+    /// Removes all range flags, keeping the storage of every chain.
+    pub fn clear(&mut self) {
+        for chain_flags in &mut self.chain_flags {
+            chain_flags.clear();
+        }
+    }
+}
+
 #[derive(Copy, Clone, PartialEq, Eq, Default)]
 pub struct feature_info_t {
     pub kind: u16,
@@ -81,6 +89,8 @@ pub struct hb_aat_map_builder_t {
     pub features: Vec<feature_range_t>,
     pub range_first: usize,
     pub range_last: usize,
+    feature_events: Vec<feature_event_t>,
+    active_features: Vec<feature_info_t>,
 }
 
 impl Default for hb_aat_map_builder_t {
@@ -90,11 +100,21 @@ impl Default for hb_aat_map_builder_t {
             range_last: HB_FEATURE_GLOBAL_END as usize,
             current_features: Vec::default(),
             features: Vec::default(),
+            feature_events: Vec::default(),
+            active_features: Vec::default(),
         }
     }
 }
 
 impl hb_aat_map_builder_t {
+    /// Resets the builder to its default state, keeping its storage.
+    pub fn clear(&mut self) {
+        self.current_features.clear();
+        self.features.clear();
+        self.range_first = HB_FEATURE_GLOBAL_START as usize;
+        self.range_last = HB_FEATURE_GLOBAL_END as usize;
+    }
+
     pub fn add_feature(&mut self, face: &hb_font_t, feature: &Feature) -> Option<()> {
         let feat = face.aat_tables.feat.as_ref()?;
 
@@ -166,8 +186,11 @@ impl hb_aat_map_builder_t {
     }
 
     pub fn compile(&mut self, face: &hb_font_t, m: &mut hb_aat_map_t) {
+        m.clear();
+
         // Compute active features per range, and compile each.
-        let mut feature_events = vec![];
+        let mut feature_events = core::mem::take(&mut self.feature_events);
+        feature_events.clear();
         for feature in &self.features {
             if feature.start == feature.end {
                 continue;
@@ -196,14 +219,16 @@ impl hb_aat_map_builder_t {
         });
 
         // Scan events and save features for each range.
-        let mut active_features = vec![];
+        let mut active_features = core::mem::take(&mut self.active_features);
+        active_features.clear();
         let mut last_index = 0;
 
         for event in &feature_events {
             if event.index != last_index {
                 // Save a snapshot of active features and the range.
                 // Sort features and merge duplicates.
-                self.current_features = active_features.clone();
+                self.current_features.clear();
+                self.current_features.extend_from_slice(&active_features);
                 self.range_first = last_index;
                 self.range_last = event.index.wrapping_sub(1);
 
@@ -246,5 +271,8 @@ impl hb_aat_map_builder_t {
                 last.cluster_last = HB_FEATURE_GLOBAL_END;
             }
         }
+
+        self.feature_events = feature_events;
+        self.active_features = active_features;
     }
 }
@@ -0,0 +1,89 @@
+use super::ot_layout_gpos_table::{attach_kind, attach_type};
+use crate::{BufferFlags, GlyphBuffer};
+
+/// How a glyph is attached to another glyph.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AttachmentKind {
+    /// A mark attached to a base glyph.
+    MarkToBase,
+    /// A mark attached to a component of a ligature glyph.
+    MarkToLigature {
+        /// Index of the ligature component, starting at 0.
+        component: u8,
+    },
+    /// A mark attached to another mark.
+    MarkToMark,
+    /// A glyph in a cursive chain, attached to its neighbour.
+    Cursive,
+}
+
+/// The attachment of a glyph, as returned by [`GlyphBuffer::attachment`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Attachment {
+    /// Index of the glyph it is attached to in the buffer.
+    pub glyph: usize,
+    /// The kind of the attachment.
+    pub kind: AttachmentKind,
+}
+
+impl GlyphBuffer {
+    /// Returns the glyph that the glyph at `index` was attached to during
+    /// positioning, and how.
+    ///
+    /// Attachments are only kept if the buffer was shaped with
+    /// [`BufferFlags::PRESERVE_ATTACHMENTS`]; otherwise this always returns
+    /// `None`. Attachments come from the GPOS mark and cursive lookups and
+    /// from AAT kerning. Attachments to glyphs removed after positioning,
+    /// like default ignorables, are dropped.
+    ///
+    /// Ligature components of marks are numbered from 0 in the order of the
+    /// components in the ligature. Component indices above 63 are clamped.
+    pub fn attachment(&self, index: usize) -> Option<Attachment> {
+        let buffer = &self.0;
+        if !buffer.flags.contains(BufferFlags::PRESERVE_ATTACHMENTS) || index >= buffer.len {
+            return None;
+        }
+        let pos = &buffer.pos[index];
+        let chain = pos.attach_chain();
+        if chain == 0 {
+            return None;
+        }
+        let glyph = index.checked_add_signed(isize::from(chain))?;
+        if glyph >= buffer.len {
+            return None;
+        }
+
+        let kind = if pos.attach_type() == attach_type::CURSIVE {
+            AttachmentKind::Cursive
+        } else {
+            let kind = pos.attach_kind();
+            match kind & 0x3 {
+                attach_kind::MARK_TO_LIGATURE => AttachmentKind::MarkToLigature {
+                    component: kind >> 2,
+                },
+                attach_kind::MARK_TO_MARK => AttachmentKind::MarkToMark,
+                _ => AttachmentKind::MarkToBase,
+            }
+        };
+        Some(Attachment { glyph, kind })
+    }
+
+    /// Returns the glyph that the mark at `index` is ultimately attached to,
+    /// following attachments of marks to other marks.
+    ///
+    /// Returns `index` itself if the glyph is not an attached mark. See
+    /// [`attachment`](Self::attachment) for when attachments are available.
+    pub fn attachment_base(&self, index: usize) -> usize {
+        let mut glyph = index;
+        // Chains can't be longer than the buffer, but guard against cycles.
+        for _ in 0..self.0.len {
+            match self.attachment(glyph) {
+                Some(attachment) if attachment.kind != AttachmentKind::Cursive => {
+                    glyph = attachment.glyph;
+                }
+                _ => break,
+            }
+        }
+        glyph
+    }
+}
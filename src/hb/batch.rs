@@ -0,0 +1,164 @@
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use super::buffer::hb_buffer_t;
+use super::face::hb_font_t;
+use crate::{BufferClusterLevel, BufferFlags, Direction, Feature, GlyphBuffer, Language, Script};
+
+/// A run of a shared text to be shaped by
+/// [`Shaper::shape_batch`](crate::Shaper::shape_batch).
+#[derive(Clone, Debug)]
+pub struct ShapeItem<'a> {
+    /// Byte range of the run in the shared text.
+    pub range: Range<usize>,
+    /// Direction of the run.
+    pub direction: Direction,
+    /// Script of the run.
+    pub script: Script,
+    /// Language of the run.
+    pub language: Option<Language>,
+    /// Features applied to the run. Feature ranges are in bytes of the
+    /// shared text, like the cluster values of the output.
+    pub features: &'a [Feature],
+    /// Buffer flags of the run, such as
+    /// [`BEGINNING_OF_TEXT`](BufferFlags::BEGINNING_OF_TEXT) for the first
+    /// run of a paragraph.
+    pub flags: BufferFlags,
+    /// Cluster level of the run.
+    pub cluster_level: BufferClusterLevel,
+}
+
+impl<'a> ShapeItem<'a> {
+    /// Creates an item without a language or features, and with the
+    /// default buffer flags and cluster level.
+    pub fn new(range: Range<usize>, direction: Direction, script: Script) -> Self {
+        ShapeItem {
+            range,
+            direction,
+            script,
+            language: None,
+            features: &[],
+            flags: BufferFlags::default(),
+            cluster_level: BufferClusterLevel::default(),
+        }
+    }
+
+    /// Returns `true` if the item can be shaped with the same plan as `other`.
+    fn shares_plan(&self, other: &ShapeItem) -> bool {
+        self.direction == other.direction
+            && self.script == other.script
+            && self.language == other.language
+            && self.features == other.features
+    }
+}
+
+impl hb_font_t<'_> {
+    /// Shapes many runs of a shared text.
+    ///
+    /// Each item produces one glyph buffer, stored at the same index of
+    /// `outputs`. Buffers already in `outputs` are reused, so shaping a
+    /// batch into the outputs of the previous one doesn't reallocate them.
+    /// Surplus buffers are dropped.
+    ///
+    /// Cluster values are byte offsets in `text` and the text surrounding
+    /// each run is used as its pre- and post-context. Consecutive items with
+    /// the same properties share a shape plan, which is otherwise taken
+    /// from the plan cache with the `std` feature.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range of an item is out of bounds or doesn't lie on
+    /// character boundaries of `text`.
+    pub fn shape_batch(&self, text: &str, items: &[ShapeItem], outputs: &mut Vec<GlyphBuffer>) {
+        outputs.truncate(items.len());
+        while outputs.len() < items.len() {
+            outputs.push(GlyphBuffer(hb_buffer_t::new()));
+        }
+        self.shape_items(text, items, outputs);
+    }
+
+    /// Shapes many runs of a shared text on multiple threads.
+    ///
+    /// Produces the same output as [`shape_batch`](Self::shape_batch).
+    /// Items are handed out to `threads` scoped threads in small blocks, and
+    /// the calling thread is one of them. A value of 0 uses the available
+    /// parallelism of the system.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range of an item is out of bounds or doesn't lie on
+    /// character boundaries of `text`.
+    #[cfg(feature = "std")]
+    pub fn shape_batch_parallel(
+        &self,
+        text: &str,
+        items: &[ShapeItem],
+        outputs: &mut Vec<GlyphBuffer>,
+        threads: usize,
+    ) {
+        let threads = match threads {
+            0 => std::thread::available_parallelism().map_or(1, |n| n.get()),
+            n => n,
+        };
+        if threads == 1 || items.len() < 2 {
+            return self.shape_batch(text, items, outputs);
+        }
+
+        outputs.truncate(items.len());
+        while outputs.len() < items.len() {
+            outputs.push(GlyphBuffer(hb_buffer_t::new()));
+        }
+
+        // Several blocks per thread keep threads busy when run lengths vary.
+        let block_len = items.len().div_ceil(threads * 8);
+        let blocks =
+            std::sync::Mutex::new(items.chunks(block_len).zip(outputs.chunks_mut(block_len)));
+        let work = || loop {
+            let Some((items, outputs)) = blocks.lock().unwrap_or_else(|e| e.into_inner()).next()
+            else {
+                break;
+            };
+            self.shape_items(text, items, outputs);
+        };
+        std::thread::scope(|scope| {
+            for _ in 1..threads.min(items.len()) {
+                scope.spawn(work);
+            }
+            work();
+        });
+    }
+
+    fn shape_items(&self, text: &str, items: &[ShapeItem], outputs: &mut [GlyphBuffer]) {
+        let mut plan = None;
+        for (i, (item, output)) in items.iter().zip(outputs).enumerate() {
+            if i == 0 || !item.shares_plan(&items[i - 1]) {
+                plan = None;
+            }
+            let plan = plan.get_or_insert_with(|| {
+                self.plan(
+                    item.direction,
+                    Some(item.script),
+                    item.language.as_ref(),
+                    item.features,
+                )
+            });
+
+            let mut buffer = core::mem::replace(output, GlyphBuffer(hb_buffer_t::new())).clear();
+            let run = &text[item.range.clone()];
+            for (offset, c) in run.char_indices() {
+                buffer.add(c, (item.range.start + offset) as u32);
+            }
+            buffer.set_pre_context(&text[..item.range.start]);
+            buffer.set_post_context(&text[item.range.end..]);
+            buffer.set_direction(item.direction);
+            buffer.set_script(item.script);
+            if let Some(language) = &item.language {
+                buffer.set_language(language.clone());
+            }
+            buffer.set_flags(item.flags);
+            buffer.set_cluster_level(item.cluster_level);
+
+            *output = self.shape_with_plan(plan, buffer, item.features);
+        }
+    }
+}
@@ -1,8 +1,11 @@
 use alloc::{string::String, vec::Vec};
 use core::cmp::min;
 use core::convert::TryFrom;
+use core::ops::Range;
 use read_fonts::types::{GlyphId, GlyphId16};
+use smallvec::SmallVec;
 
+use super::aat_map::{hb_aat_map_builder_t, hb_aat_map_t};
 use super::buffer::glyph_flag::{SAFE_TO_INSERT_TATWEEL, UNSAFE_TO_BREAK, UNSAFE_TO_CONCAT};
 use super::face::hb_glyph_extents_t;
 use super::unicode::{CharExt, GeneralCategoryExt};
@@ -10,7 +13,7 @@ use super::{hb_font_t, hb_mask_t};
 use crate::hb::set_digest::hb_set_digest_t;
 use crate::{script, BufferClusterLevel, BufferFlags, Direction, Language, Script, SerializeFlags};
 
-const CONTEXT_LENGTH: usize = 5;
+pub(crate) const CONTEXT_LENGTH: usize = 5;
 
 pub mod glyph_flag {
     /// Indicates that if input text is broken at the
@@ -150,6 +153,19 @@ impl GlyphPosition {
         let v: &mut [u8; 4] = bytemuck::cast_mut(&mut self.var);
         v[2] = n;
     }
+
+    #[inline]
+    pub(crate) fn attach_kind(&self) -> u8 {
+        // mark attachment kind, see `attach_kind`
+        let v: &[u8; 4] = bytemuck::cast_ref(&self.var);
+        v[3]
+    }
+
+    #[inline]
+    pub(crate) fn set_attach_kind(&mut self, n: u8) {
+        let v: &mut [u8; 4] = bytemuck::cast_mut(&mut self.var);
+        v[3] = n;
+    }
 }
 
 /// A glyph info.
@@ -374,10 +390,13 @@ pub const HB_BUFFER_CLUSTER_LEVEL_CHARACTERS: u32 = 2;
 pub const HB_BUFFER_CLUSTER_LEVEL_GRAPHEMES: u32 = 3;
 pub const HB_BUFFER_CLUSTER_LEVEL_DEFAULT: u32 = HB_BUFFER_CLUSTER_LEVEL_MONOTONE_GRAPHEMES;
 
+pub const HB_BUFFER_REPLACEMENT_CODEPOINT_DEFAULT: char = '\u{FFFD}';
+
 pub struct hb_buffer_t {
     // Information about how the text in the buffer should be treated.
     pub flags: BufferFlags,
     pub cluster_level: hb_buffer_cluster_level_t,
+    pub replacement: char,
     pub invisible: Option<GlyphId>,
     pub not_found_variation_selector: Option<u32>,
 
@@ -417,6 +436,16 @@ pub struct hb_buffer_t {
     pub max_len: usize,
     /// Maximum allowed operations.
     pub max_ops: i32,
+
+    /// Match position arrays used by contextual and ligature lookups, one
+    /// per nesting level. Kept across shaping calls so their storage is
+    /// reused.
+    pub(crate) match_positions: Vec<SmallVec<[usize; 4]>>,
+
+    /// AAT feature map builder and map compiled for the buffer. Kept across
+    /// shaping calls so their storage is reused.
+    pub(crate) aat_map_builder: hb_aat_map_builder_t,
+    pub(crate) aat_map: hb_aat_map_t,
 }
 
 impl hb_buffer_t {
@@ -435,6 +464,7 @@ impl hb_buffer_t {
         hb_buffer_t {
             flags: BufferFlags::empty(),
             cluster_level: HB_BUFFER_CLUSTER_LEVEL_DEFAULT,
+            replacement: HB_BUFFER_REPLACEMENT_CODEPOINT_DEFAULT,
             invisible: None,
             scratch_flags: HB_BUFFER_SCRATCH_FLAG_DEFAULT,
             not_found_variation_selector: None,
@@ -459,6 +489,9 @@ impl hb_buffer_t {
                 ['\0', '\0', '\0', '\0', '\0'],
             ],
             context_len: [0, 0],
+            match_positions: Vec::new(),
+            aat_map_builder: hb_aat_map_builder_t::default(),
+            aat_map: hb_aat_map_t::default(),
         }
     }
 
@@ -551,6 +584,8 @@ impl hb_buffer_t {
         self.scratch_flags = HB_BUFFER_SCRATCH_FLAG_DEFAULT;
         self.cluster_level = HB_BUFFER_CLUSTER_LEVEL_DEFAULT;
         self.not_found_variation_selector = None;
+        self.replacement = HB_BUFFER_REPLACEMENT_CODEPOINT_DEFAULT;
+        self.invisible = None;
     }
 
     #[inline]
@@ -1029,6 +1064,10 @@ impl hb_buffer_t {
     }
 
     pub fn delete_glyphs_inplace(&mut self, filter: impl Fn(&hb_glyph_info_t) -> bool) {
+        if self.preserves_attachments() {
+            self.remap_attachments(&filter);
+        }
+
         // Merge clusters and delete filtered glyphs.
         // NOTE! We can't use out-buffer as we have positioning data.
         let mut j = 0;
@@ -1078,6 +1117,41 @@ impl hb_buffer_t {
         self.len = j;
     }
 
+    /// Whether attachment chains are kept in the output.
+    pub(crate) fn preserves_attachments(&self) -> bool {
+        self.flags.contains(BufferFlags::PRESERVE_ATTACHMENTS)
+            && self.scratch_flags & HB_BUFFER_SCRATCH_FLAG_HAS_GPOS_ATTACHMENT != 0
+    }
+
+    /// Adjusts the attachment chains for the deletion of the glyphs matching
+    /// `filter`. Chains to deleted glyphs are cleared.
+    fn remap_attachments(&mut self, filter: &impl Fn(&hb_glyph_info_t) -> bool) {
+        // New index of every glyph, or None if it is deleted.
+        let mut index = Vec::with_capacity(self.len);
+        let mut j = 0;
+        for info in &self.info[..self.len] {
+            if filter(info) {
+                index.push(None);
+            } else {
+                index.push(Some(j));
+                j += 1;
+            }
+        }
+
+        for i in 0..self.len {
+            let chain = self.pos[i].attach_chain();
+            if chain == 0 {
+                continue;
+            }
+            let target = i as isize + isize::from(chain);
+            let chain = match (index[i], index.get(target as usize).copied().flatten()) {
+                (Some(from), Some(to)) if target >= 0 => (to as isize - from as isize) as i16,
+                _ => 0,
+            };
+            self.pos[i].set_attach_chain(chain);
+        }
+    }
+
     pub fn unsafe_to_break(&mut self, start: Option<usize>, end: Option<usize>) {
         self._set_glyph_flags(
             UNSAFE_TO_BREAK | UNSAFE_TO_CONCAT,
@@ -1476,6 +1550,115 @@ impl hb_buffer_t {
         }
     }
 
+    /// Adds the characters of an item of a text, with the surrounding text
+    /// as context, like `hb_buffer_add_utf`.
+    ///
+    /// `pre_context` yields the characters before the item backwards and
+    /// `post_context` those after it. Items yield their characters with
+    /// their offsets in the text.
+    fn add_item(
+        &mut self,
+        pre_context: impl Iterator<Item = char>,
+        item: impl Iterator<Item = (usize, char)>,
+        post_context: impl Iterator<Item = char>,
+    ) {
+        if !self.ensure(self.len + item.size_hint().0) {
+            return;
+        }
+
+        // If buffer is empty and pre-context provided, install it.
+        // This check is written this way, to make sure people can
+        // provide pre-context in one add_utf() call, then provide
+        // text in a follow-up call.
+        let mut pre_context = pre_context.take(CONTEXT_LENGTH).peekable();
+        if self.len == 0 && pre_context.peek().is_some() {
+            self.clear_context(0);
+            for (i, c) in pre_context.enumerate() {
+                self.context[0][i] = c;
+                self.context_len[0] += 1;
+            }
+        }
+
+        for (cluster, c) in item {
+            self.add(c as u32, cluster as u32);
+        }
+
+        self.clear_context(1);
+        for (i, c) in post_context.take(CONTEXT_LENGTH).enumerate() {
+            self.context[1][i] = c;
+            self.context_len[1] += 1;
+        }
+    }
+
+    fn push_utf8_bytes(&mut self, text: &[u8]) {
+        if !self.ensure(self.len + text.len()) {
+            return;
+        }
+
+        let mut offset = 0;
+        while offset < text.len() {
+            let (valid, invalid) = match core::str::from_utf8(&text[offset..]) {
+                Ok(valid) => (valid, 0),
+                Err(e) => {
+                    let valid = &text[offset..offset + e.valid_up_to()];
+                    // Replace every maximal ill-formed subsequence.
+                    let invalid = e.error_len().unwrap_or(text.len() - offset - valid.len());
+                    (core::str::from_utf8(valid).unwrap_or_default(), invalid)
+                }
+            };
+            for (i, c) in valid.char_indices() {
+                self.add(c as u32, (offset + i) as u32);
+            }
+            offset += valid.len();
+            if invalid != 0 {
+                self.add(self.replacement as u32, offset as u32);
+                offset += invalid;
+            }
+        }
+    }
+
+    fn add_utf8(&mut self, text: &str, item: Range<usize>) {
+        self.add_item(
+            text[..item.start].chars().rev(),
+            text[item.clone()]
+                .char_indices()
+                .map(|(i, c)| (item.start + i, c)),
+            text[item.end..].chars(),
+        );
+    }
+
+    fn add_utf16(&mut self, text: &[u16], item: Range<usize>) {
+        // Decode enough code units before the item for the pre-context,
+        // without starting in the middle of a surrogate pair.
+        let mut start = item.start.saturating_sub(CONTEXT_LENGTH * 2);
+        if start > 0 && (0xDC00..0xE000).contains(&text[start]) {
+            start -= 1;
+        }
+        let replacement = self.replacement;
+        let pre_context: Vec<char> = decode_utf16(&text[start..item.start], replacement)
+            .map(|(_, c)| c)
+            .collect();
+
+        self.add_item(
+            pre_context.into_iter().rev(),
+            decode_utf16(&text[item.clone()], replacement).map(|(i, c)| (item.start + i, c)),
+            decode_utf16(&text[item.end..], replacement).map(|(_, c)| c),
+        );
+    }
+
+    fn add_codepoints(&mut self, text: &[u32], item: Range<usize>) {
+        let replacement = self.replacement;
+        let decode = |&u: &u32| char::from_u32(u).unwrap_or(replacement);
+        self.add_item(
+            text[..item.start].iter().rev().map(decode),
+            text[item.clone()]
+                .iter()
+                .enumerate()
+                .map(|(i, u)| (item.start + i, decode(u))),
+            text[item.end..].iter().map(decode),
+        );
+    }
+
     fn set_pre_context(&mut self, text: &str) {
         self.clear_context(0);
         for (i, c) in text.chars().rev().enumerate().take(CONTEXT_LENGTH) {
@@ -1645,6 +1828,60 @@ impl UnicodeBuffer {
         self.0.push_str(str);
     }
 
+    /// Pushes possibly ill-formed UTF-8 to a buffer.
+    ///
+    /// Like [`push_str`](Self::push_str), characters get their byte offsets
+    /// as cluster values. Every maximal ill-formed subsequence is replaced
+    /// with one [replacement codepoint](Self::set_replacement_codepoint).
+    pub fn push_utf8_bytes(&mut self, bytes: &[u8]) {
+        self.0.push_utf8_bytes(bytes);
+    }
+
+    /// Appends an item of a UTF-8 text to the buffer.
+    ///
+    /// The item is the `item_length` bytes of `text` starting at
+    /// `item_offset`. Characters get their byte offsets in `text` as cluster
+    /// values, and the text around the item is set as pre- and post-context,
+    /// so that a paragraph can be shaped item by item. The pre-context is only
+    /// set if the buffer is empty.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the item is out of bounds or doesn't start and end on
+    /// character boundaries.
+    pub fn add_utf8(&mut self, text: &str, item_offset: usize, item_length: usize) {
+        self.0
+            .add_utf8(text, item_offset..item_offset + item_length);
+    }
+
+    /// Appends an item of a UTF-16 text to the buffer.
+    ///
+    /// Like [`add_utf8`](Self::add_utf8), but with offsets and cluster
+    /// values in UTF-16 code units. Unpaired surrogates are replaced with the
+    /// [replacement codepoint](Self::set_replacement_codepoint).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the item is out of bounds.
+    pub fn add_utf16(&mut self, text: &[u16], item_offset: usize, item_length: usize) {
+        self.0
+            .add_utf16(text, item_offset..item_offset + item_length);
+    }
+
+    /// Appends an item of a text of Unicode code points to the buffer.
+    ///
+    /// Like [`add_utf8`](Self::add_utf8), but with offsets and cluster
+    /// values in code points. Values that aren't Unicode scalar values are
+    /// replaced with the [replacement codepoint](Self::set_replacement_codepoint).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the item is out of bounds.
+    pub fn add_codepoints(&mut self, text: &[u32], item_offset: usize, item_length: usize) {
+        self.0
+            .add_codepoints(text, item_offset..item_offset + item_length);
+    }
+
     /// Sets the pre-context for this buffer.
     #[inline]
     pub fn set_pre_context(&mut self, str: &str) {
@@ -1699,6 +1936,40 @@ impl UnicodeBuffer {
         self.0.not_found_variation_selector = Some(glyph)
     }
 
+    /// Set the character that replaces invalid input.
+    ///
+    /// It is used by [`push_utf8_bytes`](Self::push_utf8_bytes),
+    /// [`add_utf16`](Self::add_utf16) and
+    /// [`add_codepoints`](Self::add_codepoints). Defaults to U+FFFD
+    /// REPLACEMENT CHARACTER.
+    #[inline]
+    pub fn set_replacement_codepoint(&mut self, replacement: char) {
+        self.0.replacement = replacement;
+    }
+
+    /// Get the character that replaces invalid input.
+    #[inline]
+    pub fn replacement_codepoint(&self) -> char {
+        self.0.replacement
+    }
+
+    /// Set the glyph that replaces hidden default ignorable characters.
+    ///
+    /// Unless the buffer flags preserve or remove default ignorables, they
+    /// are replaced with this glyph with zero advance. With `None`, the
+    /// default, the glyph of the space character is used, and default
+    /// ignorables are removed if the font has none.
+    #[inline]
+    pub fn set_invisible_glyph(&mut self, glyph: Option<u32>) {
+        self.0.invisible = glyph.map(GlyphId::new);
+    }
+
+    /// Get the glyph that replaces hidden default ignorable characters.
+    #[inline]
+    pub fn invisible_glyph(&self) -> Option<u32> {
+        self.0.invisible.map(|glyph| glyph.to_u32())
+    }
+
     /// Get the buffer language.
     #[inline]
     pub fn language(&self) -> Option<Language> {
@@ -1754,6 +2025,9 @@ impl UnicodeBuffer {
     }
 
     /// Clear the contents of the buffer.
+    ///
+    /// The segment properties, cluster level, not-found variation selector
+    /// glyph, replacement codepoint and invisible glyph are reset too.
     #[inline]
     pub fn clear(&mut self) {
         self.0.clear()
@@ -1811,6 +2085,10 @@ impl GlyphBuffer {
 
     /// Clears the content of the glyph buffer and returns an empty
     /// `UnicodeBuffer` reusing the existing allocation.
+    ///
+    /// The buffer also keeps the scratch storage used during shaping, so
+    /// once it has grown to fit the input, shaping similar runs with a
+    /// cached or reused [`ShapePlan`](crate::ShapePlan) doesn't allocate.
     #[inline]
     pub fn clear(mut self) -> UnicodeBuffer {
         self.0.clear();
@@ -1871,7 +2149,7 @@ impl GlyphBuffer {
 
             if flags.contains(SerializeFlags::GLYPH_EXTENTS) {
                 let mut extents = hb_glyph_extents_t::default();
-                face.glyph_extents(info.as_glyph(), &mut extents);
+                face.get_glyph_extents(info.as_glyph(), &mut extents);
                 write!(
                     &mut s,
                     "<{},{},{},{}>",
@@ -1904,3 +2182,23 @@ impl core::fmt::Debug for GlyphBuffer {
             .finish()
     }
 }
+
+/// Decodes UTF-16 code units, replacing unpaired surrogates, and yields the
+/// characters with their offsets.
+fn decode_utf16(text: &[u16], replacement: char) -> impl Iterator<Item = (usize, char)> + '_ {
+    let mut offset = 0;
+    char::decode_utf16(text.iter().copied()).map(move |c| {
+        let start = offset;
+        let c = match c {
+            Ok(c) => {
+                offset += c.len_utf16();
+                c
+            }
+            Err(_) => {
+                offset += 1;
+                replacement
+            }
+        };
+        (start, c)
+    })
+}
@@ -99,6 +99,11 @@ impl<const KEY_BITS: usize, const VALUE_BITS: usize, const CACHE_SIZE: usize, T:
 
     #[inline]
     pub fn get(&self, key: u32) -> Option<u32> {
+        // Keys that don't fit could otherwise match the tag of an empty slot.
+        if (key >> KEY_BITS) != 0 {
+            return None;
+        }
+
         let index = (key as usize) & (CACHE_SIZE - 1);
         let stored = self.values[index].get();
         let tag = stored >> VALUE_BITS;
@@ -123,3 +128,26 @@ impl<const KEY_BITS: usize, const VALUE_BITS: usize, const CACHE_SIZE: usize, T:
         true
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_slots_miss_out_of_range_keys() {
+        let cache = hb_cache_t::<15, 8, 256, 16>::new();
+        assert_eq!(cache.get(0x7FFF), None);
+        assert_eq!(cache.get(0xFF00), None);
+        assert_eq!(cache.get(0xFFFF), None);
+    }
+
+    #[test]
+    fn test_out_of_range_keys_are_not_stored() {
+        let cache = hb_cache_t::<15, 8, 256, 16>::new();
+        assert!(!cache.set(0xFFFF, 2));
+        assert_eq!(cache.get(0xFFFF), None);
+        assert!(cache.set(0x7FFF, 2));
+        assert_eq!(cache.get(0x7FFF), Some(2));
+        assert_eq!(cache.get(0xFFFF), None);
+    }
+}
@@ -1,4 +1,8 @@
+use alloc::vec::Vec;
+use core::ops::Range;
+
 use super::cache::hb_cache_t;
+use super::unicode::{self, hb_unicode_funcs_t, hb_unicode_general_category_t, CharExt};
 use read_fonts::{
     tables::cmap::{Cmap, Cmap14, CmapSubtable, MapVariant, PlatformId},
     types::GlyphId,
@@ -22,6 +26,14 @@ const UNICODE_FULL_ENCODING: u16 = 6;
 
 pub type cache_t = hb_cache_t<21, 19, 256, 32>;
 
+/// The character map of a font.
+///
+/// It uses the same `cmap` subtables as shaping: the best Unicode subtable,
+/// preferring symbol subtables, which also map U+0000..U+00FF to
+/// U+F000..U+F0FF, and falling back to the Macintosh Roman subtable. Variation
+/// sequences come from the format 14 subtable.
+///
+/// Returned by [`Shaper::charmap`](crate::Shaper::charmap).
 #[derive(Clone)]
 pub struct Charmap<'a> {
     subtable: Option<(PlatformId, u16, CmapSubtable<'a>)>,
@@ -29,20 +41,51 @@ pub struct Charmap<'a> {
     cache: &'a cache_t,
 }
 
+/// Indices of the encoding records of the subtables a [`Charmap`] uses.
+///
+/// Selecting them parses the subtable headers, so shapers that are built
+/// repeatedly for the same font can select them once.
+#[derive(Clone, Copy, Default)]
+pub(crate) struct CmapRecords {
+    subtable: Option<usize>,
+    vs_subtable: Option<usize>,
+}
+
+impl CmapRecords {
+    pub(crate) fn new(font: &FontRef) -> Self {
+        let Ok(cmap) = font.cmap() else {
+            return Self::default();
+        };
+        let offset_data = cmap.offset_data();
+        let vs_subtable = cmap.encoding_records().iter().position(|record| {
+            matches!(record.subtable(offset_data), Ok(CmapSubtable::Format14(_)))
+        });
+        Self {
+            subtable: find_best_cmap_subtable(&cmap),
+            vs_subtable,
+        }
+    }
+}
+
 impl<'a> Charmap<'a> {
-    pub fn new(font: &FontRef<'a>, cache: &'a cache_t) -> Self {
+    pub(crate) fn new(font: &FontRef<'a>, cache: &'a cache_t, records: CmapRecords) -> Self {
         if let Ok(cmap) = font.cmap() {
-            let subtable = find_best_cmap_subtable(&cmap);
             let offset_data = cmap.offset_data();
-            let vs_subtable = cmap
-                .encoding_records()
-                .iter()
-                .filter_map(|record| record.subtable(offset_data).ok())
-                .filter_map(|subtable| match subtable {
-                    CmapSubtable::Format14(table) => Some(table),
+            let encoding_records = cmap.encoding_records();
+            let subtable = records
+                .subtable
+                .and_then(|index| encoding_records.get(index))
+                .and_then(|record| {
+                    let subtable = record.subtable(offset_data).ok()?;
+                    Some((record.platform_id(), record.encoding_id(), subtable))
+                });
+            let vs_subtable = records
+                .vs_subtable
+                .and_then(|index| encoding_records.get(index))
+                .and_then(|record| match record.subtable(offset_data) {
+                    Ok(CmapSubtable::Format14(table)) => Some(table),
                     _ => None,
-                })
-                .next();
+                });
             return Self {
                 subtable,
                 vs_subtable,
@@ -56,12 +99,26 @@ impl<'a> Charmap<'a> {
         }
     }
 
-    fn map_impl(&self, mut c: u32) -> Option<GlyphId> {
+    fn map_impl(&self, c: u32) -> Option<GlyphId> {
+        let result = self.lookup(c);
+        if result.is_none() && self.is_symbol() && c <= 0x00FF {
+            // For symbol-encoded OpenType fonts, we duplicate the
+            // U+F000..F0FF range at U+0000..U+00FF.  That's what
+            // Windows seems to do, and that's hinted about at:
+            // https://docs.microsoft.com/en-us/typography/opentype/spec/recom
+            // under "Non-Standard (Symbol) Fonts".
+            return self.map_codepoint(0xF000 + c);
+        }
+        result
+    }
+
+    /// Looks up a code point in the subtable, without the symbol fallback.
+    fn lookup(&self, mut c: u32) -> Option<GlyphId> {
         let subtable = self.subtable.as_ref()?;
         if subtable.0 == PlatformId::Macintosh && c > 0x7F {
             c = unicode_to_macroman(c);
         }
-        let result = match &subtable.2 {
+        match &subtable.2 {
             CmapSubtable::Format0(table) => table
                 .glyph_id_array()
                 .get(c as usize)
@@ -84,24 +141,17 @@ impl<'a> Charmap<'a> {
             CmapSubtable::Format12(table) => table.map_codepoint(c),
             CmapSubtable::Format13(table) => table.map_codepoint(c),
             _ => None,
-        };
-        if result.is_none()
-            && subtable.0 == PlatformId::Windows
-            && subtable.1 == WINDOWS_SYMBOL_ENCODING
-            && c <= 0x00FF
-        {
-            // For symbol-encoded OpenType fonts, we duplicate the
-            // U+F000..F0FF range at U+0000..U+00FF.  That's what
-            // Windows seems to do, and that's hinted about at:
-            // https://docs.microsoft.com/en-us/typography/opentype/spec/recom
-            // under "Non-Standard (Symbol) Fonts".
-            return self.map(0xF000 + c);
         }
-        result
+    }
+
+    fn is_symbol(&self) -> bool {
+        self.subtable.as_ref().is_some_and(|subtable| {
+            subtable.0 == PlatformId::Windows && subtable.1 == WINDOWS_SYMBOL_ENCODING
+        })
     }
 
     #[inline]
-    pub fn map(&self, c: u32) -> Option<GlyphId> {
+    pub(crate) fn map_codepoint(&self, c: u32) -> Option<GlyphId> {
         if let Some(gid) = self.cache.get(c) {
             return Some(GlyphId::new(gid));
         }
@@ -112,16 +162,162 @@ impl<'a> Charmap<'a> {
         gid
     }
 
-    pub fn map_variant(&self, c: u32, vs: u32) -> Option<GlyphId> {
+    /// Returns the nominal glyph of a character.
+    pub fn map(&self, c: char) -> Option<u32> {
+        // Array subtables map missing characters to glyph 0.
+        self.map_codepoint(c as u32)
+            .map(GlyphId::to_u32)
+            .filter(|&gid| gid != 0)
+    }
+
+    /// Returns the glyph of a variation sequence, or `None` if the font has
+    /// none for it.
+    ///
+    /// Sequences the font maps to the default glyph return the nominal glyph
+    /// of `c`.
+    pub fn map_variant(&self, c: char, vs: char) -> Option<u32> {
         let subtable = self.vs_subtable.as_ref()?;
-        match subtable.map_variant(c, vs)? {
+        match subtable.map_variant(c as u32, vs as u32)? {
             MapVariant::UseDefault => self.map(c),
-            MapVariant::Variant(gid) => Some(gid),
+            MapVariant::Variant(gid) => Some(gid.to_u32()),
+        }
+    }
+
+    /// Returns all characters the font maps and their nominal glyphs.
+    ///
+    /// Characters are yielded in the order of the subtable, and those of
+    /// symbol fonts at both U+00xx and U+F0xx.
+    pub fn mappings(&self) -> impl Iterator<Item = (char, u32)> + '_ {
+        let (format4, format12, format13, array) = match self.subtable.as_ref().map(|s| &s.2) {
+            Some(CmapSubtable::Format4(table)) => (Some(table.iter()), None, None, None),
+            Some(CmapSubtable::Format12(table)) => (None, Some(table.iter()), None, None),
+            Some(CmapSubtable::Format13(table)) => (None, None, Some(table.iter()), None),
+            Some(CmapSubtable::Format0(table)) => {
+                let glyphs = table.glyph_id_array().iter().map(|&gid| u32::from(gid));
+                (None, None, None, Some((0, glyphs.collect::<Vec<_>>())))
+            }
+            Some(CmapSubtable::Format6(table)) => {
+                let glyphs = table
+                    .glyph_id_array()
+                    .iter()
+                    .map(|gid| u32::from(gid.get()));
+                let first = u32::from(table.first_code());
+                (None, None, None, Some((first, glyphs.collect())))
+            }
+            Some(CmapSubtable::Format10(table)) => {
+                let glyphs = table
+                    .glyph_id_array()
+                    .iter()
+                    .map(|gid| u32::from(gid.get()));
+                let first = table.start_char_code();
+                (None, None, None, Some((first, glyphs.collect())))
+            }
+            _ => (None, None, None, None),
+        };
+        let array = array.into_iter().flat_map(|(first, glyphs)| {
+            glyphs
+                .into_iter()
+                .enumerate()
+                .map(move |(i, gid)| (first + i as u32, GlyphId::new(gid)))
+        });
+
+        let macintosh = self
+            .subtable
+            .as_ref()
+            .is_some_and(|subtable| subtable.0 == PlatformId::Macintosh);
+        let symbol = (0..=0xFF)
+            .filter(|_| self.is_symbol())
+            .filter(|&c| self.lookup(c).is_none())
+            .filter_map(|c| Some((c, self.lookup(0xF000 + c)?)));
+
+        format4
+            .into_iter()
+            .flatten()
+            .chain(format12.into_iter().flatten())
+            .chain(format13.into_iter().flatten())
+            .chain(array)
+            .filter(|(_, gid)| gid.to_u32() != 0)
+            .map(move |(c, gid)| {
+                if macintosh && c > 0x7F {
+                    (macroman_to_unicode(c), gid)
+                } else {
+                    (c, gid)
+                }
+            })
+            .chain(symbol)
+            .filter_map(|(c, gid)| Some((char::from_u32(c)?, gid.to_u32())))
+    }
+
+    /// Returns all variation sequences the font maps and their glyphs.
+    ///
+    /// Sequences the font maps to the default glyph yield the nominal glyph
+    /// of the character, and are skipped if it has none.
+    pub fn variation_sequences(&self) -> impl Iterator<Item = (char, char, u32)> + '_ {
+        self.vs_subtable
+            .iter()
+            .flat_map(|subtable| subtable.iter())
+            .filter_map(|(c, vs, variant)| {
+                let c = char::from_u32(c)?;
+                let vs = char::from_u32(vs)?;
+                let gid = match variant {
+                    MapVariant::UseDefault => self.map(c)?,
+                    MapVariant::Variant(gid) => gid.to_u32(),
+                };
+                Some((c, vs, gid))
+            })
+    }
+
+    /// Returns the byte ranges of the grapheme clusters of `text` that the
+    /// font has no glyphs for, in order.
+    ///
+    /// Like shaping, it treats characters as supported if they decompose or
+    /// compose with the following marks to supported characters, if they are
+    /// spaces and the font has a space glyph, or if they are default
+    /// ignorables, which are hidden. Lookups that may substitute glyphs are
+    /// not considered. Grapheme clusters are approximated like in shaping:
+    /// marks, emoji modifiers, regional indicator pairs and ZWJ
+    /// sequences extend the previous cluster.
+    pub fn missing_clusters(&self, text: &str) -> Vec<Range<usize>> {
+        unicode::graphemes(text)
+            .filter(|grapheme| {
+                let mut chars = text[grapheme.clone()].chars();
+                let Some(mut base) = chars.next() else {
+                    return false;
+                };
+                let mut covered = true;
+                for c in chars {
+                    match unicode::compose(base, c).filter(|&ab| self.has_char(ab)) {
+                        Some(composed) => base = composed,
+                        None => covered &= self.has_char(c),
+                    }
+                }
+                !(covered && self.has_char(base))
+            })
+            .collect()
+    }
+
+    /// Returns `true` if shaping finds a glyph for `c` on its own.
+    fn has_char(&self, c: char) -> bool {
+        if self.map(c).is_some() || c.is_default_ignorable() {
+            return true;
+        }
+        if let Some((a, b)) = unicode::decompose(c) {
+            if self.has_char(a) && (b == '\0' || self.has_char(b)) {
+                return true;
+            }
+        }
+        if c.general_category() == hb_unicode_general_category_t::SpaceSeparator
+            && c.space_fallback() != hb_unicode_funcs_t::NOT_SPACE
+        {
+            return self.map(' ').is_some();
         }
+        c == '\u{2011}' && self.map('\u{2010}').is_some()
     }
 }
 
-fn find_best_cmap_subtable<'a>(cmap: &Cmap<'a>) -> Option<(PlatformId, u16, CmapSubtable<'a>)> {
+/// Returns the index of the encoding record of the subtable used for
+/// mapping.
+fn find_best_cmap_subtable(cmap: &Cmap) -> Option<usize> {
     // Symbol subtable.
     // Prefer symbol if available.
     // https://github.com/harfbuzz/harfbuzz/issues/1918
@@ -140,29 +336,21 @@ fn find_best_cmap_subtable<'a>(cmap: &Cmap<'a>) -> Option<(PlatformId, u16, Cmap
         .or_else(|| find_cmap_subtable(cmap, PlatformId::Macintosh, 0))
 }
 
-fn find_cmap_subtable<'a>(
-    cmap: &Cmap<'a>,
-    platform_id: PlatformId,
-    encoding_id: u16,
-) -> Option<(PlatformId, u16, CmapSubtable<'a>)> {
+fn find_cmap_subtable(cmap: &Cmap, platform_id: PlatformId, encoding_id: u16) -> Option<usize> {
     let offset_data = cmap.offset_data();
-    for record in cmap.encoding_records() {
-        if record.platform_id() != platform_id || record.encoding_id() != encoding_id {
-            continue;
-        }
-        if let Ok(subtable) = record.subtable(offset_data) {
-            match subtable {
-                CmapSubtable::Format0(_)
-                | CmapSubtable::Format4(_)
-                | CmapSubtable::Format6(_)
-                | CmapSubtable::Format10(_)
-                | CmapSubtable::Format12(_)
-                | CmapSubtable::Format13(_) => return Some((platform_id, encoding_id, subtable)),
-                _ => {}
-            }
-        }
-    }
-    None
+    cmap.encoding_records().iter().position(|record| {
+        record.platform_id() == platform_id
+            && record.encoding_id() == encoding_id
+            && matches!(
+                record.subtable(offset_data),
+                Ok(CmapSubtable::Format0(_)
+                    | CmapSubtable::Format4(_)
+                    | CmapSubtable::Format6(_)
+                    | CmapSubtable::Format10(_)
+                    | CmapSubtable::Format12(_)
+                    | CmapSubtable::Format13(_))
+            )
+    })
 }
 
 #[rustfmt::skip]
@@ -185,6 +373,12 @@ static UNICODE_TO_MACROMAN: &[u16] = &[
     0x00AF, 0x02D8, 0x02D9, 0x02DA, 0x00B8, 0x02DD, 0x02DB, 0x02C7,
 ];
 
+fn macroman_to_unicode(c: u32) -> u32 {
+    UNICODE_TO_MACROMAN
+        .get((c as usize).wrapping_sub(0x80))
+        .map_or(0, |&u| u32::from(u))
+}
+
 fn unicode_to_macroman(c: u32) -> u32 {
     let u = c as u16;
     let Some(index) = UNICODE_TO_MACROMAN.iter().position(|m| *m == u) else {
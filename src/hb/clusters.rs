@@ -0,0 +1,358 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use super::buffer::{_cluster_group_func, hb_buffer_t, GlyphPosition};
+use super::unicode::graphemes;
+use crate::GlyphBuffer;
+
+/// A cluster of glyphs and the text it was shaped from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cluster {
+    /// Range of the text of the cluster, in the units of the cluster values.
+    pub text: Range<usize>,
+    /// Range of the glyphs of the cluster in the buffer.
+    pub glyphs: Range<usize>,
+    /// Advance of the cluster along the direction of the buffer.
+    pub advance: i32,
+}
+
+/// The result of [`GlyphBuffer::hit_test`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Hit {
+    /// Range of the text of the hit cluster.
+    pub text: Range<usize>,
+    /// Whether the position is on the trailing half of the cluster in
+    /// logical order, so that the caret belongs after it.
+    pub trailing: bool,
+}
+
+impl Hit {
+    /// Returns the text position of the caret: the end of the cluster for
+    /// trailing hits and its start otherwise.
+    pub fn caret_index(&self) -> usize {
+        if self.trailing {
+            self.text.end
+        } else {
+            self.text.start
+        }
+    }
+}
+
+/// An iterator over the clusters of a [`GlyphBuffer`].
+///
+/// Created by [`GlyphBuffer::clusters`].
+pub struct Clusters<'a> {
+    buffer: &'a hb_buffer_t,
+    text: Range<usize>,
+    /// The distinct cluster values of the buffer, in increasing order.
+    values: Vec<u32>,
+    start: usize,
+}
+
+impl Iterator for Clusters<'_> {
+    type Item = Cluster;
+
+    fn next(&mut self) -> Option<Cluster> {
+        if self.start >= self.buffer.len {
+            return None;
+        }
+        let glyphs = self.start..self.buffer.group_end(self.start, _cluster_group_func);
+        self.start = glyphs.end;
+
+        let cluster = self.buffer.info[glyphs.start].cluster;
+        let index = self.values.partition_point(|&value| value <= cluster);
+        let start = if index == 1 {
+            self.text.start
+        } else {
+            cluster as usize
+        };
+        let end = self
+            .values
+            .get(index)
+            .map_or(self.text.end, |&value| value as usize);
+        let advance = self.buffer.pos[glyphs.clone()]
+            .iter()
+            .map(|pos| advance(self.buffer, pos))
+            .sum();
+        Some(Cluster {
+            text: start..end.max(start),
+            glyphs,
+            advance,
+        })
+    }
+}
+
+impl GlyphBuffer {
+    /// Returns the clusters of the buffer in glyph order, which is visual
+    /// order for right-to-left and bottom-to-top text.
+    ///
+    /// `text` is the range of the text the buffer was shaped from, in the
+    /// units of the cluster values. For a buffer filled with
+    /// [`UnicodeBuffer::push_str`](crate::UnicodeBuffer::push_str), it is
+    /// `0..text.len()` in bytes.
+    ///
+    /// A cluster is a run of adjacent glyphs with the same cluster value,
+    /// and its text extends to the next greater cluster value of the buffer.
+    /// Characters without glyphs of their own, like removed default
+    /// ignorables, thereby belong to the preceding cluster, and text before
+    /// the smallest cluster value to the logically first cluster.
+    ///
+    /// With the monotone [cluster levels](crate::BufferClusterLevel), cluster
+    /// values never decrease in logical order and every cluster has a
+    /// distinct text range. With the other levels, reordered glyphs may
+    /// split a cluster value into several clusters with the same text range.
+    pub fn clusters(&self, text: Range<usize>) -> Clusters<'_> {
+        let mut values: Vec<u32> = self.glyph_infos().iter().map(|info| info.cluster).collect();
+        values.sort_unstable();
+        values.dedup();
+        Clusters {
+            buffer: &self.0,
+            text,
+            values,
+            start: 0,
+        }
+    }
+
+    /// Returns the index of the first glyph of the cluster of every text
+    /// unit in `text`, like the cluster map of DirectWrite.
+    ///
+    /// `text` is the range of the text the buffer was shaped from, as for
+    /// [`clusters`](Self::clusters), and the map has an entry for every unit
+    /// in it. Units of clusters split by reordering map to the glyph of the
+    /// first split part. If the buffer has no glyphs, all units map to 0.
+    pub fn cluster_map(&self, text: Range<usize>) -> Vec<usize> {
+        let mut map = vec![usize::MAX; text.len()];
+        for cluster in self.clusters(text.clone()) {
+            let start = cluster.text.start.clamp(text.start, text.end) - text.start;
+            let end = cluster.text.end.clamp(text.start, text.end) - text.start;
+            let units = start..end.max(start);
+            for entry in &mut map[units] {
+                *entry = (*entry).min(cluster.glyphs.start);
+            }
+        }
+        for entry in &mut map {
+            if *entry == usize::MAX {
+                *entry = 0;
+            }
+        }
+        map
+    }
+
+    /// Returns the caret position at the start of every character or
+    /// grapheme cluster of a cluster, in logical order.
+    ///
+    /// Positions are pen positions along the direction of the buffer,
+    /// relative to the start of the buffer, like the sums of the glyph
+    /// advances. For right-to-left text, the first character starts at the
+    /// right edge of the cluster.
+    ///
+    /// If a glyph of the cluster has one
+    /// [ligature caret](crate::Shaper::ligature_carets) less than the number
+    /// of `characters`, its carets separate the characters. Otherwise the
+    /// advance of the cluster is divided equally between them.
+    pub fn caret_positions(
+        &self,
+        shaper: &crate::Shaper,
+        cluster: &Cluster,
+        characters: usize,
+    ) -> Vec<i32> {
+        let buffer = &self.0;
+        let pen = |glyph: usize| -> i32 {
+            buffer.pos[..glyph]
+                .iter()
+                .map(|pos| advance(buffer, pos))
+                .sum()
+        };
+        let start = pen(cluster.glyphs.start);
+        let backward = buffer.direction.is_backward();
+
+        let ligature = cluster.glyphs.clone().find_map(|glyph| {
+            let carets = shaper.ligature_carets(buffer.info[glyph].glyph_id, buffer.direction);
+            (carets.len() + 1 == characters).then_some((glyph, carets))
+        });
+        if let Some((glyph, carets)) = ligature {
+            let origin = pen(glyph);
+            let mut offsets: Vec<i32> = carets
+                .iter()
+                .map(|&caret| {
+                    if buffer.direction.is_horizontal() {
+                        origin + caret
+                    } else {
+                        origin - caret
+                    }
+                })
+                .collect();
+            offsets.sort_unstable();
+            if backward {
+                offsets.reverse();
+                offsets.insert(0, start + cluster.advance);
+            } else {
+                offsets.insert(0, start);
+            }
+            return offsets;
+        }
+
+        (0..characters)
+            .map(|i| {
+                let offset = (i64::from(cluster.advance) * i as i64 / characters as i64) as i32;
+                if backward {
+                    start + cluster.advance - offset
+                } else {
+                    start + offset
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the grapheme cluster at a pen position and the side of it
+    /// that was hit.
+    ///
+    /// `text` is the text the buffer was shaped from, with cluster values
+    /// that are byte offsets into it, as from
+    /// [`UnicodeBuffer::push_str`](crate::UnicodeBuffer::push_str).
+    /// `position` is along the direction of the buffer relative to its
+    /// start, like the sums of the glyph advances: an x offset from the left
+    /// edge for horizontal text and a downward y offset from the top for
+    /// vertical text.
+    ///
+    /// Clusters of several grapheme clusters, like ligatures, are divided
+    /// at their [caret positions](Self::caret_positions), so each grapheme
+    /// cluster can be hit on its own. Clusters without advance, like marks
+    /// in clusters of their own, can't be hit, so the caret never lands
+    /// between a base and its marks. Positions before the first or after
+    /// the last cluster hit the outer side of that cluster. An empty buffer
+    /// returns an empty range at the start of `text`.
+    pub fn hit_test(&self, shaper: &crate::Shaper, text: &str, position: i32) -> Hit {
+        let backward = self.0.direction.is_backward();
+        let mut pen = 0;
+        let mut first = None;
+        let mut last = None;
+        for cluster in self.clusters(0..text.len()) {
+            let start = pen;
+            pen += cluster.advance;
+            if cluster.advance == 0 {
+                first.get_or_insert(cluster);
+                continue;
+            }
+            if position < pen {
+                let carets = self.grapheme_carets(shaper, text, &cluster);
+                if position < start {
+                    // Before the first cluster: the near side of its first
+                    // grapheme cluster along the pen.
+                    let grapheme = if backward {
+                        carets.last()
+                    } else {
+                        carets.first()
+                    };
+                    return Hit {
+                        text: grapheme.map_or(cluster.text, |(grapheme, _)| grapheme.clone()),
+                        trailing: backward,
+                    };
+                }
+                for (i, (grapheme, caret)) in carets.iter().enumerate() {
+                    let next = carets.get(i + 1).map(|(_, caret)| *caret);
+                    let (low, high) = if backward {
+                        (next.unwrap_or(start), *caret)
+                    } else {
+                        (*caret, next.unwrap_or(pen))
+                    };
+                    // Carets outside of the cluster leave positions that no
+                    // range contains to the last grapheme cluster.
+                    if (low..high).contains(&position) || i + 1 == carets.len() {
+                        // The far half of the grapheme cluster along the pen
+                        // is its logical end for forward directions.
+                        let far = position >= low + (high - low) / 2;
+                        return Hit {
+                            text: grapheme.clone(),
+                            trailing: far != backward,
+                        };
+                    }
+                }
+            }
+            last = Some(cluster);
+        }
+
+        match last.or(first) {
+            Some(cluster) => {
+                let carets = self.grapheme_carets(shaper, text, &cluster);
+                let grapheme = if backward {
+                    carets.first()
+                } else {
+                    carets.last()
+                };
+                Hit {
+                    text: grapheme.map_or(cluster.text, |(grapheme, _)| grapheme.clone()),
+                    trailing: !backward,
+                }
+            }
+            None => Hit {
+                text: 0..0,
+                trailing: false,
+            },
+        }
+    }
+
+    /// Returns the pen position of the caret before the byte at `index` of
+    /// `text`.
+    ///
+    /// `text` and the returned position are as for
+    /// [`hit_test`](Self::hit_test). The caret at the start of a cluster is
+    /// at its leading edge, which is the right edge for right-to-left text.
+    /// Inside a cluster, carets are at the [caret
+    /// positions](Self::caret_positions) of its grapheme clusters, and an
+    /// `index` inside a grapheme cluster, like between a base and its marks,
+    /// is at the start of that grapheme cluster. An `index` at or after the
+    /// end of `text` is at the trailing edge of the logically last cluster.
+    pub fn caret_offset(&self, shaper: &crate::Shaper, text: &str, index: usize) -> i32 {
+        let backward = self.0.direction.is_backward();
+        let mut pen = 0;
+        for cluster in self.clusters(0..text.len()) {
+            pen += cluster.advance;
+            if cluster.text.contains(&index) {
+                let carets = self.grapheme_carets(shaper, text, &cluster);
+                return carets
+                    .iter()
+                    .take_while(|(grapheme, _)| grapheme.start <= index)
+                    .last()
+                    .map_or(0, |(_, caret)| *caret);
+            }
+        }
+
+        // The caret is at the logical end or start of the buffer.
+        if (index >= text.len()) == backward {
+            0
+        } else {
+            pen
+        }
+    }
+
+    /// Returns the grapheme clusters of `cluster` with the caret positions
+    /// at their starts, in logical order.
+    fn grapheme_carets(
+        &self,
+        shaper: &crate::Shaper,
+        text: &str,
+        cluster: &Cluster,
+    ) -> Vec<(Range<usize>, i32)> {
+        let graphemes: Vec<Range<usize>> = match text.get(cluster.text.clone()) {
+            Some(slice) if !slice.is_empty() => graphemes(slice)
+                .map(|grapheme| {
+                    cluster.text.start + grapheme.start..cluster.text.start + grapheme.end
+                })
+                .collect(),
+            _ => vec![cluster.text.clone()],
+        };
+        let carets = self.caret_positions(shaper, cluster, graphemes.len());
+        graphemes.into_iter().zip(carets).collect()
+    }
+}
+
+/// Returns the advance of a glyph along the direction of `buffer`.
+pub(crate) fn advance(buffer: &hb_buffer_t, pos: &GlyphPosition) -> i32 {
+    if buffer.direction.is_horizontal() {
+        pos.x_advance
+    } else {
+        -pos.y_advance
+    }
+}
@@ -1,37 +1,97 @@
+use alloc::{sync::Arc, vec::Vec};
 #[cfg(not(feature = "std"))]
 #[allow(unused_imports)]
 use core_maths::CoreFloat;
 use read_fonts::types::{F2Dot14, Fixed, GlyphId};
-use read_fonts::{FontRef, TableProvider};
+use read_fonts::{FontRef, ReadError, TableProvider};
 use smallvec::SmallVec;
 
-use super::aat::AatTables;
-use super::buffer::GlyphPropsFlags;
-use super::charmap::{cache_t as cmap_cache_t, Charmap};
+use super::aat::{AatCache, AatTables};
+use super::charmap::{cache_t as cmap_cache_t, Charmap, CmapRecords};
 use super::glyph_metrics::GlyphMetrics;
-use super::glyph_names::GlyphNames;
+#[cfg(feature = "std")]
+use super::glyph_names::GlyphNameIndex;
+use super::glyph_names::{glyph_name_to_char, GlyphNames};
 use super::ot::{LayoutTable, OtCache, OtTables};
 use super::ot_layout::TableIndex;
 use super::ot_shape::{hb_ot_shape_context_t, shape_internal};
-use crate::{script, Feature, GlyphBuffer, NormalizedCoord, ShapePlan, UnicodeBuffer, Variation};
+#[cfg(feature = "std")]
+use super::ot_shape_plan::{hb_shape_plan_cache_t, hb_shape_plan_key_t};
+use super::persist::{Reader, Writer};
+use super::{aat_layout_lcar_table, ot_layout_gdef_table};
+use crate::{
+    script, Feature, GlyphBuffer, GlyphExtents, NormalizedCoord, ShapePlan, UnicodeBuffer,
+    Variation,
+};
+use crate::{Direction, Language, Script};
 
 /// Data required for shaping with a single font.
 pub struct ShaperData {
     ot_cache: OtCache,
+    aat_cache: AatCache,
     cmap_cache: cmap_cache_t,
+    #[cfg(feature = "std")]
+    plan_cache: hb_shape_plan_cache_t,
+    #[cfg(feature = "std")]
+    glyph_name_index: std::sync::OnceLock<GlyphNameIndex>,
 }
 
 impl ShaperData {
     /// Creates new cached shaper data for the given font.
     pub fn new(font: &FontRef) -> Self {
         let ot_cache = OtCache::new(font);
+        let aat_cache = AatCache::new(font);
         let cmap_cache = cmap_cache_t::new();
         Self {
             ot_cache,
+            aat_cache,
             cmap_cache,
+            #[cfg(feature = "std")]
+            plan_cache: hb_shape_plan_cache_t::default(),
+            #[cfg(feature = "std")]
+            glyph_name_index: std::sync::OnceLock::new(),
         }
     }
 
+    /// Serializes the precomputed lookup, subtable, GDEF and AAT data so
+    /// that it can be restored with [`from_bytes`](Self::from_bytes),
+    /// typically in another process.
+    ///
+    /// Every lazily computed entry, such as the per-lookup coverage and
+    /// subtable data, is forced first, so this can be expensive for large
+    /// fonts whose lookups haven't been used yet. Runtime caches and shape
+    /// plans are not included. The font must be the one this data was
+    /// created for.
+    pub fn to_bytes(&self, font: &FontRef) -> Vec<u8> {
+        let mut writer = Writer::new(font);
+        self.ot_cache.write(font, &mut writer);
+        self.aat_cache.write(&mut writer);
+        writer.finish()
+    }
+
+    /// Restores shaper data serialized with [`to_bytes`](Self::to_bytes).
+    ///
+    /// Returns `None` if the data is malformed, was written by a different
+    /// version of this crate, or was computed from a font whose GSUB, GPOS,
+    /// GDEF, `morx`, `kerx` or `maxp` tables differ from those of `font`.
+    pub fn from_bytes(font: &FontRef, data: &[u8]) -> Option<Self> {
+        let mut reader = Reader::new(font, data)?;
+        let ot_cache = OtCache::read(font, &mut reader)?;
+        let aat_cache = AatCache::read(&mut reader)?;
+        if !reader.is_empty() {
+            return None;
+        }
+        Some(Self {
+            ot_cache,
+            aat_cache,
+            cmap_cache: cmap_cache_t::new(),
+            #[cfg(feature = "std")]
+            plan_cache: hb_shape_plan_cache_t::default(),
+            #[cfg(feature = "std")]
+            glyph_name_index: std::sync::OnceLock::new(),
+        })
+    }
+
     /// Returns a builder for constructing a new shaper with the given
     /// font.
     pub fn shaper<'a>(&'a self, font: &FontRef<'a>) -> ShaperBuilder<'a> {
@@ -40,6 +100,7 @@ impl ShaperData {
             font: font.clone(),
             instance: None,
             point_size: None,
+            setup: None,
         }
     }
 }
@@ -160,6 +221,7 @@ pub struct ShaperBuilder<'a> {
     font: FontRef<'a>,
     instance: Option<&'a ShaperInstance>,
     point_size: Option<f32>,
+    setup: Option<ShaperSetup>,
 }
 
 impl<'a> ShaperBuilder<'a> {
@@ -182,15 +244,22 @@ impl<'a> ShaperBuilder<'a> {
     /// Builds the shaper with the current configuration.
     pub fn build(self) -> crate::Shaper<'a> {
         let font = self.font;
-        let units_per_em = font.head().map(|head| head.units_per_em()).unwrap_or(1000);
-        let charmap = Charmap::new(&font, &self.data.cmap_cache);
-        let glyph_metrics = GlyphMetrics::new(&font);
         let coords = self
             .instance
             .map(|instance| instance.coords())
             .unwrap_or_default();
+        let setup = self
+            .setup
+            .unwrap_or_else(|| ShaperSetup::new(&font, coords));
+        let ShaperSetup {
+            units_per_em,
+            cmap_records,
+            feature_variation_indices,
+        } = setup;
+        let charmap = Charmap::new(&font, &self.data.cmap_cache, cmap_records);
+        let glyph_metrics = GlyphMetrics::new(&font);
         let ot_tables = OtTables::new(&font, &self.data.ot_cache, coords);
-        let aat_tables = AatTables::new(&font);
+        let aat_tables = AatTables::new(&font, &self.data.aat_cache);
         hb_font_t {
             font,
             units_per_em,
@@ -200,7 +269,192 @@ impl<'a> ShaperBuilder<'a> {
             glyph_metrics,
             ot_tables,
             aat_tables,
+            feature_variation_indices,
+            #[cfg(feature = "std")]
+            plan_cache: &self.data.plan_cache,
+            #[cfg(feature = "std")]
+            glyph_name_index: &self.data.glyph_name_index,
+        }
+    }
+}
+
+/// The parts of a shaper that don't borrow the font.
+///
+/// [`ShaperBuilder::build`] computes them for every shaper, while
+/// [`OwnedShaper`] computes them once per instance.
+#[derive(Clone, Copy)]
+struct ShaperSetup {
+    units_per_em: u16,
+    cmap_records: CmapRecords,
+    /// The FeatureVariations records selected for GSUB and GPOS. Selecting
+    /// them evaluates their condition sets, so it isn't done on every shape.
+    feature_variation_indices: [Option<u32>; 2],
+}
+
+impl ShaperSetup {
+    fn new(font: &FontRef, coords: &[F2Dot14]) -> Self {
+        let units_per_em = font.head().map(|head| head.units_per_em()).unwrap_or(1000);
+        let feature_variation_indices = [
+            font.gsub().ok().map(LayoutTable::Gsub),
+            font.gpos().ok().map(LayoutTable::Gpos),
+        ]
+        .map(|table| table.and_then(|table| table.feature_variation_index(coords)));
+        Self {
+            units_per_em,
+            cmap_records: CmapRecords::new(font),
+            feature_variation_indices,
+        }
+    }
+}
+
+/// A shaper that owns its font data, shaper data and instance.
+///
+/// [`Shaper`](crate::Shaper) borrows all of these, which makes it hard to
+/// keep around in long-lived structures. This type holds them instead and
+/// hands out shapers borrowing from itself with [`shaper`](Self::shaper).
+///
+/// The font data can be any owner of the bytes, such as an
+/// `Arc<[u8]>`, a `Vec<u8>` or a memory map. Its [`AsRef`] implementation
+/// must return the same bytes every time.
+pub struct OwnedShaper<T = Arc<[u8]>> {
+    font_data: T,
+    index: u32,
+    data: ShaperData,
+    instance: Option<ShaperInstance>,
+    point_size: Option<f32>,
+    setup: ShaperSetup,
+}
+
+impl<T: AsRef<[u8]>> OwnedShaper<T> {
+    /// Creates a new owned shaper for the font at the given index of a font
+    /// file or collection.
+    pub fn new(font_data: T, index: u32) -> Result<Self, ReadError> {
+        let font = FontRef::from_index(font_data.as_ref(), index)?;
+        let data = ShaperData::new(&font);
+        let setup = ShaperSetup::new(&font, &[]);
+        Ok(Self {
+            font_data,
+            index,
+            data,
+            instance: None,
+            point_size: None,
+            setup,
+        })
+    }
+
+    /// Creates a new owned shaper from previously computed shaper data,
+    /// such as data restored with [`ShaperData::from_bytes`].
+    ///
+    /// The shaper data must have been created for the same font.
+    pub fn from_data(font_data: T, index: u32, data: ShaperData) -> Result<Self, ReadError> {
+        let font = FontRef::from_index(font_data.as_ref(), index)?;
+        let setup = ShaperSetup::new(&font, &[]);
+        Ok(Self {
+            font_data,
+            index,
+            data,
+            instance: None,
+            point_size: None,
+            setup,
+        })
+    }
+
+    /// Returns the font data.
+    pub fn font_data(&self) -> &T {
+        &self.font_data
+    }
+
+    /// Returns the index of the font in the font data.
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// Returns the font.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the [`AsRef`] implementation of the font data returns
+    /// bytes that are no longer a valid font, which the constructors check
+    /// once.
+    pub fn font(&self) -> FontRef<'_> {
+        FontRef::from_index(self.font_data.as_ref(), self.index)
+            .expect("font data was validated on construction")
+    }
+
+    /// Returns the shaper data.
+    pub fn data(&self) -> &ShaperData {
+        &self.data
+    }
+
+    /// Returns the instance, if any.
+    pub fn instance(&self) -> Option<&ShaperInstance> {
+        self.instance.as_ref()
+    }
+
+    /// Sets the instance for the shapers created from now on.
+    ///
+    /// This defines the variable font configuration.
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as [`font`](Self::font).
+    pub fn set_instance(&mut self, instance: Option<ShaperInstance>) {
+        let coords = instance
+            .as_ref()
+            .map(|instance| instance.coords())
+            .unwrap_or_default();
+        self.setup = ShaperSetup::new(&self.font(), coords);
+        self.instance = instance;
+    }
+
+    /// Sets the instance from the specified list of variation settings.
+    ///
+    /// The setting values are in user space and the order is insignificant.
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as [`font`](Self::font).
+    pub fn set_variations<V>(&mut self, variations: V)
+    where
+        V: IntoIterator,
+        V::Item: Into<Variation>,
+    {
+        let instance = ShaperInstance::from_variations(&self.font(), variations);
+        self.set_instance(Some(instance));
+    }
+
+    /// Returns the point size, if any.
+    pub fn point_size(&self) -> Option<f32> {
+        self.point_size
+    }
+
+    /// Sets the point size for the shapers created from now on.
+    ///
+    /// This controls adjustments provided by the tracking table.
+    pub fn set_point_size(&mut self, size: Option<f32>) {
+        self.point_size = size;
+    }
+
+    /// Returns a shaper borrowing the owned font, data and instance.
+    ///
+    /// The character map subtables and the feature variations of the
+    /// instance are selected once, when the shaper or its instance is set
+    /// up. Otherwise this only reads the font's table directory and the
+    /// headers of the tables used for shaping, so it is cheap enough to
+    /// call for every shaping request.
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as [`font`](Self::font).
+    pub fn shaper(&self) -> crate::Shaper<'_> {
+        ShaperBuilder {
+            data: &self.data,
+            font: self.font(),
+            instance: self.instance.as_ref(),
+            point_size: self.point_size,
+            setup: Some(self.setup),
         }
+        .build()
     }
 }
 
@@ -215,6 +469,11 @@ pub struct hb_font_t<'a> {
     glyph_metrics: GlyphMetrics<'a>,
     pub(crate) ot_tables: OtTables<'a>,
     pub(crate) aat_tables: AatTables<'a>,
+    feature_variation_indices: [Option<u32>; 2],
+    #[cfg(feature = "std")]
+    plan_cache: &'a hb_shape_plan_cache_t,
+    #[cfg(feature = "std")]
+    glyph_name_index: &'a std::sync::OnceLock<GlyphNameIndex>,
 }
 
 impl<'a> crate::Shaper<'a> {
@@ -229,17 +488,167 @@ impl<'a> crate::Shaper<'a> {
         self.ot_tables.coords
     }
 
+    /// Returns the character map used for shaping.
+    pub fn charmap(&self) -> &Charmap<'a> {
+        &self.charmap
+    }
+
+    /// Returns the name of a glyph from the CFF charset or the `post` table.
+    pub fn glyph_name(&self, glyph: u32) -> Option<&'a str> {
+        self.glyph_names().get(glyph)
+    }
+
+    /// Returns the glyph with the given name.
+    ///
+    /// Like HarfBuzz's `hb_font_glyph_from_string`, the name is looked up in
+    /// the font's glyph names first, returning the lowest glyph if several
+    /// share it. Otherwise, a decimal glyph index and `gidDDD` are accepted,
+    /// as are `uniXXXX`, `uXXXX` to `uXXXXXX` and names of the Adobe Glyph
+    /// List For New Fonts, which are mapped to the nominal glyph of their
+    /// character. Names of several characters, like `uniXXXXYYYY` ligature
+    /// names, and the `afii` names of the legacy Adobe Glyph List are only
+    /// found among the font's names.
+    ///
+    /// With the `std` feature, the font's names are indexed on first use and
+    /// the index is shared by all shapers created from the same
+    /// [`ShaperData`]. Otherwise, every lookup scans all glyph names.
+    pub fn glyph_from_name(&self, name: &str) -> Option<u32> {
+        if name.is_empty() {
+            return None;
+        }
+        let names = self.glyph_names();
+        let num_glyphs = self.glyph_metrics.num_glyphs();
+        #[cfg(feature = "std")]
+        let glyph = self
+            .glyph_name_index
+            .get_or_init(|| GlyphNameIndex::new(&names, num_glyphs))
+            .get(name);
+        #[cfg(not(feature = "std"))]
+        let glyph = names
+            .names(num_glyphs)
+            .find(|(_, n)| *n == name)
+            .map(|(gid, _)| gid);
+        if glyph.is_some() {
+            return glyph;
+        }
+
+        let digits = name.strip_prefix("gid").unwrap_or(name);
+        if digits.bytes().all(|b| b.is_ascii_digit()) {
+            return digits.parse().ok().filter(|&gid| gid < num_glyphs);
+        }
+        self.charmap.map(glyph_name_to_char(name)?)
+    }
+
+    /// Returns the caret positions inside a ligature glyph, in font units.
+    ///
+    /// Carets are read from the GDEF `LigCaretList`, with variation deltas
+    /// applied for the current instance, or else from the AAT `lcar` table.
+    /// Device tables only adjust hinted sizes, so they are ignored, as in
+    /// positioning. Carets are x coordinates for horizontal directions and
+    /// y coordinates for vertical ones. Carets defined by contour points use
+    /// the `glyf` outline at the current instance, composites included, and
+    /// resolve to 0 when the point doesn't exist. Glyphs without caret data
+    /// have no carets.
+    pub fn ligature_carets(&self, glyph: u32, direction: Direction) -> Vec<i32> {
+        let glyph = GlyphId::new(glyph);
+        let mut carets = Vec::new();
+        if !ot_layout_gdef_table::get_lig_carets(self, direction, glyph, &mut carets) {
+            aat_layout_lcar_table::get_lig_carets(self, direction, glyph, &mut carets);
+        }
+        carets
+    }
+
+    /// Returns the advance of a glyph in font units for the current
+    /// instance.
+    ///
+    /// This is the `x_advance` of a glyph shaped in a horizontal direction
+    /// or the `y_advance` in a vertical one, before positioning, like
+    /// HarfBuzz's `hb_font_get_glyph_advance_for_direction`. Vertical
+    /// advances are negative, and fall back to the units per EM for fonts
+    /// without vertical metrics.
+    pub fn glyph_advance(&self, glyph: u32, direction: Direction) -> i32 {
+        let glyph = GlyphId::new(glyph);
+        if direction.is_horizontal() {
+            self.glyph_h_advance(glyph)
+        } else {
+            self.glyph_v_advance(glyph)
+        }
+    }
+
+    /// Writes the advances of `glyphs` to `advances`, like
+    /// [`glyph_advance`](Self::glyph_advance).
+    ///
+    /// Stops at the end of the shorter slice.
+    pub fn glyph_advances(&self, glyphs: &[u32], direction: Direction, advances: &mut [i32]) {
+        for (glyph, advance) in glyphs.iter().zip(advances) {
+            *advance = self.glyph_advance(*glyph, direction);
+        }
+    }
+
+    /// Returns the origin of a glyph for a direction in font units for the
+    /// current instance, relative to its horizontal origin.
+    ///
+    /// Glyphs shaped in a vertical direction are offset by minus this
+    /// origin, like HarfBuzz's `hb_font_get_glyph_origin_for_direction`. The
+    /// vertical origin is centered horizontally, and its height comes from
+    /// the `VORG` table, the `vmtx` top side bearing or the ascender. The
+    /// horizontal origin is always `(0, 0)`.
+    pub fn glyph_origin(&self, glyph: u32, direction: Direction) -> (i32, i32) {
+        let glyph = GlyphId::new(glyph);
+        if direction.is_horizontal() {
+            (0, 0)
+        } else {
+            (self.glyph_h_origin(glyph), self.glyph_v_origin(glyph))
+        }
+    }
+
+    /// Writes the origins of `glyphs` to `origins`, like
+    /// [`glyph_origin`](Self::glyph_origin).
+    ///
+    /// Stops at the end of the shorter slice.
+    pub fn glyph_origins(&self, glyphs: &[u32], direction: Direction, origins: &mut [(i32, i32)]) {
+        for (glyph, origin) in glyphs.iter().zip(origins) {
+            *origin = self.glyph_origin(*glyph, direction);
+        }
+    }
+
+    /// Returns the extents of a glyph in font units for the current
+    /// instance, relative to its horizontal origin.
+    ///
+    /// These are the extents the shaper uses for fallback mark positioning.
+    /// They are computed from `glyf` outlines, with `gvar` deltas at
+    /// non-default coordinates, or else from `CFF2` or `CFF` charstrings, in
+    /// which case they include curve control points, as in HarfBuzz. Glyphs
+    /// without contours have empty extents, and glyphs that don't exist
+    /// have none.
+    pub fn glyph_extents(&self, glyph: u32) -> Option<GlyphExtents> {
+        let mut extents = GlyphExtents::default();
+        self.get_glyph_extents(GlyphId::new(glyph), &mut extents)
+            .then_some(extents)
+    }
+
+    /// Writes the extents of `glyphs` to `extents`, like
+    /// [`glyph_extents`](Self::glyph_extents).
+    ///
+    /// Stops at the end of the shorter slice.
+    pub fn glyph_extents_batch(&self, glyphs: &[u32], extents: &mut [Option<GlyphExtents>]) {
+        for (glyph, extents) in glyphs.iter().zip(extents) {
+            *extents = self.glyph_extents(*glyph);
+        }
+    }
+
     /// Shapes the buffer content using provided font and features.
     ///
     /// Consumes the buffer. You can then run [`GlyphBuffer::clear`] to get the [`UnicodeBuffer`] back
     /// without allocating a new one.
     ///
-    /// If you plan to shape multiple strings, prefer [`shape_with_plan`](Self::shape_with_plan).
-    /// This is because [`ShapePlan`](crate::ShapePlan) initialization is pretty slow and should preferably
-    /// be called once for each shaping configuration.
+    /// With the `std` feature, the [`ShapePlan`](crate::ShapePlan) is taken from a cache
+    /// shared by all shapers created from the same [`ShaperData`]. Otherwise, a new plan
+    /// is created for every call, which is pretty slow, and
+    /// [`shape_with_plan`](Self::shape_with_plan) should be preferred when shaping
+    /// multiple strings.
     pub fn shape(&self, buffer: UnicodeBuffer, features: &[Feature]) -> GlyphBuffer {
-        let plan = ShapePlan::new(
-            self,
+        let plan = self.plan(
             buffer.0.direction,
             buffer.0.script,
             buffer.0.language.as_ref(),
@@ -248,6 +657,53 @@ impl<'a> crate::Shaper<'a> {
         self.shape_with_plan(&plan, buffer, features)
     }
 
+    /// Returns a plan for the given properties, from the plan cache when
+    /// available.
+    pub(crate) fn plan(
+        &self,
+        direction: Direction,
+        script: Option<Script>,
+        language: Option<&Language>,
+        features: &[Feature],
+    ) -> impl core::ops::Deref<Target = ShapePlan> {
+        #[cfg(feature = "std")]
+        {
+            self.cached_plan(direction, script, language, features)
+        }
+        #[cfg(not(feature = "std"))]
+        {
+            alloc::boxed::Box::new(ShapePlan::new(self, direction, script, language, features))
+        }
+    }
+
+    /// Returns a shape plan for the given properties from the plan cache,
+    /// creating it if necessary.
+    ///
+    /// The cache is shared by all shapers created from the same
+    /// [`ShaperData`] and holds a bounded number of recently used plans.
+    /// Plans are keyed the same way as HarfBuzz's
+    /// `hb_shape_plan_create_cached2`, so the ranges of non-global features
+    /// don't produce distinct plans.
+    #[cfg(feature = "std")]
+    pub fn cached_plan(
+        &self,
+        direction: Direction,
+        script: Option<Script>,
+        language: Option<&Language>,
+        features: &[Feature],
+    ) -> std::sync::Arc<ShapePlan> {
+        let variation_indices = self.feature_variation_indices();
+        self.plan_cache.get_or_create(
+            |key| key.matches(direction, script, language, features, variation_indices),
+            || {
+                (
+                    hb_shape_plan_key_t::new(self, direction, script, language, features),
+                    ShapePlan::new(self, direction, script, language, features),
+                )
+            },
+        )
+    }
+
     /// Shapes the buffer content using the provided font and plan.
     ///
     /// Consumes the buffer. You can then run [`GlyphBuffer::clear`] to get the [`UnicodeBuffer`] back
@@ -256,6 +712,11 @@ impl<'a> crate::Shaper<'a> {
     /// It is up to the caller to ensure that the shape plan matches the properties of the provided
     /// buffer, otherwise the shaping result will likely be incorrect.
     ///
+    /// A plan can be reused with any shaper created from the same font, regardless of its
+    /// variation coordinates. If the plan was created for an instance that selects different
+    /// feature variations, a plan matching this shaper's instance is used instead, taken from
+    /// the plan cache with the `std` feature.
+    ///
     /// # Panics
     ///
     /// Will panic when debugging assertions are enabled if the buffer and plan have mismatched
@@ -275,6 +736,19 @@ impl<'a> crate::Shaper<'a> {
             plan.script.unwrap_or(script::UNKNOWN)
         );
 
+        let instance_plan;
+        let plan = if plan.variation_indices == self.feature_variation_indices() {
+            plan
+        } else {
+            instance_plan = self.plan(
+                plan.direction,
+                plan.script,
+                plan.language.as_ref(),
+                features,
+            );
+            &*instance_plan
+        };
+
         if buffer.len > 0 {
             // Save the original direction, we use it later.
             let target_direction = buffer.direction;
@@ -300,11 +774,11 @@ impl<'a> crate::Shaper<'a> {
     }
 
     pub(crate) fn get_nominal_glyph(&self, c: u32) -> Option<GlyphId> {
-        self.charmap.map(c)
+        self.charmap.map_codepoint(c)
     }
 
     pub(crate) fn get_nominal_variant_glyph(&self, c: char, vs: char) -> Option<GlyphId> {
-        self.charmap.map_variant(c as u32, vs as u32)
+        self.charmap.map_variant(c, vs).map(GlyphId::new)
     }
 
     pub(crate) fn glyph_h_advance(&self, glyph: GlyphId) -> i32 {
@@ -330,7 +804,26 @@ impl<'a> crate::Shaper<'a> {
             .unwrap_or_default()
     }
 
-    pub(crate) fn glyph_extents(
+    pub(crate) fn glyph_contour_point_for_origin(
+        &self,
+        glyph: GlyphId,
+        point_index: u16,
+        direction: Direction,
+    ) -> Option<(i32, i32)> {
+        let (x, y) = self
+            .glyph_metrics
+            .contour_point(glyph, point_index, self.ot_tables.coords)?;
+        if direction.is_horizontal() {
+            Some((x, y))
+        } else {
+            Some((
+                x - self.glyph_h_origin(glyph),
+                y - self.glyph_v_origin(glyph),
+            ))
+        }
+    }
+
+    pub(crate) fn get_glyph_extents(
         &self,
         glyph: GlyphId,
         glyph_extents: &mut hb_glyph_extents_t,
@@ -351,16 +844,7 @@ impl<'a> crate::Shaper<'a> {
     }
 
     pub(crate) fn glyph_props(&self, glyph: GlyphId) -> u16 {
-        let glyph = glyph.to_u32();
-        match self.ot_tables.glyph_class(glyph) {
-            1 => GlyphPropsFlags::BASE_GLYPH.bits(),
-            2 => GlyphPropsFlags::LIGATURE.bits(),
-            3 => {
-                let class = self.ot_tables.glyph_mark_attachment_class(glyph);
-                (class << 8) | GlyphPropsFlags::MARK.bits()
-            }
-            _ => 0,
-        }
+        self.ot_tables.glyph_props(glyph.to_u32())
     }
 
     pub(crate) fn layout_table(&self, table_index: TableIndex) -> Option<LayoutTable<'a>> {
@@ -378,16 +862,48 @@ impl<'a> crate::Shaper<'a> {
         }
     }
 
+    /// Returns the index of the FeatureVariations record selected by the
+    /// current coordinates for GSUB and GPOS.
+    #[inline]
+    pub(crate) fn feature_variation_indices(&self) -> [Option<u32>; 2] {
+        self.feature_variation_indices
+    }
+
     pub(crate) fn layout_tables(&self) -> impl Iterator<Item = (TableIndex, LayoutTable<'a>)> + '_ {
         TableIndex::iter().filter_map(move |idx| self.layout_table(idx).map(|table| (idx, table)))
     }
 }
 
-#[derive(Clone, Copy, Default, bytemuck::Pod, bytemuck::Zeroable)]
+/// The extents of a font, as returned by
+/// [`Shaper::font_extents`](crate::Shaper::font_extents).
+///
+/// Values are in font units, along the y axis for horizontal directions and
+/// the x axis for vertical ones.
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub struct hb_font_extents_t {
+    /// Distance from the baseline to the top of the font, typically
+    /// positive.
+    pub ascender: i32,
+    /// Distance from the baseline to the bottom of the font, typically
+    /// negative.
+    pub descender: i32,
+    /// Suggested gap between lines.
+    pub line_gap: i32,
+}
+
+/// The extents of a glyph, as returned by
+/// [`Shaper::glyph_extents`](crate::Shaper::glyph_extents).
+///
+/// Values are in font units, with the y axis pointing up.
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, bytemuck::Pod, bytemuck::Zeroable)]
 #[repr(C)]
 pub struct hb_glyph_extents_t {
+    /// Distance from the glyph origin to the left edge of the glyph.
     pub x_bearing: i32,
+    /// Distance from the glyph origin to the top edge of the glyph.
     pub y_bearing: i32,
+    /// Width of the glyph.
     pub width: i32,
+    /// Height of the glyph, negative since it extends down from the top edge.
     pub height: i32,
 }
@@ -0,0 +1,302 @@
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use super::buffer::hb_buffer_t;
+use super::face::hb_font_t;
+use super::reshape::{cluster_starts, empty_like, is_independent_boundary};
+use crate::{Feature, GlyphBuffer, UnicodeBuffer};
+
+/// A run of glyphs shaped with one font, as returned by
+/// [`Shaper::shape_with_fallback`](crate::Shaper::shape_with_fallback).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FontRun {
+    /// Index of the font: 0 for the primary shaper and `i + 1` for the
+    /// fallback shaper at index `i`.
+    pub font: usize,
+    /// Byte range of the run in the text.
+    pub text: Range<usize>,
+    /// Range of the glyphs of the run in [`FallbackRuns::buffer`].
+    pub glyphs: Range<usize>,
+}
+
+/// The result of
+/// [`Shaper::shape_with_fallback`](crate::Shaper::shape_with_fallback).
+pub struct FallbackRuns {
+    /// The glyphs of all runs, in the order of a shaped buffer.
+    ///
+    /// Glyph ids are those of the font of the run they belong to, and cluster
+    /// values are byte offsets in the text.
+    pub buffer: GlyphBuffer,
+    /// The runs in glyph order, which is visual order for right-to-left and
+    /// bottom-to-top text. Adjacent runs have different fonts.
+    pub runs: Vec<FontRun>,
+}
+
+impl hb_font_t<'_> {
+    /// Shapes a buffer, reshaping text that the font has no glyphs for with
+    /// fallback fonts.
+    ///
+    /// `buffer` must be filled with `text` by
+    /// [`UnicodeBuffer::push_str`], so that cluster values are byte offsets
+    /// in `text`. `fallbacks` are tried in order.
+    ///
+    /// Every maximal span of clusters with `.notdef` glyphs is expanded to
+    /// boundaries that are not [unsafe to break](crate::GlyphInfo::unsafe_to_break)
+    /// and can't be joined by grapheme clusters or syllables, then reshaped
+    /// with the next font, with the surrounding text as context. Spans the
+    /// next font misses glyphs for are handled the same way with the font
+    /// after it. The result of a fallback font is only used if it has fewer
+    /// clusters with `.notdef` glyphs than the span it replaces, so text no
+    /// font supports keeps the `.notdef` glyphs of the earliest font.
+    ///
+    /// The [invisible glyph](UnicodeBuffer::set_invisible_glyph) and the
+    /// [not found variation selector
+    /// glyph](UnicodeBuffer::set_not_found_variation_selector_glyph) of
+    /// `buffer` are glyphs of this font, so they are not used by fallback
+    /// fonts.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `buffer` doesn't hold the characters of `text` with their
+    /// byte offsets as cluster values.
+    pub fn shape_with_fallback(
+        &self,
+        fallbacks: &[&hb_font_t],
+        mut buffer: UnicodeBuffer,
+        text: &str,
+        features: &[Feature],
+    ) -> FallbackRuns {
+        let infos = &buffer.0.info[..buffer.0.len];
+        let mut chars = text.char_indices();
+        let filled_from_text = infos.iter().all(|info| {
+            chars.next().is_some_and(|(offset, c)| {
+                info.glyph_id == c as u32 && info.cluster as usize == offset
+            })
+        }) && chars.next().is_none();
+        assert!(filled_from_text, "the buffer must be filled with the text");
+
+        buffer.guess_segment_properties();
+        // The template is only used to shape with fallback fonts, and the
+        // invisible and not found variation selector glyphs are glyphs of the
+        // primary font.
+        let mut template = empty_like(&buffer.0);
+        template.context = buffer.0.context;
+        template.context_len = buffer.0.context_len;
+        template.invisible = None;
+        template.not_found_variation_selector = None;
+
+        let mut fonts = Vec::with_capacity(fallbacks.len() + 1);
+        fonts.push(self);
+        fonts.extend_from_slice(fallbacks);
+        let fallback = Fallback {
+            fonts,
+            template: &template,
+            text,
+            features,
+        };
+
+        let shaped = self.shape(buffer, features).0;
+        let mut pieces = Vec::new();
+        fallback.resolve(
+            Piece {
+                font: 0,
+                text: 0..text.len(),
+                buffer: shaped,
+            },
+            &mut pieces,
+        );
+        if template.direction.is_backward() {
+            pieces.reverse();
+        }
+
+        let mut buffer = empty_like(&template);
+        buffer.context = template.context;
+        buffer.context_len = template.context_len;
+        let mut runs: Vec<FontRun> = Vec::new();
+        for piece in pieces {
+            let len = piece.buffer.len;
+            if len == 0 && piece.text.is_empty() {
+                continue;
+            }
+            let glyphs = buffer.info.len()..buffer.info.len() + len;
+            buffer.info.extend_from_slice(&piece.buffer.info[..len]);
+            buffer.pos.extend_from_slice(&piece.buffer.pos[..len]);
+            match runs.last_mut() {
+                Some(run) if run.font == piece.font => {
+                    run.text =
+                        run.text.start.min(piece.text.start)..run.text.end.max(piece.text.end);
+                    run.glyphs.end = glyphs.end;
+                }
+                _ => runs.push(FontRun {
+                    font: piece.font,
+                    text: piece.text,
+                    glyphs,
+                }),
+            }
+        }
+        buffer.len = buffer.info.len();
+        buffer.have_positions = true;
+
+        FallbackRuns {
+            buffer: GlyphBuffer(buffer),
+            runs,
+        }
+    }
+}
+
+/// Text shaped with one font.
+struct Piece {
+    font: usize,
+    text: Range<usize>,
+    buffer: hb_buffer_t,
+}
+
+impl Piece {
+    /// Returns the piece of the glyphs of `text`.
+    fn slice(&self, text: Range<usize>) -> Piece {
+        let mut buffer = empty_like(&self.buffer);
+        for (info, pos) in self.buffer.info[..self.buffer.len]
+            .iter()
+            .zip(&self.buffer.pos)
+        {
+            if text.contains(&(info.cluster as usize)) {
+                buffer.info.push(*info);
+                buffer.pos.push(*pos);
+            }
+        }
+        buffer.len = buffer.info.len();
+        Piece {
+            font: self.font,
+            text,
+            buffer,
+        }
+    }
+
+    /// Returns the number of clusters with `.notdef` glyphs.
+    fn missing_clusters(&self) -> usize {
+        let infos = &self.buffer.info[..self.buffer.len];
+        let mut count = 0;
+        let mut last = None;
+        for info in infos.iter().filter(|info| info.glyph_id == 0) {
+            if last != Some(info.cluster) {
+                count += 1;
+                last = Some(info.cluster);
+            }
+        }
+        count
+    }
+}
+
+struct Fallback<'a> {
+    fonts: Vec<&'a hb_font_t<'a>>,
+    template: &'a hb_buffer_t,
+    text: &'a str,
+    features: &'a [Feature],
+}
+
+impl Fallback<'_> {
+    /// Appends the pieces of `piece` to `out` in logical order, reshaping
+    /// its missing spans with the following fonts.
+    fn resolve(&self, piece: Piece, out: &mut Vec<Piece>) {
+        let font = piece.font + 1;
+        if font == self.fonts.len() || piece.missing_clusters() == 0 {
+            out.push(piece);
+            return;
+        }
+
+        let template = self.template;
+        let plan = self.fonts[font].plan(
+            template.direction,
+            template.script,
+            template.language.as_ref(),
+            self.features,
+        );
+        let mut cursor = piece.text.start;
+        for span in self.missing_spans(&piece) {
+            if span.start > cursor {
+                out.push(piece.slice(cursor..span.start));
+            }
+            cursor = span.end;
+
+            let original = piece.slice(span.clone());
+            let shaped = Piece {
+                font,
+                text: span.clone(),
+                buffer: self.fonts[font].shape_range(
+                    &plan,
+                    template,
+                    self.text,
+                    span,
+                    self.features,
+                ),
+            };
+            let mut pieces = Vec::new();
+            self.resolve(shaped, &mut pieces);
+            let missing: usize = pieces.iter().map(Piece::missing_clusters).sum();
+            if missing < original.missing_clusters() {
+                out.append(&mut pieces);
+            } else {
+                out.push(original);
+            }
+        }
+        if cursor < piece.text.end {
+            out.push(piece.slice(cursor..piece.text.end));
+        }
+    }
+
+    /// Returns the text ranges of the maximal spans of clusters with
+    /// `.notdef` glyphs, expanded to safe boundaries, in logical order.
+    fn missing_spans(&self, piece: &Piece) -> Vec<Range<usize>> {
+        let buffer = &piece.buffer;
+        let Some(starts) = cluster_starts(buffer, self.text) else {
+            return Vec::from([piece.text.clone()]);
+        };
+
+        let logical = |i: usize| {
+            if buffer.direction.is_backward() {
+                &buffer.info[buffer.len - 1 - i]
+            } else {
+                &buffer.info[i]
+            }
+        };
+        let missing = |c: usize| {
+            let end = starts.get(c + 1).map_or(buffer.len, |start| start.glyph);
+            (starts[c].glyph..end).any(|i| logical(i).glyph_id == 0)
+        };
+        let is_boundary = |c: usize| {
+            starts[c].safe_to_break && is_independent_boundary(self.text, starts[c].offset)
+        };
+        let offset = |c: usize| {
+            if c == 0 {
+                piece.text.start
+            } else {
+                starts.get(c).map_or(piece.text.end, |start| start.offset)
+            }
+        };
+
+        let mut spans: Vec<Range<usize>> = Vec::new();
+        let mut c = 0;
+        while c < starts.len() {
+            if !missing(c) {
+                c += 1;
+                continue;
+            }
+            let mut start = c;
+            while start > 0 && !is_boundary(start) {
+                start -= 1;
+            }
+            let mut end = c + 1;
+            while end < starts.len() && (missing(end) || !is_boundary(end)) {
+                end += 1;
+            }
+            c = end;
+
+            let span = offset(start)..offset(end);
+            match spans.last_mut() {
+                Some(last) if span.start <= last.end => last.end = span.end,
+                _ => spans.push(span),
+            }
+        }
+        spans
+    }
+}
@@ -1,13 +1,36 @@
 use crate::Tag;
+use alloc::vec::Vec;
+use core::ops::Range;
+#[cfg(not(feature = "std"))]
+use core_maths::CoreFloat;
 use read_fonts::{
     tables::{
-        glyf::Glyf, gvar::Gvar, hmtx::Hmtx, hvar::Hvar, loca::Loca, mvar::Mvar, vmtx::Vmtx,
-        vorg::Vorg, vvar::Vvar,
+        glyf::{Anchor, CompositeGlyphFlags, Glyf, Glyph},
+        gvar::Gvar,
+        hmtx::Hmtx,
+        hvar::Hvar,
+        loca::Loca,
+        mvar::Mvar,
+        postscript::{
+            charstring::{self, CommandSink},
+            dict::{self, Entry},
+            BlendState, Charset, FdSelect, Index,
+        },
+        variations::ItemVariationStore,
+        vmtx::Vmtx,
+        vorg::Vorg,
+        vvar::Vvar,
     },
-    types::{BoundingBox, F2Dot14, Fixed, GlyphId, Point},
-    FontRef, TableProvider,
+    types::{BigEndian, BoundingBox, F2Dot14, Fixed, GlyphId, Point},
+    FontData, FontRead, FontRef, TableProvider,
 };
 
+/// The maximum depth of nested composite glyphs, as in HarfBuzz.
+const MAX_NESTING_LEVEL: usize = 64;
+
+/// The number of phantom points that follow the points of a `glyf` outline.
+const PHANTOM_COUNT: usize = 4;
+
 #[derive(Clone)]
 pub(crate) struct GlyphMetrics<'a> {
     hmtx: Option<Hmtx<'a>>,
@@ -16,6 +39,7 @@ pub(crate) struct GlyphMetrics<'a> {
     vvar: Option<Vvar<'a>>,
     vorg: Option<Vorg<'a>>,
     glyf: Option<GlyfTables<'a>>,
+    cff: Option<CffTables<'a>>,
     mvar: Option<Mvar<'a>>,
     num_glyphs: u32,
     upem: u16,
@@ -51,6 +75,7 @@ impl<'a> GlyphMetrics<'a> {
         } else {
             None
         };
+        let cff = CffTables::new(font);
         let mvar = font.mvar().ok();
         let (ascent, descent) = if let Ok(os2) = font.os2() {
             (os2.s_typo_ascender(), os2.s_typo_descender())
@@ -66,6 +91,7 @@ impl<'a> GlyphMetrics<'a> {
             vvar,
             vorg,
             glyf,
+            cff,
             mvar,
             num_glyphs,
             upem,
@@ -74,6 +100,10 @@ impl<'a> GlyphMetrics<'a> {
         }
     }
 
+    pub fn num_glyphs(&self) -> u32 {
+        self.num_glyphs
+    }
+
     pub fn advance_width(&self, gid: impl Into<GlyphId>, coords: &[F2Dot14]) -> Option<i32> {
         let gid = gid.into();
         let Some(mut advance) = self
@@ -149,8 +179,13 @@ impl<'a> GlyphMetrics<'a> {
         if !coords.is_empty() {
             if let Some(vvar) = self.vvar.as_ref() {
                 bearing += vvar.tsb_delta(gid, coords).unwrap_or_default().to_i32();
-            } else if let Some(deltas) = self.phantom_deltas(gid, coords) {
-                bearing += deltas[3].y.to_i32();
+            } else if let Some(glyf) = self.glyf.as_ref() {
+                // Measure from the varied top phantom point to the varied
+                // outline, as HarfBuzz does without VVAR.
+                let mut points = Vec::new();
+                self.glyf_points(glyf, gid, coords, 0, &mut points)?;
+                let top = hb_round(points[points.len() - PHANTOM_COUNT + 2].y) as i32;
+                bearing = top - self.extents(gid, coords)?.y_max;
             }
         }
         Some(bearing)
@@ -220,23 +255,205 @@ impl<'a> GlyphMetrics<'a> {
 
     pub fn extents(&self, gid: impl Into<GlyphId>, coords: &[F2Dot14]) -> Option<BoundingBox<i32>> {
         let gid = gid.into();
-        let glyf = self.glyf.as_ref()?;
+        let Some(glyf) = self.glyf.as_ref() else {
+            return self.cff.as_ref()?.extents(gid, coords);
+        };
         let glyph = glyf.loca.get_glyf(gid, &glyf.glyf).ok()?;
         let Some(glyph) = glyph else {
             // Return empty extents for empty glyph
             return Some(BoundingBox::default());
         };
-        if !coords.is_empty() {
-            return None; // TODO https://github.com/harfbuzz/harfrust/pull/52#issuecomment-2878117808
+        if coords.is_empty() {
+            return Some(BoundingBox {
+                x_min: glyph.x_min() as i32,
+                x_max: glyph.x_max() as i32,
+                y_min: glyph.y_min() as i32,
+                y_max: glyph.y_max() as i32,
+            });
+        }
+        // Header bounds don't vary, so measure the varied outline instead.
+        let mut points = Vec::new();
+        self.glyf_points(glyf, gid, coords, 0, &mut points)?;
+        let outline = &points[..points.len() - PHANTOM_COUNT];
+        let (mut min_x, mut min_y) = (f32::MAX, f32::MAX);
+        let (mut max_x, mut max_y) = (f32::MIN, f32::MIN);
+        for point in outline {
+            min_x = min_x.min(point.x);
+            min_y = min_y.min(point.y);
+            max_x = max_x.max(point.x);
+            max_y = max_y.max(point.y);
+        }
+        if min_x >= max_x || min_y >= max_y {
+            return Some(BoundingBox::default());
         }
+        let x_min = hb_round(min_x);
+        let y_max = hb_round(max_y);
         Some(BoundingBox {
-            x_min: glyph.x_min() as i32,
-            x_max: glyph.x_max() as i32,
-            y_min: glyph.y_min() as i32,
-            y_max: glyph.y_max() as i32,
+            x_min: x_min as i32,
+            x_max: (x_min + hb_round(max_x - x_min)) as i32,
+            y_min: (y_max + hb_round(min_y - y_max)) as i32,
+            y_max: y_max as i32,
         })
     }
 
+    pub fn contour_point(
+        &self,
+        gid: impl Into<GlyphId>,
+        point_index: u16,
+        coords: &[F2Dot14],
+    ) -> Option<(i32, i32)> {
+        let glyf = self.glyf.as_ref()?;
+        let mut points = Vec::new();
+        self.glyf_points(glyf, gid.into(), coords, 0, &mut points)?;
+        let outline = &points[..points.len() - PHANTOM_COUNT];
+        let point = outline.get(usize::from(point_index))?;
+        Some((hb_round(point.x) as i32, hb_round(point.y) as i32))
+    }
+
+    /// Appends the points of a `glyf` outline at `coords` to `all_points`,
+    /// followed by its four phantom points, like HarfBuzz's
+    /// `Glyph::get_points`.
+    ///
+    /// Composite glyphs are flattened into the points of their components.
+    fn glyf_points(
+        &self,
+        glyf: &GlyfTables<'a>,
+        gid: GlyphId,
+        coords: &[F2Dot14],
+        depth: usize,
+        all_points: &mut Vec<Point<f32>>,
+    ) -> Option<()> {
+        if depth > MAX_NESTING_LEVEL {
+            return None;
+        }
+        let glyph = glyf.loca.get_glyf(gid, &glyf.glyf).ok()?;
+        let mut points = Vec::new();
+        let mut end_points: &[BigEndian<u16>] = &[];
+        match &glyph {
+            Some(Glyph::Simple(simple)) => {
+                points.extend(
+                    simple
+                        .points()
+                        .map(|point| Point::new(f32::from(point.x), f32::from(point.y))),
+                );
+                end_points = simple.end_pts_of_contours();
+            }
+            Some(Glyph::Composite(composite)) => {
+                // One point per component, holding its offset, so that gvar
+                // can vary it.
+                points.extend(
+                    composite
+                        .components()
+                        .map(|component| match component.anchor {
+                            Anchor::Offset { x, y } => Point::new(f32::from(x), f32::from(y)),
+                            Anchor::Point { .. } => Point::default(),
+                        }),
+                );
+            }
+            None => {}
+        }
+
+        let (x_min, y_max) = glyph
+            .as_ref()
+            .map(|glyph| (i32::from(glyph.x_min()), i32::from(glyph.y_max())))
+            .unwrap_or_default();
+        let hmtx = self.hmtx.as_ref();
+        let h_delta = hmtx
+            .and_then(|hmtx| hmtx.side_bearing(gid))
+            .map_or(0, |lsb| x_min - i32::from(lsb));
+        let h_advance = hmtx.and_then(|hmtx| hmtx.advance(gid)).map_or(0, i32::from);
+        let tsb = self
+            .vmtx
+            .as_ref()
+            .and_then(|vmtx| vmtx.side_bearing(gid))
+            .map_or(0, i32::from);
+        let v_origin = y_max + tsb;
+        let v_advance = self.advance_height(gid, &[]).unwrap_or_default();
+        points.extend([
+            Point::new(h_delta as f32, 0.0),
+            Point::new((h_advance + h_delta) as f32, 0.0),
+            Point::new(0.0, v_origin as f32),
+            Point::new(0.0, (v_origin - v_advance) as f32),
+        ]);
+
+        if !coords.is_empty() {
+            glyf.apply_deltas(gid, coords, end_points, &mut points);
+        }
+
+        let base = all_points.len();
+        if let Some(Glyph::Composite(composite)) = &glyph {
+            let mut phantoms = [Point::default(); PHANTOM_COUNT];
+            phantoms.copy_from_slice(&points[points.len() - PHANTOM_COUNT..]);
+            let mut component_points = Vec::new();
+            for (component, offset) in composite.components().zip(&points) {
+                component_points.clear();
+                self.glyf_points(
+                    glyf,
+                    component.glyph.into(),
+                    coords,
+                    depth + 1,
+                    &mut component_points,
+                )?;
+                let count = component_points.len() - PHANTOM_COUNT;
+                if component
+                    .flags
+                    .contains(CompositeGlyphFlags::USE_MY_METRICS)
+                {
+                    phantoms.copy_from_slice(&component_points[count..]);
+                }
+
+                let transform = component.transform;
+                let [xx, yx, xy, yy] =
+                    [transform.xx, transform.yx, transform.xy, transform.yy].map(F2Dot14::to_f32);
+                let scaled_offsets = component.flags
+                    & (CompositeGlyphFlags::SCALED_COMPONENT_OFFSET
+                        | CompositeGlyphFlags::UNSCALED_COMPONENT_OFFSET)
+                    == CompositeGlyphFlags::SCALED_COMPONENT_OFFSET;
+                for point in &mut component_points {
+                    if scaled_offsets {
+                        *point += *offset;
+                    }
+                    *point = Point::new(point.x * xx + point.y * xy, point.x * yx + point.y * yy);
+                    if !scaled_offsets {
+                        *point += *offset;
+                    }
+                }
+
+                if let Anchor::Point {
+                    base: base_point,
+                    component: component_point,
+                } = component.anchor
+                {
+                    let parent = all_points[base..].get(usize::from(base_point));
+                    let child = component_points[..count].get(usize::from(component_point));
+                    if let (Some(&parent), Some(&child)) = (parent, child) {
+                        let delta = parent - child;
+                        for point in &mut component_points {
+                            *point += delta;
+                        }
+                    }
+                }
+
+                all_points.extend_from_slice(&component_points[..count]);
+            }
+            all_points.extend_from_slice(&phantoms);
+        } else {
+            all_points.extend_from_slice(&points);
+        }
+
+        if depth == 0 {
+            // Like rasterizers, shift the outline by the varied left side
+            // bearing.
+            let shift = all_points[all_points.len() - PHANTOM_COUNT].x;
+            if shift != 0.0 {
+                for point in &mut all_points[base..] {
+                    point.x -= shift;
+                }
+            }
+        }
+        Some(())
+    }
+
     fn phantom_deltas(&self, gid: GlyphId, coords: &[F2Dot14]) -> Option<[Point<Fixed>; 4]> {
         let glyf = self.glyf.as_ref()?;
         let gvar = glyf.gvar.as_ref()?;
@@ -244,3 +461,492 @@ impl<'a> GlyphMetrics<'a> {
             .ok()?
     }
 }
+
+impl GlyfTables<'_> {
+    /// Adds the gvar deltas of `gid` at `coords` to `points`, inferring the
+    /// deltas of untouched points from the contours ending at `end_points`.
+    fn apply_deltas(
+        &self,
+        gid: GlyphId,
+        coords: &[F2Dot14],
+        end_points: &[BigEndian<u16>],
+        points: &mut [Point<f32>],
+    ) -> Option<()> {
+        let var_data = self.gvar.as_ref()?.glyph_variation_data(gid).ok()??;
+        let orig_points = points.to_vec();
+        let mut deltas = Vec::new();
+        let mut touched = Vec::new();
+        for (tuple, scalar) in var_data.active_tuples_at(coords) {
+            let scalar = scalar.to_f32();
+            if tuple.has_deltas_for_all_points() {
+                for delta in tuple.deltas() {
+                    if let Some(point) = points.get_mut(usize::from(delta.position)) {
+                        point.x += delta.x_delta as f32 * scalar;
+                        point.y += delta.y_delta as f32 * scalar;
+                    }
+                }
+                continue;
+            }
+
+            deltas.clear();
+            deltas.resize(points.len(), Point::default());
+            touched.clear();
+            touched.resize(points.len(), false);
+            for delta in tuple.deltas() {
+                let index = usize::from(delta.position);
+                if let Some(point_delta) = deltas.get_mut(index) {
+                    point_delta.x += delta.x_delta as f32 * scalar;
+                    point_delta.y += delta.y_delta as f32 * scalar;
+                    touched[index] = true;
+                }
+            }
+            let mut start = 0;
+            for end in end_points {
+                let end = usize::from(end.get());
+                if end < start || end >= points.len() {
+                    break;
+                }
+                infer_deltas(
+                    &orig_points[start..=end],
+                    &mut deltas[start..=end],
+                    &touched[start..=end],
+                );
+                start = end + 1;
+            }
+            for (point, delta) in points.iter_mut().zip(&deltas) {
+                *point += *delta;
+            }
+        }
+        Some(())
+    }
+}
+
+/// Interpolates the deltas of the untouched points of a contour between
+/// their touched neighbours, like HarfBuzz's `gvar::infer_deltas`.
+fn infer_deltas(orig_points: &[Point<f32>], deltas: &mut [Point<f32>], touched: &[bool]) {
+    let Some(first) = touched.iter().position(|&touched| touched) else {
+        return;
+    };
+    let len = touched.len();
+    let mut prev = first;
+    loop {
+        let mut next = (prev + 1) % len;
+        while !touched[next] {
+            next = (next + 1) % len;
+        }
+        let mut i = (prev + 1) % len;
+        while i != next {
+            deltas[i] = Point::new(
+                infer_delta(
+                    orig_points[i].x,
+                    orig_points[prev].x,
+                    orig_points[next].x,
+                    deltas[prev].x,
+                    deltas[next].x,
+                ),
+                infer_delta(
+                    orig_points[i].y,
+                    orig_points[prev].y,
+                    orig_points[next].y,
+                    deltas[prev].y,
+                    deltas[next].y,
+                ),
+            );
+            i = (i + 1) % len;
+        }
+        prev = next;
+        if prev == first {
+            break;
+        }
+    }
+}
+
+fn infer_delta(target: f32, prev: f32, next: f32, prev_delta: f32, next_delta: f32) -> f32 {
+    if prev == next {
+        if prev_delta == next_delta {
+            prev_delta
+        } else {
+            0.0
+        }
+    } else if target <= prev.min(next) {
+        if prev < next {
+            prev_delta
+        } else {
+            next_delta
+        }
+    } else if target >= prev.max(next) {
+        if prev > next {
+            prev_delta
+        } else {
+            next_delta
+        }
+    } else {
+        prev_delta + (target - prev) / (next - prev) * (next_delta - prev_delta)
+    }
+}
+
+/// Rounds like HarfBuzz's `roundf`, which rounds halves up rather than away
+/// from zero.
+fn hb_round(value: f32) -> f32 {
+    (value + 0.5).floor()
+}
+
+/// The charstrings of a `CFF` or `CFF2` table and the dictionaries needed to
+/// evaluate them.
+#[derive(Clone)]
+struct CffTables<'a> {
+    data: &'a [u8],
+    is_cff2: bool,
+    global_subrs: Index<'a>,
+    charstrings: Index<'a>,
+    font_dicts: Index<'a>,
+    fd_select: Option<FdSelect<'a>>,
+    private_dict_range: Range<usize>,
+    var_store: Option<ItemVariationStore<'a>>,
+    /// The charset of a CFF font that isn't CID-keyed, for `seac`.
+    charset: Option<Charset<'a>>,
+}
+
+impl<'a> CffTables<'a> {
+    fn new(font: &FontRef<'a>) -> Option<Self> {
+        let (data, top_dict, global_subrs, is_cff2) = if let Ok(cff2) = font.cff2() {
+            (
+                cff2.offset_data().as_bytes(),
+                cff2.top_dict_data(),
+                Index::from(cff2.global_subrs()),
+                true,
+            )
+        } else {
+            let cff = font.cff().ok()?;
+            (
+                cff.offset_data().as_bytes(),
+                cff.top_dicts().get(0).ok()?,
+                Index::from(cff.global_subrs()),
+                false,
+            )
+        };
+        let mut tables = Self {
+            data,
+            is_cff2,
+            global_subrs,
+            charstrings: Index::Empty,
+            font_dicts: Index::Empty,
+            fd_select: None,
+            private_dict_range: 0..0,
+            var_store: None,
+            charset: None,
+        };
+        let mut charset_offset = 0;
+        let mut is_cid = false;
+        for entry in dict::entries(top_dict, None) {
+            match entry.ok()? {
+                Entry::CharstringsOffset(offset) => {
+                    tables.charstrings = Index::new(data.get(offset..)?, is_cff2).ok()?;
+                }
+                Entry::PrivateDictRange(range) => tables.private_dict_range = range,
+                Entry::FdArrayOffset(offset) => {
+                    tables.font_dicts = Index::new(data.get(offset..)?, is_cff2).ok()?;
+                }
+                Entry::FdSelectOffset(offset) => {
+                    tables.fd_select = FdSelect::read(FontData::new(data.get(offset..)?)).ok();
+                }
+                Entry::VariationStoreOffset(offset) if is_cff2 => {
+                    // The store is preceded by its length.
+                    let store = FontData::new(data.get(offset.checked_add(2)?..)?);
+                    tables.var_store = ItemVariationStore::read(store).ok();
+                }
+                Entry::Charset(offset) => charset_offset = offset,
+                Entry::Ros { .. } => is_cid = true,
+                _ => {}
+            }
+        }
+        if !is_cff2 && !is_cid {
+            let num_glyphs = tables.charstrings.count();
+            tables.charset = Charset::new(FontData::new(data), charset_offset, num_glyphs).ok();
+        }
+        Some(tables)
+    }
+
+    /// Returns the bounds of the outline of `gid` at `coords`, including
+    /// curve control points, like HarfBuzz's CFF extents.
+    fn extents(&self, gid: GlyphId, coords: &[F2Dot14]) -> Option<BoundingBox<i32>> {
+        Some(self.bounds(gid, coords, false)?.finish())
+    }
+
+    fn bounds(&self, gid: GlyphId, coords: &[F2Dot14], in_seac: bool) -> Option<CffBounds> {
+        let charstring = self.charstrings.get(gid.to_u32() as usize).ok()?;
+        let private_dict_range = if self.font_dicts.count() != 0 {
+            let font_index = match &self.fd_select {
+                Some(fd_select) => fd_select.font_index(gid)?,
+                None => 0,
+            };
+            let font_dict = self.font_dicts.get(usize::from(font_index)).ok()?;
+            dict::entries(font_dict, None).find_map(|entry| match entry {
+                Ok(Entry::PrivateDictRange(range)) => Some(range),
+                _ => None,
+            })?
+        } else {
+            self.private_dict_range.clone()
+        };
+        let blend_state = |store_index| {
+            let store = self.var_store.clone()?;
+            BlendState::new(store, coords, store_index).ok()
+        };
+
+        let private_dict = self.data.get(private_dict_range.clone())?;
+        let mut subrs = None;
+        let mut store_index = 0;
+        for entry in dict::entries(private_dict, blend_state(0)) {
+            match entry.ok()? {
+                Entry::SubrsOffset(offset) => {
+                    let start = private_dict_range.start.checked_add(offset)?;
+                    subrs = Some(Index::new(self.data.get(start..)?, self.is_cff2).ok()?);
+                }
+                Entry::VariationStoreIndex(index) => store_index = index,
+                _ => {}
+            }
+        }
+
+        let mut bounds = CffBounds::default();
+        charstring::evaluate(
+            charstring,
+            self.global_subrs.clone(),
+            subrs,
+            blend_state(store_index),
+            &mut bounds,
+        )
+        .ok()?;
+
+        // The charstring evaluator ignores the `seac` form of `endchar`,
+        // which places an accent glyph over a base glyph.
+        if let Some(([dx, dy], [base, accent])) = self
+            .charset
+            .as_ref()
+            .and_then(|_| seac_components(charstring))
+        {
+            if in_seac {
+                return None;
+            }
+            let base = self.standard_code_to_glyph(base)?;
+            let accent = self.standard_code_to_glyph(accent)?;
+            bounds.merge(&self.bounds(base, coords, true)?, Fixed::ZERO, Fixed::ZERO);
+            bounds.merge(&self.bounds(accent, coords, true)?, dx, dy);
+        }
+        Some(bounds)
+    }
+
+    /// Returns the glyph named by a code of the Standard Encoding.
+    fn standard_code_to_glyph(&self, code: u8) -> Option<GlyphId> {
+        let sid = STANDARD_ENCODING_TO_SID[usize::from(code)];
+        if sid == 0 {
+            return None;
+        }
+        self.charset
+            .as_ref()?
+            .iter()
+            .find(|(_, string_id)| string_id.to_u16() == u16::from(sid))
+            .map(|(gid, _)| gid)
+    }
+}
+
+/// Returns the accent offset and the Standard Encoding codes of the base and
+/// accent glyphs of a charstring that ends with the `seac` form of
+/// `endchar`.
+///
+/// Only the top level of the charstring is scanned, which is where `seac`
+/// appears in practice.
+fn seac_components(charstring: &[u8]) -> Option<([Fixed; 2], [u8; 2])> {
+    // The last four operands and the number of operands on the stack.
+    let mut args = [Fixed::ZERO; 4];
+    let mut count = 0;
+    let mut stems = 0;
+    let mut i = 0;
+    while let Some(&b0) = charstring.get(i) {
+        i += 1;
+        let operand = match b0 {
+            28 => {
+                let bytes = charstring.get(i..i + 2)?;
+                i += 2;
+                Fixed::from_i32(i32::from(i16::from_be_bytes([bytes[0], bytes[1]])))
+            }
+            32..=246 => Fixed::from_i32(i32::from(b0) - 139),
+            247..=250 => {
+                let b1 = *charstring.get(i)?;
+                i += 1;
+                Fixed::from_i32((i32::from(b0) - 247) * 256 + i32::from(b1) + 108)
+            }
+            251..=254 => {
+                let b1 = *charstring.get(i)?;
+                i += 1;
+                Fixed::from_i32(-(i32::from(b0) - 251) * 256 - i32::from(b1) - 108)
+            }
+            255 => {
+                let bytes = charstring.get(i..i + 4)?;
+                i += 4;
+                Fixed::from_bits(i32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+            }
+            // hstem, vstem, hstemhm and vstemhm
+            1 | 3 | 18 | 23 => {
+                stems += count / 2;
+                count = 0;
+                continue;
+            }
+            // hintmask and cntrmask, with an implied vstemhm
+            19 | 20 => {
+                stems += count / 2;
+                count = 0;
+                i += usize::div_ceil(stems, 8);
+                continue;
+            }
+            // endchar
+            14 => {
+                if count < 4 {
+                    return None;
+                }
+                let code = |arg: Fixed| u8::try_from(arg.to_i32()).ok();
+                return Some(([args[0], args[1]], [code(args[2])?, code(args[3])?]));
+            }
+            // callsubr and callgsubr
+            10 | 29 => return None,
+            12 => {
+                i += 1;
+                count = 0;
+                continue;
+            }
+            _ => {
+                count = 0;
+                continue;
+            }
+        };
+        args.rotate_left(1);
+        args[3] = operand;
+        count += 1;
+    }
+    None
+}
+
+/// Maps the codes of the Standard Encoding to standard string identifiers.
+///
+/// See <https://adobe-type-tools.github.io/font-tech-notes/pdfs/5176.CFF.pdf#page=37>
+#[rustfmt::skip]
+const STANDARD_ENCODING_TO_SID: [u8; 256] = [
+      0,   0,   0,   0,   0,   0,   0,   0,   0,   0,   0,   0,   0,   0,   0,   0,
+      0,   0,   0,   0,   0,   0,   0,   0,   0,   0,   0,   0,   0,   0,   0,   0,
+      1,   2,   3,   4,   5,   6,   7,   8,   9,  10,  11,  12,  13,  14,  15,  16,
+     17,  18,  19,  20,  21,  22,  23,  24,  25,  26,  27,  28,  29,  30,  31,  32,
+     33,  34,  35,  36,  37,  38,  39,  40,  41,  42,  43,  44,  45,  46,  47,  48,
+     49,  50,  51,  52,  53,  54,  55,  56,  57,  58,  59,  60,  61,  62,  63,  64,
+     65,  66,  67,  68,  69,  70,  71,  72,  73,  74,  75,  76,  77,  78,  79,  80,
+     81,  82,  83,  84,  85,  86,  87,  88,  89,  90,  91,  92,  93,  94,  95,   0,
+      0,   0,   0,   0,   0,   0,   0,   0,   0,   0,   0,   0,   0,   0,   0,   0,
+      0,   0,   0,   0,   0,   0,   0,   0,   0,   0,   0,   0,   0,   0,   0,   0,
+      0,  96,  97,  98,  99, 100, 101, 102, 103, 104, 105, 106, 107, 108, 109, 110,
+      0, 111, 112, 113, 114,   0, 115, 116, 117, 118, 119, 120, 121, 122,   0, 123,
+      0, 124, 125, 126, 127, 128, 129, 130, 131,   0, 132, 133,   0, 134, 135, 136,
+    137,   0,   0,   0,   0,   0,   0,   0,   0,   0,   0,   0,   0,   0,   0,   0,
+      0, 138,   0, 139,   0,   0,   0,   0, 140, 141, 142, 143,   0,   0,   0,   0,
+      0, 144,   0,   0,   0, 145,   0,   0, 146, 147, 148, 149,   0,   0,   0,   0,
+];
+
+/// Accumulates the bounds of the points of a charstring.
+#[derive(Default)]
+struct CffBounds {
+    x: Fixed,
+    y: Fixed,
+    is_open: bool,
+    bounds: Option<[Fixed; 4]>,
+}
+
+impl CffBounds {
+    fn add(&mut self, x: Fixed, y: Fixed) {
+        let [x_min, y_min, x_max, y_max] = self.bounds.get_or_insert([x, y, x, y]);
+        *x_min = (*x_min).min(x);
+        *y_min = (*y_min).min(y);
+        *x_max = (*x_max).max(x);
+        *y_max = (*y_max).max(y);
+    }
+
+    /// Adds the bounds of `other`, offset by `dx` and `dy`.
+    fn merge(&mut self, other: &Self, dx: Fixed, dy: Fixed) {
+        if let Some([x_min, y_min, x_max, y_max]) = other.bounds {
+            self.add(x_min + dx, y_min + dy);
+            self.add(x_max + dx, y_max + dy);
+        }
+    }
+
+    /// Adds the current point when a path starts drawing.
+    fn open(&mut self) {
+        if !self.is_open {
+            self.is_open = true;
+            self.add(self.x, self.y);
+        }
+    }
+
+    /// Rounds the bounds like HarfBuzz: the bearings are rounded, and the
+    /// width and height are rounded from the rounded bearings.
+    fn finish(self) -> BoundingBox<i32> {
+        let [x_min, y_min, x_max, y_max] = self.bounds.unwrap_or_default();
+        let mut bounds = BoundingBox::default();
+        if x_min < x_max {
+            let x_bearing = hb_round(x_min.to_f32());
+            bounds.x_min = x_bearing as i32;
+            bounds.x_max = (x_bearing + hb_round(x_max.to_f32() - x_bearing)) as i32;
+        }
+        if y_min < y_max {
+            let y_bearing = hb_round(y_max.to_f32());
+            bounds.y_min = (y_bearing + hb_round(y_min.to_f32() - y_bearing)) as i32;
+            bounds.y_max = y_bearing as i32;
+        }
+        bounds
+    }
+}
+
+impl CommandSink for CffBounds {
+    fn move_to(&mut self, x: Fixed, y: Fixed) {
+        self.is_open = false;
+        self.x = x;
+        self.y = y;
+    }
+
+    fn line_to(&mut self, x: Fixed, y: Fixed) {
+        self.open();
+        self.add(x, y);
+        self.x = x;
+        self.y = y;
+    }
+
+    fn curve_to(&mut self, cx0: Fixed, cy0: Fixed, cx1: Fixed, cy1: Fixed, x: Fixed, y: Fixed) {
+        self.open();
+        self.add(cx0, cy0);
+        self.add(cx1, cy1);
+        self.add(x, y);
+        self.x = x;
+        self.y = y;
+    }
+
+    fn close(&mut self) {
+        self.is_open = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cff_bounds_round_halves_up() {
+        let mut bounds = CffBounds::default();
+        bounds.add(Fixed::from_f64(-3.5), Fixed::from_f64(-10.5));
+        bounds.add(Fixed::from_f64(4.5), Fixed::from_f64(7.5));
+        // The width and height are rounded from the rounded bearings.
+        assert_eq!(
+            bounds.finish(),
+            BoundingBox {
+                x_min: -3,
+                y_min: -10,
+                x_max: 5,
+                y_max: 8,
+            }
+        );
+    }
+}
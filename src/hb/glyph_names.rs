@@ -1,5 +1,15 @@
+#[cfg(feature = "std")]
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use super::glyph_names_table::AGLFN;
 use read_fonts::{
-    tables::{cff::Cff, post::Post, postscript::Charset},
+    tables::{
+        cff::Cff,
+        post::{Post, DEFAULT_GLYPH_NAMES},
+        postscript::Charset,
+    },
+    types::Version16Dot16,
     FontRef, TableProvider,
 };
 
@@ -25,18 +35,124 @@ impl<'a> GlyphNames<'a> {
         }
     }
 
-    pub fn get(&self, glyph_id: u32) -> Option<&str> {
+    pub fn get(&self, glyph_id: u32) -> Option<&'a str> {
+        self.get_with(glyph_id, None)
+    }
+
+    /// Returns the names of the glyphs below `num_glyphs` that have one.
+    pub fn names(&self, num_glyphs: u32) -> impl Iterator<Item = (u32, &'a str)> + '_ {
+        // The `post` string data can only be walked from the start, so
+        // collect it once instead of for every glyph.
+        let strings: Option<Vec<&'a str>> = match self {
+            Self::Post(post) if post.version() == Version16Dot16::VERSION_2_0 => {
+                post.string_data().map(|data| {
+                    data.iter()
+                        .map_while(|s| s.ok())
+                        .map(|s| s.as_str())
+                        .collect()
+                })
+            }
+            _ => None,
+        };
+        (0..num_glyphs).filter_map(move |gid| Some((gid, self.get_with(gid, strings.as_deref())?)))
+    }
+
+    fn get_with(&self, glyph_id: u32, strings: Option<&[&'a str]>) -> Option<&'a str> {
         let name = match self {
             Self::Cff(cff, charset) => {
                 let sid = charset.string_id(glyph_id.into()).ok()?;
                 core::str::from_utf8(cff.string(sid)?.bytes()).ok()
             }
             Self::Post(post) => {
-                let gid: u16 = glyph_id.try_into().ok()?;
-                post.glyph_name(gid.into())
+                let gid = usize::try_from(glyph_id).ok()?;
+                match post.version() {
+                    Version16Dot16::VERSION_1_0 => DEFAULT_GLYPH_NAMES.get(gid).copied(),
+                    Version16Dot16::VERSION_2_0 => {
+                        let index = post.glyph_name_index()?.get(gid)?.get() as usize;
+                        match index.checked_sub(DEFAULT_GLYPH_NAMES.len()) {
+                            None => Some(DEFAULT_GLYPH_NAMES[index]),
+                            Some(index) => match strings {
+                                Some(strings) => strings.get(index).copied(),
+                                None => post.string_data()?.get(index)?.ok().map(|s| s.as_str()),
+                            },
+                        }
+                    }
+                    _ => None,
+                }
             }
             Self::None => None,
         }?;
         (!name.is_empty()).then_some(name)
     }
 }
+
+/// Glyph names of a font sorted for lookup by name.
+#[cfg(feature = "std")]
+pub(crate) struct GlyphNameIndex {
+    /// All names, concatenated.
+    names: String,
+    /// Start and end of a name in `names` and its glyph, sorted by name and
+    /// then by glyph.
+    entries: Vec<(u32, u32, u32)>,
+}
+
+#[cfg(feature = "std")]
+impl GlyphNameIndex {
+    pub fn new(names: &GlyphNames, num_glyphs: u32) -> Self {
+        let mut index = Self {
+            names: String::new(),
+            entries: Vec::new(),
+        };
+        for (gid, name) in names.names(num_glyphs) {
+            let start = index.names.len() as u32;
+            index.names.push_str(name);
+            index.entries.push((start, index.names.len() as u32, gid));
+        }
+        let names = &index.names;
+        index.entries.sort_unstable_by(|a, b| {
+            names[a.0 as usize..a.1 as usize]
+                .cmp(&names[b.0 as usize..b.1 as usize])
+                .then(a.2.cmp(&b.2))
+        });
+        index
+    }
+
+    /// Returns the first glyph with the given name.
+    pub fn get(&self, name: &str) -> Option<u32> {
+        let name_of =
+            |&(start, end, _): &(u32, u32, u32)| &self.names[start as usize..end as usize];
+        let i = self.entries.partition_point(|entry| name_of(entry) < name);
+        let entry = self.entries.get(i)?;
+        (name_of(entry) == name).then_some(entry.2)
+    }
+}
+
+/// Returns the character a glyph name stands for by convention: `uniXXXX`,
+/// `uXXXX` to `uXXXXXX` or a name of the Adobe Glyph List For New Fonts.
+///
+/// Names of several characters, like `uniXXXXYYYY` or `f_i`, and the `afii`
+/// names of the legacy Adobe Glyph List return `None`.
+pub(crate) fn glyph_name_to_char(name: &str) -> Option<char> {
+    let hex = |digits: &str| {
+        if digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+            u32::from_str_radix(digits, 16).ok()
+        } else {
+            None
+        }
+    };
+    let codepoint = if let Some(digits) = name.strip_prefix("uni").filter(|d| d.len() == 4) {
+        hex(digits)
+    } else if let Some(digits) = name
+        .strip_prefix('u')
+        .filter(|d| (4..=6).contains(&d.len()))
+    {
+        hex(digits)
+    } else {
+        None
+    };
+    if let Some(c) = codepoint {
+        return char::from_u32(c);
+    }
+    let i = AGLFN.binary_search_by(|(n, _)| (*n).cmp(name)).ok()?;
+    Some(AGLFN[i].1)
+}
@@ -0,0 +1,610 @@
+// WARNING: this file was generated by scripts/gen-agl-table.py
+
+/// The Adobe Glyph List For New Fonts 1.7, as glyph names and the
+/// characters they stand for, sorted by name.
+#[rustfmt::skip]
+pub const AGLFN: &[(&str, char)] = &[
+    ("A", '\u{0041}'),
+    ("AE", '\u{00C6}'),
+    ("AEacute", '\u{01FC}'),
+    ("Aacute", '\u{00C1}'),
+    ("Abreve", '\u{0102}'),
+    ("Acircumflex", '\u{00C2}'),
+    ("Adieresis", '\u{00C4}'),
+    ("Agrave", '\u{00C0}'),
+    ("Alpha", '\u{0391}'),
+    ("Alphatonos", '\u{0386}'),
+    ("Amacron", '\u{0100}'),
+    ("Aogonek", '\u{0104}'),
+    ("Aring", '\u{00C5}'),
+    ("Aringacute", '\u{01FA}'),
+    ("Atilde", '\u{00C3}'),
+    ("B", '\u{0042}'),
+    ("Beta", '\u{0392}'),
+    ("C", '\u{0043}'),
+    ("Cacute", '\u{0106}'),
+    ("Ccaron", '\u{010C}'),
+    ("Ccedilla", '\u{00C7}'),
+    ("Ccircumflex", '\u{0108}'),
+    ("Cdotaccent", '\u{010A}'),
+    ("Chi", '\u{03A7}'),
+    ("D", '\u{0044}'),
+    ("Dcaron", '\u{010E}'),
+    ("Dcroat", '\u{0110}'),
+    ("Delta", '\u{2206}'),
+    ("E", '\u{0045}'),
+    ("Eacute", '\u{00C9}'),
+    ("Ebreve", '\u{0114}'),
+    ("Ecaron", '\u{011A}'),
+    ("Ecircumflex", '\u{00CA}'),
+    ("Edieresis", '\u{00CB}'),
+    ("Edotaccent", '\u{0116}'),
+    ("Egrave", '\u{00C8}'),
+    ("Emacron", '\u{0112}'),
+    ("Eng", '\u{014A}'),
+    ("Eogonek", '\u{0118}'),
+    ("Epsilon", '\u{0395}'),
+    ("Epsilontonos", '\u{0388}'),
+    ("Eta", '\u{0397}'),
+    ("Etatonos", '\u{0389}'),
+    ("Eth", '\u{00D0}'),
+    ("Euro", '\u{20AC}'),
+    ("F", '\u{0046}'),
+    ("G", '\u{0047}'),
+    ("Gamma", '\u{0393}'),
+    ("Gbreve", '\u{011E}'),
+    ("Gcaron", '\u{01E6}'),
+    ("Gcircumflex", '\u{011C}'),
+    ("Gcommaaccent", '\u{0122}'),
+    ("Gdotaccent", '\u{0120}'),
+    ("H", '\u{0048}'),
+    ("H18533", '\u{25CF}'),
+    ("H18543", '\u{25AA}'),
+    ("H18551", '\u{25AB}'),
+    ("H22073", '\u{25A1}'),
+    ("Hbar", '\u{0126}'),
+    ("Hcircumflex", '\u{0124}'),
+    ("I", '\u{0049}'),
+    ("IJ", '\u{0132}'),
+    ("Iacute", '\u{00CD}'),
+    ("Ibreve", '\u{012C}'),
+    ("Icircumflex", '\u{00CE}'),
+    ("Idieresis", '\u{00CF}'),
+    ("Idotaccent", '\u{0130}'),
+    ("Ifraktur", '\u{2111}'),
+    ("Igrave", '\u{00CC}'),
+    ("Imacron", '\u{012A}'),
+    ("Iogonek", '\u{012E}'),
+    ("Iota", '\u{0399}'),
+    ("Iotadieresis", '\u{03AA}'),
+    ("Iotatonos", '\u{038A}'),
+    ("Itilde", '\u{0128}'),
+    ("J", '\u{004A}'),
+    ("Jcircumflex", '\u{0134}'),
+    ("K", '\u{004B}'),
+    ("Kappa", '\u{039A}'),
+    ("Kcommaaccent", '\u{0136}'),
+    ("L", '\u{004C}'),
+    ("Lacute", '\u{0139}'),
+    ("Lambda", '\u{039B}'),
+    ("Lcaron", '\u{013D}'),
+    ("Lcommaaccent", '\u{013B}'),
+    ("Ldot", '\u{013F}'),
+    ("Lslash", '\u{0141}'),
+    ("M", '\u{004D}'),
+    ("Mu", '\u{039C}'),
+    ("N", '\u{004E}'),
+    ("Nacute", '\u{0143}'),
+    ("Ncaron", '\u{0147}'),
+    ("Ncommaaccent", '\u{0145}'),
+    ("Ntilde", '\u{00D1}'),
+    ("Nu", '\u{039D}'),
+    ("O", '\u{004F}'),
+    ("OE", '\u{0152}'),
+    ("Oacute", '\u{00D3}'),
+    ("Obreve", '\u{014E}'),
+    ("Ocircumflex", '\u{00D4}'),
+    ("Odieresis", '\u{00D6}'),
+    ("Ograve", '\u{00D2}'),
+    ("Ohorn", '\u{01A0}'),
+    ("Ohungarumlaut", '\u{0150}'),
+    ("Omacron", '\u{014C}'),
+    ("Omega", '\u{2126}'),
+    ("Omegatonos", '\u{038F}'),
+    ("Omicron", '\u{039F}'),
+    ("Omicrontonos", '\u{038C}'),
+    ("Oslash", '\u{00D8}'),
+    ("Oslashacute", '\u{01FE}'),
+    ("Otilde", '\u{00D5}'),
+    ("P", '\u{0050}'),
+    ("Phi", '\u{03A6}'),
+    ("Pi", '\u{03A0}'),
+    ("Psi", '\u{03A8}'),
+    ("Q", '\u{0051}'),
+    ("R", '\u{0052}'),
+    ("Racute", '\u{0154}'),
+    ("Rcaron", '\u{0158}'),
+    ("Rcommaaccent", '\u{0156}'),
+    ("Rfraktur", '\u{211C}'),
+    ("Rho", '\u{03A1}'),
+    ("S", '\u{0053}'),
+    ("SF010000", '\u{250C}'),
+    ("SF020000", '\u{2514}'),
+    ("SF030000", '\u{2510}'),
+    ("SF040000", '\u{2518}'),
+    ("SF050000", '\u{253C}'),
+    ("SF060000", '\u{252C}'),
+    ("SF070000", '\u{2534}'),
+    ("SF080000", '\u{251C}'),
+    ("SF090000", '\u{2524}'),
+    ("SF100000", '\u{2500}'),
+    ("SF110000", '\u{2502}'),
+    ("SF190000", '\u{2561}'),
+    ("SF200000", '\u{2562}'),
+    ("SF210000", '\u{2556}'),
+    ("SF220000", '\u{2555}'),
+    ("SF230000", '\u{2563}'),
+    ("SF240000", '\u{2551}'),
+    ("SF250000", '\u{2557}'),
+    ("SF260000", '\u{255D}'),
+    ("SF270000", '\u{255C}'),
+    ("SF280000", '\u{255B}'),
+    ("SF360000", '\u{255E}'),
+    ("SF370000", '\u{255F}'),
+    ("SF380000", '\u{255A}'),
+    ("SF390000", '\u{2554}'),
+    ("SF400000", '\u{2569}'),
+    ("SF410000", '\u{2566}'),
+    ("SF420000", '\u{2560}'),
+    ("SF430000", '\u{2550}'),
+    ("SF440000", '\u{256C}'),
+    ("SF450000", '\u{2567}'),
+    ("SF460000", '\u{2568}'),
+    ("SF470000", '\u{2564}'),
+    ("SF480000", '\u{2565}'),
+    ("SF490000", '\u{2559}'),
+    ("SF500000", '\u{2558}'),
+    ("SF510000", '\u{2552}'),
+    ("SF520000", '\u{2553}'),
+    ("SF530000", '\u{256B}'),
+    ("SF540000", '\u{256A}'),
+    ("Sacute", '\u{015A}'),
+    ("Scaron", '\u{0160}'),
+    ("Scedilla", '\u{015E}'),
+    ("Scircumflex", '\u{015C}'),
+    ("Scommaaccent", '\u{0218}'),
+    ("Sigma", '\u{03A3}'),
+    ("T", '\u{0054}'),
+    ("Tau", '\u{03A4}'),
+    ("Tbar", '\u{0166}'),
+    ("Tcaron", '\u{0164}'),
+    ("Tcommaaccent", '\u{0162}'),
+    ("Theta", '\u{0398}'),
+    ("Thorn", '\u{00DE}'),
+    ("U", '\u{0055}'),
+    ("Uacute", '\u{00DA}'),
+    ("Ubreve", '\u{016C}'),
+    ("Ucircumflex", '\u{00DB}'),
+    ("Udieresis", '\u{00DC}'),
+    ("Ugrave", '\u{00D9}'),
+    ("Uhorn", '\u{01AF}'),
+    ("Uhungarumlaut", '\u{0170}'),
+    ("Umacron", '\u{016A}'),
+    ("Uogonek", '\u{0172}'),
+    ("Upsilon", '\u{03A5}'),
+    ("Upsilon1", '\u{03D2}'),
+    ("Upsilondieresis", '\u{03AB}'),
+    ("Upsilontonos", '\u{038E}'),
+    ("Uring", '\u{016E}'),
+    ("Utilde", '\u{0168}'),
+    ("V", '\u{0056}'),
+    ("W", '\u{0057}'),
+    ("Wacute", '\u{1E82}'),
+    ("Wcircumflex", '\u{0174}'),
+    ("Wdieresis", '\u{1E84}'),
+    ("Wgrave", '\u{1E80}'),
+    ("X", '\u{0058}'),
+    ("Xi", '\u{039E}'),
+    ("Y", '\u{0059}'),
+    ("Yacute", '\u{00DD}'),
+    ("Ycircumflex", '\u{0176}'),
+    ("Ydieresis", '\u{0178}'),
+    ("Ygrave", '\u{1EF2}'),
+    ("Z", '\u{005A}'),
+    ("Zacute", '\u{0179}'),
+    ("Zcaron", '\u{017D}'),
+    ("Zdotaccent", '\u{017B}'),
+    ("Zeta", '\u{0396}'),
+    ("a", '\u{0061}'),
+    ("aacute", '\u{00E1}'),
+    ("abreve", '\u{0103}'),
+    ("acircumflex", '\u{00E2}'),
+    ("acute", '\u{00B4}'),
+    ("acutecomb", '\u{0301}'),
+    ("adieresis", '\u{00E4}'),
+    ("ae", '\u{00E6}'),
+    ("aeacute", '\u{01FD}'),
+    ("agrave", '\u{00E0}'),
+    ("aleph", '\u{2135}'),
+    ("alpha", '\u{03B1}'),
+    ("alphatonos", '\u{03AC}'),
+    ("amacron", '\u{0101}'),
+    ("ampersand", '\u{0026}'),
+    ("angle", '\u{2220}'),
+    ("angleleft", '\u{2329}'),
+    ("angleright", '\u{232A}'),
+    ("anoteleia", '\u{0387}'),
+    ("aogonek", '\u{0105}'),
+    ("approxequal", '\u{2248}'),
+    ("aring", '\u{00E5}'),
+    ("aringacute", '\u{01FB}'),
+    ("arrowboth", '\u{2194}'),
+    ("arrowdblboth", '\u{21D4}'),
+    ("arrowdbldown", '\u{21D3}'),
+    ("arrowdblleft", '\u{21D0}'),
+    ("arrowdblright", '\u{21D2}'),
+    ("arrowdblup", '\u{21D1}'),
+    ("arrowdown", '\u{2193}'),
+    ("arrowleft", '\u{2190}'),
+    ("arrowright", '\u{2192}'),
+    ("arrowup", '\u{2191}'),
+    ("arrowupdn", '\u{2195}'),
+    ("arrowupdnbse", '\u{21A8}'),
+    ("asciicircum", '\u{005E}'),
+    ("asciitilde", '\u{007E}'),
+    ("asterisk", '\u{002A}'),
+    ("asteriskmath", '\u{2217}'),
+    ("at", '\u{0040}'),
+    ("atilde", '\u{00E3}'),
+    ("b", '\u{0062}'),
+    ("backslash", '\u{005C}'),
+    ("bar", '\u{007C}'),
+    ("beta", '\u{03B2}'),
+    ("block", '\u{2588}'),
+    ("braceleft", '\u{007B}'),
+    ("braceright", '\u{007D}'),
+    ("bracketleft", '\u{005B}'),
+    ("bracketright", '\u{005D}'),
+    ("breve", '\u{02D8}'),
+    ("brokenbar", '\u{00A6}'),
+    ("bullet", '\u{2022}'),
+    ("c", '\u{0063}'),
+    ("cacute", '\u{0107}'),
+    ("caron", '\u{02C7}'),
+    ("carriagereturn", '\u{21B5}'),
+    ("ccaron", '\u{010D}'),
+    ("ccedilla", '\u{00E7}'),
+    ("ccircumflex", '\u{0109}'),
+    ("cdotaccent", '\u{010B}'),
+    ("cedilla", '\u{00B8}'),
+    ("cent", '\u{00A2}'),
+    ("chi", '\u{03C7}'),
+    ("circle", '\u{25CB}'),
+    ("circlemultiply", '\u{2297}'),
+    ("circleplus", '\u{2295}'),
+    ("circumflex", '\u{02C6}'),
+    ("club", '\u{2663}'),
+    ("colon", '\u{003A}'),
+    ("colonmonetary", '\u{20A1}'),
+    ("comma", '\u{002C}'),
+    ("congruent", '\u{2245}'),
+    ("copyright", '\u{00A9}'),
+    ("currency", '\u{00A4}'),
+    ("d", '\u{0064}'),
+    ("dagger", '\u{2020}'),
+    ("daggerdbl", '\u{2021}'),
+    ("dcaron", '\u{010F}'),
+    ("dcroat", '\u{0111}'),
+    ("degree", '\u{00B0}'),
+    ("delta", '\u{03B4}'),
+    ("diamond", '\u{2666}'),
+    ("dieresis", '\u{00A8}'),
+    ("dieresistonos", '\u{0385}'),
+    ("divide", '\u{00F7}'),
+    ("dkshade", '\u{2593}'),
+    ("dnblock", '\u{2584}'),
+    ("dollar", '\u{0024}'),
+    ("dong", '\u{20AB}'),
+    ("dotaccent", '\u{02D9}'),
+    ("dotbelowcomb", '\u{0323}'),
+    ("dotlessi", '\u{0131}'),
+    ("dotmath", '\u{22C5}'),
+    ("e", '\u{0065}'),
+    ("eacute", '\u{00E9}'),
+    ("ebreve", '\u{0115}'),
+    ("ecaron", '\u{011B}'),
+    ("ecircumflex", '\u{00EA}'),
+    ("edieresis", '\u{00EB}'),
+    ("edotaccent", '\u{0117}'),
+    ("egrave", '\u{00E8}'),
+    ("eight", '\u{0038}'),
+    ("element", '\u{2208}'),
+    ("ellipsis", '\u{2026}'),
+    ("emacron", '\u{0113}'),
+    ("emdash", '\u{2014}'),
+    ("emptyset", '\u{2205}'),
+    ("endash", '\u{2013}'),
+    ("eng", '\u{014B}'),
+    ("eogonek", '\u{0119}'),
+    ("epsilon", '\u{03B5}'),
+    ("epsilontonos", '\u{03AD}'),
+    ("equal", '\u{003D}'),
+    ("equivalence", '\u{2261}'),
+    ("estimated", '\u{212E}'),
+    ("eta", '\u{03B7}'),
+    ("etatonos", '\u{03AE}'),
+    ("eth", '\u{00F0}'),
+    ("exclam", '\u{0021}'),
+    ("exclamdbl", '\u{203C}'),
+    ("exclamdown", '\u{00A1}'),
+    ("existential", '\u{2203}'),
+    ("f", '\u{0066}'),
+    ("female", '\u{2640}'),
+    ("figuredash", '\u{2012}'),
+    ("filledbox", '\u{25A0}'),
+    ("filledrect", '\u{25AC}'),
+    ("five", '\u{0035}'),
+    ("fiveeighths", '\u{215D}'),
+    ("florin", '\u{0192}'),
+    ("four", '\u{0034}'),
+    ("fraction", '\u{2044}'),
+    ("franc", '\u{20A3}'),
+    ("g", '\u{0067}'),
+    ("gamma", '\u{03B3}'),
+    ("gbreve", '\u{011F}'),
+    ("gcaron", '\u{01E7}'),
+    ("gcircumflex", '\u{011D}'),
+    ("gcommaaccent", '\u{0123}'),
+    ("gdotaccent", '\u{0121}'),
+    ("germandbls", '\u{00DF}'),
+    ("gradient", '\u{2207}'),
+    ("grave", '\u{0060}'),
+    ("gravecomb", '\u{0300}'),
+    ("greater", '\u{003E}'),
+    ("greaterequal", '\u{2265}'),
+    ("guillemotleft", '\u{00AB}'),
+    ("guillemotright", '\u{00BB}'),
+    ("guilsinglleft", '\u{2039}'),
+    ("guilsinglright", '\u{203A}'),
+    ("h", '\u{0068}'),
+    ("hbar", '\u{0127}'),
+    ("hcircumflex", '\u{0125}'),
+    ("heart", '\u{2665}'),
+    ("hookabovecomb", '\u{0309}'),
+    ("house", '\u{2302}'),
+    ("hungarumlaut", '\u{02DD}'),
+    ("hyphen", '\u{002D}'),
+    ("i", '\u{0069}'),
+    ("iacute", '\u{00ED}'),
+    ("ibreve", '\u{012D}'),
+    ("icircumflex", '\u{00EE}'),
+    ("idieresis", '\u{00EF}'),
+    ("igrave", '\u{00EC}'),
+    ("ij", '\u{0133}'),
+    ("imacron", '\u{012B}'),
+    ("infinity", '\u{221E}'),
+    ("integral", '\u{222B}'),
+    ("integralbt", '\u{2321}'),
+    ("integraltp", '\u{2320}'),
+    ("intersection", '\u{2229}'),
+    ("invbullet", '\u{25D8}'),
+    ("invcircle", '\u{25D9}'),
+    ("invsmileface", '\u{263B}'),
+    ("iogonek", '\u{012F}'),
+    ("iota", '\u{03B9}'),
+    ("iotadieresis", '\u{03CA}'),
+    ("iotadieresistonos", '\u{0390}'),
+    ("iotatonos", '\u{03AF}'),
+    ("itilde", '\u{0129}'),
+    ("j", '\u{006A}'),
+    ("jcircumflex", '\u{0135}'),
+    ("k", '\u{006B}'),
+    ("kappa", '\u{03BA}'),
+    ("kcommaaccent", '\u{0137}'),
+    ("kgreenlandic", '\u{0138}'),
+    ("l", '\u{006C}'),
+    ("lacute", '\u{013A}'),
+    ("lambda", '\u{03BB}'),
+    ("lcaron", '\u{013E}'),
+    ("lcommaaccent", '\u{013C}'),
+    ("ldot", '\u{0140}'),
+    ("less", '\u{003C}'),
+    ("lessequal", '\u{2264}'),
+    ("lfblock", '\u{258C}'),
+    ("lira", '\u{20A4}'),
+    ("logicaland", '\u{2227}'),
+    ("logicalnot", '\u{00AC}'),
+    ("logicalor", '\u{2228}'),
+    ("longs", '\u{017F}'),
+    ("lozenge", '\u{25CA}'),
+    ("lslash", '\u{0142}'),
+    ("ltshade", '\u{2591}'),
+    ("m", '\u{006D}'),
+    ("macron", '\u{00AF}'),
+    ("male", '\u{2642}'),
+    ("minus", '\u{2212}'),
+    ("minute", '\u{2032}'),
+    ("mu", '\u{00B5}'),
+    ("multiply", '\u{00D7}'),
+    ("musicalnote", '\u{266A}'),
+    ("musicalnotedbl", '\u{266B}'),
+    ("n", '\u{006E}'),
+    ("nacute", '\u{0144}'),
+    ("napostrophe", '\u{0149}'),
+    ("ncaron", '\u{0148}'),
+    ("ncommaaccent", '\u{0146}'),
+    ("nine", '\u{0039}'),
+    ("notelement", '\u{2209}'),
+    ("notequal", '\u{2260}'),
+    ("notsubset", '\u{2284}'),
+    ("ntilde", '\u{00F1}'),
+    ("nu", '\u{03BD}'),
+    ("numbersign", '\u{0023}'),
+    ("o", '\u{006F}'),
+    ("oacute", '\u{00F3}'),
+    ("obreve", '\u{014F}'),
+    ("ocircumflex", '\u{00F4}'),
+    ("odieresis", '\u{00F6}'),
+    ("oe", '\u{0153}'),
+    ("ogonek", '\u{02DB}'),
+    ("ograve", '\u{00F2}'),
+    ("ohorn", '\u{01A1}'),
+    ("ohungarumlaut", '\u{0151}'),
+    ("omacron", '\u{014D}'),
+    ("omega", '\u{03C9}'),
+    ("omega1", '\u{03D6}'),
+    ("omegatonos", '\u{03CE}'),
+    ("omicron", '\u{03BF}'),
+    ("omicrontonos", '\u{03CC}'),
+    ("one", '\u{0031}'),
+    ("onedotenleader", '\u{2024}'),
+    ("oneeighth", '\u{215B}'),
+    ("onehalf", '\u{00BD}'),
+    ("onequarter", '\u{00BC}'),
+    ("onesuperior", '\u{00B9}'),
+    ("onethird", '\u{2153}'),
+    ("openbullet", '\u{25E6}'),
+    ("ordfeminine", '\u{00AA}'),
+    ("ordmasculine", '\u{00BA}'),
+    ("orthogonal", '\u{221F}'),
+    ("oslash", '\u{00F8}'),
+    ("oslashacute", '\u{01FF}'),
+    ("otilde", '\u{00F5}'),
+    ("p", '\u{0070}'),
+    ("paragraph", '\u{00B6}'),
+    ("parenleft", '\u{0028}'),
+    ("parenright", '\u{0029}'),
+    ("partialdiff", '\u{2202}'),
+    ("percent", '\u{0025}'),
+    ("period", '\u{002E}'),
+    ("periodcentered", '\u{00B7}'),
+    ("perpendicular", '\u{22A5}'),
+    ("perthousand", '\u{2030}'),
+    ("peseta", '\u{20A7}'),
+    ("phi", '\u{03C6}'),
+    ("phi1", '\u{03D5}'),
+    ("pi", '\u{03C0}'),
+    ("plus", '\u{002B}'),
+    ("plusminus", '\u{00B1}'),
+    ("prescription", '\u{211E}'),
+    ("product", '\u{220F}'),
+    ("propersubset", '\u{2282}'),
+    ("propersuperset", '\u{2283}'),
+    ("proportional", '\u{221D}'),
+    ("psi", '\u{03C8}'),
+    ("q", '\u{0071}'),
+    ("question", '\u{003F}'),
+    ("questiondown", '\u{00BF}'),
+    ("quotedbl", '\u{0022}'),
+    ("quotedblbase", '\u{201E}'),
+    ("quotedblleft", '\u{201C}'),
+    ("quotedblright", '\u{201D}'),
+    ("quoteleft", '\u{2018}'),
+    ("quotereversed", '\u{201B}'),
+    ("quoteright", '\u{2019}'),
+    ("quotesinglbase", '\u{201A}'),
+    ("quotesingle", '\u{0027}'),
+    ("r", '\u{0072}'),
+    ("racute", '\u{0155}'),
+    ("radical", '\u{221A}'),
+    ("rcaron", '\u{0159}'),
+    ("rcommaaccent", '\u{0157}'),
+    ("reflexsubset", '\u{2286}'),
+    ("reflexsuperset", '\u{2287}'),
+    ("registered", '\u{00AE}'),
+    ("revlogicalnot", '\u{2310}'),
+    ("rho", '\u{03C1}'),
+    ("ring", '\u{02DA}'),
+    ("rtblock", '\u{2590}'),
+    ("s", '\u{0073}'),
+    ("sacute", '\u{015B}'),
+    ("scaron", '\u{0161}'),
+    ("scedilla", '\u{015F}'),
+    ("scircumflex", '\u{015D}'),
+    ("scommaaccent", '\u{0219}'),
+    ("second", '\u{2033}'),
+    ("section", '\u{00A7}'),
+    ("semicolon", '\u{003B}'),
+    ("seven", '\u{0037}'),
+    ("seveneighths", '\u{215E}'),
+    ("shade", '\u{2592}'),
+    ("sigma", '\u{03C3}'),
+    ("sigma1", '\u{03C2}'),
+    ("similar", '\u{223C}'),
+    ("six", '\u{0036}'),
+    ("slash", '\u{002F}'),
+    ("smileface", '\u{263A}'),
+    ("space", '\u{0020}'),
+    ("spade", '\u{2660}'),
+    ("sterling", '\u{00A3}'),
+    ("suchthat", '\u{220B}'),
+    ("summation", '\u{2211}'),
+    ("sun", '\u{263C}'),
+    ("t", '\u{0074}'),
+    ("tau", '\u{03C4}'),
+    ("tbar", '\u{0167}'),
+    ("tcaron", '\u{0165}'),
+    ("tcommaaccent", '\u{0163}'),
+    ("therefore", '\u{2234}'),
+    ("theta", '\u{03B8}'),
+    ("theta1", '\u{03D1}'),
+    ("thorn", '\u{00FE}'),
+    ("three", '\u{0033}'),
+    ("threeeighths", '\u{215C}'),
+    ("threequarters", '\u{00BE}'),
+    ("threesuperior", '\u{00B3}'),
+    ("tilde", '\u{02DC}'),
+    ("tildecomb", '\u{0303}'),
+    ("tonos", '\u{0384}'),
+    ("trademark", '\u{2122}'),
+    ("triagdn", '\u{25BC}'),
+    ("triaglf", '\u{25C4}'),
+    ("triagrt", '\u{25BA}'),
+    ("triagup", '\u{25B2}'),
+    ("two", '\u{0032}'),
+    ("twodotenleader", '\u{2025}'),
+    ("twosuperior", '\u{00B2}'),
+    ("twothirds", '\u{2154}'),
+    ("u", '\u{0075}'),
+    ("uacute", '\u{00FA}'),
+    ("ubreve", '\u{016D}'),
+    ("ucircumflex", '\u{00FB}'),
+    ("udieresis", '\u{00FC}'),
+    ("ugrave", '\u{00F9}'),
+    ("uhorn", '\u{01B0}'),
+    ("uhungarumlaut", '\u{0171}'),
+    ("umacron", '\u{016B}'),
+    ("underscore", '\u{005F}'),
+    ("underscoredbl", '\u{2017}'),
+    ("union", '\u{222A}'),
+    ("universal", '\u{2200}'),
+    ("uogonek", '\u{0173}'),
+    ("upblock", '\u{2580}'),
+    ("upsilon", '\u{03C5}'),
+    ("upsilondieresis", '\u{03CB}'),
+    ("upsilondieresistonos", '\u{03B0}'),
+    ("upsilontonos", '\u{03CD}'),
+    ("uring", '\u{016F}'),
+    ("utilde", '\u{0169}'),
+    ("v", '\u{0076}'),
+    ("w", '\u{0077}'),
+    ("wacute", '\u{1E83}'),
+    ("wcircumflex", '\u{0175}'),
+    ("wdieresis", '\u{1E85}'),
+    ("weierstrass", '\u{2118}'),
+    ("wgrave", '\u{1E81}'),
+    ("x", '\u{0078}'),
+    ("xi", '\u{03BE}'),
+    ("y", '\u{0079}'),
+    ("yacute", '\u{00FD}'),
+    ("ycircumflex", '\u{0177}'),
+    ("ydieresis", '\u{00FF}'),
+    ("yen", '\u{00A5}'),
+    ("ygrave", '\u{1EF3}'),
+    ("z", '\u{007A}'),
+    ("zacute", '\u{017A}'),
+    ("zcaron", '\u{017E}'),
+    ("zdotaccent", '\u{017C}'),
+    ("zero", '\u{0030}'),
+    ("zeta", '\u{03B6}'),
+];
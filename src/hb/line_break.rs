@@ -0,0 +1,356 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use super::buffer::{
+    hb_buffer_t, hb_glyph_info_t, GlyphPosition, HB_BUFFER_CLUSTER_LEVEL_MONOTONE_CHARACTERS,
+};
+use super::clusters::advance;
+use super::face::hb_font_t;
+use super::ot_shape_plan::hb_ot_shape_plan_t;
+use super::reshape::{cluster_starts, empty_like, ClusterStart};
+use crate::{Feature, GlyphBuffer};
+
+/// A line produced by [`Shaper::break_lines`](crate::Shaper::break_lines).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Line {
+    /// Byte range of the line in the text.
+    pub text: Range<usize>,
+    /// Range of the glyphs of the line in [`LineBreaks::buffer`].
+    pub glyphs: Range<usize>,
+    /// Advance of the line along the direction of the text.
+    pub advance: i32,
+}
+
+/// The result of [`Shaper::break_lines`](crate::Shaper::break_lines).
+pub struct LineBreaks {
+    /// The glyphs of all lines, one line after the other.
+    ///
+    /// Lines are stored in logical order and the glyphs of each line in
+    /// visual order, so the lines of a right-to-left paragraph are not
+    /// mirrored. The buffer has the properties of the paragraph and its
+    /// cluster values are byte offsets in the text.
+    pub buffer: GlyphBuffer,
+    /// The lines, in logical order.
+    pub lines: Vec<Line>,
+}
+
+impl GlyphBuffer {
+    /// Returns the clusters at which the text can be broken without
+    /// reshaping either side, in logical order.
+    ///
+    /// These are the cluster values of the glyphs that aren't
+    /// [unsafe to break](crate::GlyphInfo::unsafe_to_break) and start a
+    /// cluster following all previous ones. The start of the text is not
+    /// included.
+    pub fn safe_to_break_clusters(&self) -> impl Iterator<Item = u32> + '_ {
+        cluster_boundaries(&self.0)
+            .filter(|info| !info.unsafe_to_break())
+            .map(|info| info.cluster)
+    }
+
+    /// Returns the clusters at which the text can be split and the parts
+    /// shaped separately and concatenated, in logical order.
+    ///
+    /// These are the cluster values of the glyphs that aren't
+    /// [unsafe to concatenate](crate::GlyphInfo::unsafe_to_concat) and start
+    /// a cluster following all previous ones. They are only meaningful if
+    /// the buffer was shaped with
+    /// [`BufferFlags::PRODUCE_UNSAFE_TO_CONCAT`](crate::BufferFlags::PRODUCE_UNSAFE_TO_CONCAT).
+    /// The start of the text is not included.
+    pub fn safe_to_concat_clusters(&self) -> impl Iterator<Item = u32> + '_ {
+        cluster_boundaries(&self.0)
+            .filter(|info| !info.unsafe_to_concat())
+            .map(|info| info.cluster)
+    }
+}
+
+/// Returns the first glyph of every cluster but the first, in logical order,
+/// skipping clusters that don't follow all previous ones.
+fn cluster_boundaries(buffer: &hb_buffer_t) -> impl Iterator<Item = &hb_glyph_info_t> {
+    let infos = &buffer.info[..buffer.len];
+    let backward = buffer.direction.is_backward();
+    let mut max = None;
+    (0..infos.len())
+        .map(move |i| {
+            if backward {
+                &infos[infos.len() - 1 - i]
+            } else {
+                &infos[i]
+            }
+        })
+        .filter(move |info| {
+            let boundary = max.is_some_and(|max| info.cluster > max);
+            if max.map_or(true, |max| info.cluster > max) {
+                max = Some(info.cluster);
+            }
+            boundary
+        })
+}
+
+impl hb_font_t<'_> {
+    /// Breaks a shaped paragraph into lines of at most `max_advance`.
+    ///
+    /// `paragraph` must be the result of shaping `text` with cluster values
+    /// set to byte offsets in `text`, as done by
+    /// [`UnicodeBuffer::push_str`](crate::UnicodeBuffer::push_str), and
+    /// `features` must be those it was shaped with. `breaks` are the byte
+    /// offsets of the break opportunities, in increasing order; those inside
+    /// a cluster are ignored.
+    ///
+    /// Lines are filled greedily, measuring the advances of the paragraph.
+    /// Glyphs are reused where the line edges are not
+    /// [unsafe to break](crate::GlyphInfo::unsafe_to_break). Otherwise only
+    /// the text between the edge and the nearest safe break inside the line
+    /// is reshaped, with the surrounding text as context, and the line is
+    /// measured again. A line that doesn't fit after reshaping ends at the
+    /// previous break opportunity instead. A line only overflows if it has no
+    /// break opportunity at all.
+    ///
+    /// If the cluster values of the paragraph aren't monotone, as with the
+    /// non-monotone [cluster levels](crate::BufferClusterLevel), the text is
+    /// measured from one reshape with monotone clusters and every line is
+    /// reshaped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a break opportunity doesn't lie on a character boundary of
+    /// `text`.
+    pub fn break_lines(
+        &self,
+        paragraph: &GlyphBuffer,
+        text: &str,
+        features: &[Feature],
+        breaks: &[usize],
+        max_advance: i32,
+    ) -> LineBreaks {
+        let paragraph = &paragraph.0;
+        let plan = self.plan(
+            paragraph.direction,
+            paragraph.script,
+            paragraph.language.as_ref(),
+            features,
+        );
+        let starts = cluster_starts(paragraph, text);
+        let offset_advances = if starts.is_none() {
+            // Measure from one reshape with clusters that are character
+            // offsets, rather than reshaping every candidate line.
+            let mut template = empty_like(paragraph);
+            template.cluster_level = HB_BUFFER_CLUSTER_LEVEL_MONOTONE_CHARACTERS;
+            let reshaped = self.shape_range(&plan, &template, text, 0..text.len(), features);
+            let mut sums = vec![0; text.len() + 1];
+            for (info, pos) in reshaped.info[..reshaped.len]
+                .iter()
+                .zip(&reshaped.pos[..reshaped.len])
+            {
+                sums[(info.cluster as usize + 1).min(text.len())] += advance(&reshaped, pos);
+            }
+            for i in 1..sums.len() {
+                sums[i] += sums[i - 1];
+            }
+            sums
+        } else {
+            Vec::new()
+        };
+        let breaker = LineBreaker {
+            font: self,
+            plan: &plan,
+            paragraph,
+            text,
+            features,
+            starts,
+            advances: paragraph.pos[..paragraph.len]
+                .iter()
+                .scan(0, |sum, pos| {
+                    *sum += advance(paragraph, pos);
+                    Some(*sum)
+                })
+                .collect(),
+            offset_advances,
+        };
+
+        let mut candidates: Vec<usize> = Vec::with_capacity(breaks.len() + 1);
+        for &offset in breaks {
+            let valid = match &breaker.starts {
+                Some(starts) => starts
+                    .get(breaker.index(offset))
+                    .is_some_and(|c| c.offset == offset),
+                None => text.is_char_boundary(offset),
+            };
+            if valid && offset > candidates.last().copied().unwrap_or(0) && offset < text.len() {
+                candidates.push(offset);
+            }
+        }
+        candidates.push(text.len());
+
+        let mut buffer = empty_like(paragraph);
+        let mut lines = Vec::new();
+        let (mut start, mut first) = (0, 0);
+        while start < text.len() {
+            let mut end = first;
+            while end + 1 < candidates.len()
+                && breaker.estimate(start, candidates[end + 1]) <= max_advance
+            {
+                end += 1;
+            }
+            let line = loop {
+                let line = breaker.line(start, candidates[end]);
+                if line.advance <= max_advance || end == first {
+                    break line;
+                }
+                end -= 1;
+            };
+
+            let glyphs = buffer.info.len()..buffer.info.len() + line.info.len();
+            buffer.info.extend(line.info);
+            buffer.pos.extend(line.pos);
+            lines.push(Line {
+                text: start..candidates[end],
+                glyphs,
+                advance: line.advance,
+            });
+            start = candidates[end];
+            first = end + 1;
+        }
+        buffer.len = buffer.info.len();
+
+        LineBreaks {
+            buffer: GlyphBuffer(buffer),
+            lines,
+        }
+    }
+}
+
+struct LineBreaker<'a> {
+    font: &'a hb_font_t<'a>,
+    plan: &'a hb_ot_shape_plan_t,
+    paragraph: &'a hb_buffer_t,
+    text: &'a str,
+    features: &'a [Feature],
+    starts: Option<Vec<ClusterStart>>,
+    /// Sums of the advances of the paragraph glyphs up to and including
+    /// each glyph, in storage order.
+    advances: Vec<i32>,
+    /// Sums of the advances of the text before each byte offset, from a
+    /// reshape of the whole text, if the paragraph has no usable clusters.
+    offset_advances: Vec<i32>,
+}
+
+/// The glyphs of a line in visual order.
+struct LineGlyphs {
+    info: Vec<hb_glyph_info_t>,
+    pos: Vec<GlyphPosition>,
+    advance: i32,
+}
+
+impl LineBreaker<'_> {
+    /// Returns the index of the first cluster at or after `offset`.
+    fn index(&self, offset: usize) -> usize {
+        let starts = self.starts.as_deref().unwrap_or_default();
+        starts.partition_point(|cluster| cluster.offset < offset)
+    }
+
+    /// Returns the logical index of the first glyph at or after `offset`.
+    fn glyph(&self, offset: usize) -> usize {
+        let starts = self.starts.as_deref().unwrap_or_default();
+        starts
+            .get(self.index(offset))
+            .map_or(self.paragraph.len, |cluster| cluster.glyph)
+    }
+
+    /// Returns the storage range of the glyphs of `text[range]`.
+    fn storage(&self, range: Range<usize>) -> Range<usize> {
+        let glyphs = self.glyph(range.start)..self.glyph(range.end);
+        let len = self.paragraph.len;
+        if self.paragraph.direction.is_backward() {
+            len - glyphs.end..len - glyphs.start
+        } else {
+            glyphs
+        }
+    }
+
+    /// Returns the advance of `text[start..end]` in the paragraph, or in the
+    /// reshaped text if the paragraph has no usable clusters.
+    fn estimate(&self, start: usize, end: usize) -> i32 {
+        if self.starts.is_none() {
+            return self.offset_advances[end] - self.offset_advances[start];
+        }
+        let range = self.storage(start..end);
+        let before = range.start.checked_sub(1).map_or(0, |i| self.advances[i]);
+        let after = range.end.checked_sub(1).map_or(0, |i| self.advances[i]);
+        after - before
+    }
+
+    fn is_safe_break(&self, offset: usize) -> bool {
+        offset == 0
+            || offset == self.text.len()
+            || self
+                .starts
+                .as_deref()
+                .and_then(|starts| starts.get(self.index(offset)))
+                .is_some_and(|cluster| cluster.offset == offset && cluster.safe_to_break)
+    }
+
+    /// Returns the glyphs of `text[start..end]`, reshaping the text between
+    /// unsafe edges and the nearest safe breaks inside the line.
+    fn line(&self, start: usize, end: usize) -> LineGlyphs {
+        let starts = self.starts.as_deref().unwrap_or_default();
+        let safe_starts = || {
+            starts[self.index(start)..self.index(end)]
+                .iter()
+                .filter(|cluster| cluster.offset > start && cluster.safe_to_break)
+        };
+        let head_end = if self.is_safe_break(start) {
+            start
+        } else {
+            safe_starts().next().map_or(end, |cluster| cluster.offset)
+        };
+        let tail_start = if self.is_safe_break(end) {
+            end
+        } else {
+            safe_starts()
+                .rev()
+                .find(|cluster| cluster.offset > head_end)
+                .map_or(head_end, |cluster| cluster.offset)
+        };
+
+        let reshape = |range: Range<usize>| {
+            if range.is_empty() {
+                hb_buffer_t::new()
+            } else {
+                let font = self.font;
+                font.shape_range(self.plan, self.paragraph, self.text, range, self.features)
+            }
+        };
+        let head = reshape(start..head_end);
+        let tail = reshape(tail_start..end);
+        let middle = self.storage(head_end..tail_start);
+
+        let mut pieces = [
+            (&head.info[..head.len], &head.pos[..head.len]),
+            (
+                &self.paragraph.info[middle.clone()],
+                &self.paragraph.pos[middle],
+            ),
+            (&tail.info[..tail.len], &tail.pos[..tail.len]),
+        ];
+        if self.paragraph.direction.is_backward() {
+            pieces.reverse();
+        }
+
+        let mut line = LineGlyphs {
+            info: Vec::new(),
+            pos: Vec::new(),
+            advance: 0,
+        };
+        for (info, pos) in pieces {
+            line.info.extend_from_slice(info);
+            line.pos.extend_from_slice(pos);
+            line.advance += pos
+                .iter()
+                .map(|pos| advance(self.paragraph, pos))
+                .sum::<i32>();
+        }
+        line
+    }
+}
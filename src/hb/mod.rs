@@ -4,6 +4,7 @@
 #![allow(non_snake_case)]
 #![allow(clippy::collapsible_if)]
 #![allow(clippy::collapsible_else_if)]
+#![allow(clippy::collapsible_match)]
 #![allow(clippy::comparison_chain)]
 #![allow(clippy::needless_range_loop)]
 #![allow(clippy::non_canonical_partial_ord_impl)]
@@ -20,29 +21,39 @@
 #![allow(clippy::manual_range_contains)]
 
 mod algs;
+pub mod attachment;
+pub mod batch;
 #[macro_use]
 pub mod buffer;
 mod aat;
 mod aat_layout;
 mod aat_layout_kerx_table;
+mod aat_layout_lcar_table;
 mod aat_layout_morx_table;
 mod aat_layout_trak_table;
 mod aat_map;
 mod cache;
-mod charmap;
+pub mod charmap;
+pub mod clusters;
 pub mod common;
 pub mod face;
+pub mod fallback;
 mod glyph_metrics;
 mod glyph_names;
+mod glyph_names_table;
 mod kerning;
+pub mod line_break;
 mod machine_cursor;
 mod ot;
 mod ot_layout;
+pub mod ot_layout_base_table;
 mod ot_layout_common;
+mod ot_layout_gdef_table;
 mod ot_layout_gpos_table;
 mod ot_layout_gsub_table;
 mod ot_layout_gsubgpos;
 mod ot_map;
+pub mod ot_metrics;
 mod ot_shape;
 mod ot_shape_fallback;
 mod ot_shape_normalize;
@@ -68,6 +79,8 @@ mod ot_shaper_use_machine;
 mod ot_shaper_use_table;
 mod aat_layout_common;
 mod ot_shaper_vowel_constraints;
+mod persist;
+mod reshape;
 mod set_digest;
 mod tag;
 mod tag_table;
@@ -75,6 +88,8 @@ mod text_parser;
 #[rustfmt::skip]
 mod ucd_table;
 mod unicode;
+#[cfg(feature = "std")]
+pub mod word_cache;
 
 use read_fonts::types::Tag as hb_tag_t;
 
@@ -132,30 +132,31 @@ impl Apply for SequenceContextFormat3<'_> {
                 .unwrap_or_default()
         };
         let mut match_end = 0;
-        let mut match_positions = smallvec::SmallVec::from_elem(0, 4);
-        if match_input(
-            ctx,
-            input_coverages.len() as u16 - 1,
-            &input,
-            &mut match_end,
-            &mut match_positions,
-            None,
-        ) {
-            ctx.buffer
-                .unsafe_to_break_from_outbuffer(Some(ctx.buffer.idx), Some(match_end));
-            apply_lookup(
+        ctx.with_match_positions(|ctx, match_positions| {
+            if match_input(
                 ctx,
-                input_coverages.len() - 1,
-                &mut match_positions,
-                match_end,
-                self.seq_lookup_records(),
-            );
-            Some(())
-        } else {
-            ctx.buffer
-                .unsafe_to_concat(Some(ctx.buffer.idx), Some(match_end));
-            None
-        }
+                input_coverages.len() as u16 - 1,
+                &input,
+                &mut match_end,
+                match_positions,
+                None,
+            ) {
+                ctx.buffer
+                    .unsafe_to_break_from_outbuffer(Some(ctx.buffer.idx), Some(match_end));
+                apply_lookup(
+                    ctx,
+                    input_coverages.len() - 1,
+                    match_positions,
+                    match_end,
+                    self.seq_lookup_records(),
+                );
+                Some(())
+            } else {
+                ctx.buffer
+                    .unsafe_to_concat(Some(ctx.buffer.idx), Some(match_end));
+                None
+            }
+        })
     }
 }
 
@@ -349,59 +350,59 @@ impl Apply for ChainedSequenceContextFormat3<'_> {
 
         let mut end_index = ctx.buffer.idx;
         let mut match_end = 0;
-        let mut match_positions = smallvec::SmallVec::from_elem(0, 4);
+        ctx.with_match_positions(|ctx, match_positions| {
+            let input_matches = match_input(
+                ctx,
+                input_coverages.len() as u16 - 1,
+                &input,
+                &mut match_end,
+                match_positions,
+                None,
+            );
 
-        let input_matches = match_input(
-            ctx,
-            input_coverages.len() as u16 - 1,
-            &input,
-            &mut match_end,
-            &mut match_positions,
-            None,
-        );
+            if input_matches {
+                end_index = match_end;
+            }
 
-        if input_matches {
-            end_index = match_end;
-        }
+            if !(input_matches
+                && match_lookahead(
+                    ctx,
+                    lookahead_coverages.len() as u16,
+                    &ahead,
+                    match_end,
+                    &mut end_index,
+                ))
+            {
+                ctx.buffer
+                    .unsafe_to_concat(Some(ctx.buffer.idx), Some(end_index));
+                return None;
+            }
 
-        if !(input_matches
-            && match_lookahead(
-                ctx,
-                lookahead_coverages.len() as u16,
-                &ahead,
-                match_end,
-                &mut end_index,
-            ))
-        {
-            ctx.buffer
-                .unsafe_to_concat(Some(ctx.buffer.idx), Some(end_index));
-            return None;
-        }
+            let mut start_index = ctx.buffer.out_len;
 
-        let mut start_index = ctx.buffer.out_len;
+            if !match_backtrack(
+                ctx,
+                backtrack_coverages.len() as u16,
+                &back,
+                &mut start_index,
+            ) {
+                ctx.buffer
+                    .unsafe_to_concat_from_outbuffer(Some(start_index), Some(end_index));
+                return None;
+            }
 
-        if !match_backtrack(
-            ctx,
-            backtrack_coverages.len() as u16,
-            &back,
-            &mut start_index,
-        ) {
             ctx.buffer
-                .unsafe_to_concat_from_outbuffer(Some(start_index), Some(end_index));
-            return None;
-        }
-
-        ctx.buffer
-            .unsafe_to_break_from_outbuffer(Some(start_index), Some(end_index));
-        apply_lookup(
-            ctx,
-            input_coverages.len() - 1,
-            &mut match_positions,
-            match_end,
-            self.seq_lookup_records(),
-        );
+                .unsafe_to_break_from_outbuffer(Some(start_index), Some(end_index));
+            apply_lookup(
+                ctx,
+                input_coverages.len() - 1,
+                match_positions,
+                match_end,
+                self.seq_lookup_records(),
+            );
 
-        Some(())
+            Some(())
+        })
     }
 }
 
@@ -433,23 +434,23 @@ fn apply_context<T: ToU16>(
     };
 
     let mut match_end = 0;
-    let mut match_positions = smallvec::SmallVec::from_elem(0, 4);
-
-    if match_input(
-        ctx,
-        input.len() as _,
-        &match_func,
-        &mut match_end,
-        &mut match_positions,
-        None,
-    ) {
-        ctx.buffer
-            .unsafe_to_break(Some(ctx.buffer.idx), Some(match_end));
-        apply_lookup(ctx, input.len(), &mut match_positions, match_end, lookups);
-        return Some(());
-    }
+    ctx.with_match_positions(|ctx, match_positions| {
+        if match_input(
+            ctx,
+            input.len() as _,
+            &match_func,
+            &mut match_end,
+            match_positions,
+            None,
+        ) {
+            ctx.buffer
+                .unsafe_to_break(Some(ctx.buffer.idx), Some(match_end));
+            apply_lookup(ctx, input.len(), match_positions, match_end, lookups);
+            return Some(());
+        }
 
-    None
+        None
+    })
 }
 
 fn apply_chain_context<T: ToU16>(
@@ -479,40 +480,40 @@ fn apply_chain_context<T: ToU16>(
 
     let mut end_index = ctx.buffer.idx;
     let mut match_end = 0;
-    let mut match_positions = smallvec::SmallVec::from_elem(0, 4);
-
-    let input_matches = match_input(
-        ctx,
-        input.len() as u16,
-        &f3,
-        &mut match_end,
-        &mut match_positions,
-        None,
-    );
-
-    if input_matches {
-        end_index = match_end;
-    }
+    ctx.with_match_positions(|ctx, match_positions| {
+        let input_matches = match_input(
+            ctx,
+            input.len() as u16,
+            &f3,
+            &mut match_end,
+            match_positions,
+            None,
+        );
 
-    if !(input_matches
-        && match_lookahead(ctx, lookahead.len() as u16, &f2, match_end, &mut end_index))
-    {
-        ctx.buffer
-            .unsafe_to_concat(Some(ctx.buffer.idx), Some(end_index));
-        return None;
-    }
+        if input_matches {
+            end_index = match_end;
+        }
 
-    let mut start_index = ctx.buffer.out_len;
+        if !(input_matches
+            && match_lookahead(ctx, lookahead.len() as u16, &f2, match_end, &mut end_index))
+        {
+            ctx.buffer
+                .unsafe_to_concat(Some(ctx.buffer.idx), Some(end_index));
+            return None;
+        }
 
-    if !match_backtrack(ctx, backtrack.len() as u16, &f1, &mut start_index) {
-        ctx.buffer
-            .unsafe_to_concat_from_outbuffer(Some(start_index), Some(end_index));
-        return None;
-    }
+        let mut start_index = ctx.buffer.out_len;
 
-    ctx.buffer
-        .unsafe_to_break_from_outbuffer(Some(start_index), Some(end_index));
-    apply_lookup(ctx, input.len(), &mut match_positions, match_end, lookups);
+        if !match_backtrack(ctx, backtrack.len() as u16, &f1, &mut start_index) {
+            ctx.buffer
+                .unsafe_to_concat_from_outbuffer(Some(start_index), Some(end_index));
+            return None;
+        }
 
-    Some(())
+        ctx.buffer
+            .unsafe_to_break_from_outbuffer(Some(start_index), Some(end_index));
+        apply_lookup(ctx, input.len(), match_positions, match_end, lookups);
+
+        Some(())
+    })
 }
@@ -4,7 +4,7 @@ use crate::hb::ot_layout::{
     _hb_glyph_info_multiplied,
 };
 use crate::hb::ot_layout_common::lookup_flags;
-use crate::hb::ot_layout_gpos_table::attach_type;
+use crate::hb::ot_layout_gpos_table::{attach_kind, attach_type};
 use crate::hb::ot_layout_gsubgpos::OT::hb_ot_apply_context_t;
 use crate::hb::ot_layout_gsubgpos::{match_t, skipping_iterator_t, Apply};
 use read_fonts::tables::gpos::{
@@ -18,6 +18,7 @@ trait MarkArrayExt {
         base_anchor: &AnchorTable,
         mark_anchor: &AnchorTable,
         glyph_pos: usize,
+        kind: u8,
     ) -> Option<()>;
 }
 
@@ -28,6 +29,7 @@ impl MarkArrayExt for MarkArray<'_> {
         base_anchor: &AnchorTable,
         mark_anchor: &AnchorTable,
         glyph_pos: usize,
+        kind: u8,
     ) -> Option<()> {
         // If this subtable doesn't have an anchor for this base and this class
         // return `None` such that the subsequent subtables have a chance at it.
@@ -44,6 +46,7 @@ impl MarkArrayExt for MarkArray<'_> {
         pos.y_offset = base_y - mark_y;
         pos.set_attach_type(attach_type::MARK);
         pos.set_attach_chain((glyph_pos as isize - idx as isize) as i16);
+        pos.set_attach_kind(kind);
 
         ctx.buffer.scratch_flags |= HB_BUFFER_SCRATCH_FLAG_HAS_GPOS_ATTACHMENT;
         ctx.buffer.idx += 1;
@@ -122,7 +125,13 @@ impl Apply for MarkBasePosFormat1<'_> {
             .get(mark_record.mark_class() as usize)?
             .ok()?;
 
-        mark_array.apply(ctx, &base_anchor, &mark_anchor, idx as usize)
+        mark_array.apply(
+            ctx,
+            &base_anchor,
+            &mark_anchor,
+            idx as usize,
+            attach_kind::MARK_TO_BASE,
+        )
     }
 }
 
@@ -203,7 +212,13 @@ impl Apply for MarkMarkPosFormat1<'_> {
             .get(mark1_record.mark_class() as usize)?
             .ok()?;
 
-        mark1_array.apply(ctx, &base_anchor, &mark1_anchor, iter_idx)
+        mark1_array.apply(
+            ctx,
+            &base_anchor,
+            &mark1_anchor,
+            iter_idx,
+            attach_kind::MARK_TO_MARK,
+        )
     }
 }
 
@@ -294,6 +309,7 @@ impl Apply for MarkLigPosFormat1<'_> {
             .get(mark_record.mark_class() as usize)?
             .ok()?;
 
-        mark_array.apply(ctx, &base_anchor, &mark_anchor, idx)
+        let kind = attach_kind::mark_to_ligature(comp_index);
+        mark_array.apply(ctx, &base_anchor, &mark_anchor, idx, kind)
     }
 }
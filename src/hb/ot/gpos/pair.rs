@@ -66,8 +66,13 @@ impl Apply for PairPosFormat1<'_> {
                 success(ctx, iter_index, flag1, flag2, has_record2)
             };
 
-        let (pair, data) =
-            find_second_glyph(self, first_glyph_coverage_index as usize, second_glyph)?;
+        let Some((pair, data)) =
+            find_second_glyph(self, first_glyph_coverage_index as usize, second_glyph)
+        else {
+            ctx.buffer
+                .unsafe_to_concat(Some(ctx.buffer.idx), Some(second_glyph_index + 1));
+            return None;
+        };
         // let sets = self.pair_sets();
         // let data = sets.offset_data();
         // let sets = self.pair_sets();
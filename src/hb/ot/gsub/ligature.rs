@@ -34,31 +34,32 @@ impl Apply for Ligature<'_> {
             };
 
             let mut match_end = 0;
-            let mut match_positions = smallvec::SmallVec::from_elem(0, 4);
-            let mut total_component_count = 0;
+            ctx.with_match_positions(|ctx, match_positions| {
+                let mut total_component_count = 0;
 
-            if !match_input(
-                ctx,
-                components.len() as u16,
-                &f,
-                &mut match_end,
-                &mut match_positions,
-                Some(&mut total_component_count),
-            ) {
-                ctx.buffer
-                    .unsafe_to_concat(Some(ctx.buffer.idx), Some(match_end));
-                return None;
-            }
-            let count = components.len() + 1;
-            ligate_input(
-                ctx,
-                count,
-                &match_positions,
-                match_end,
-                total_component_count,
-                self.ligature_glyph().into(),
-            );
-            Some(())
+                if !match_input(
+                    ctx,
+                    components.len() as u16,
+                    &f,
+                    &mut match_end,
+                    match_positions,
+                    Some(&mut total_component_count),
+                ) {
+                    ctx.buffer
+                        .unsafe_to_concat(Some(ctx.buffer.idx), Some(match_end));
+                    return None;
+                }
+                let count = components.len() + 1;
+                ligate_input(
+                    ctx,
+                    count,
+                    match_positions,
+                    match_end,
+                    total_component_count,
+                    self.ligature_glyph().into(),
+                );
+                Some(())
+            })
         }
     }
 }
@@ -1,11 +1,11 @@
 use crate::hb::{
     hb_font_t,
     ot_layout_gsubgpos::{Apply, WouldApply, WouldApplyContext, OT::hb_ot_apply_context_t},
+    persist::{Reader, Writer},
     set_digest::hb_set_digest_t,
 };
 
 use alloc::vec::Vec;
-use core::ops::Range;
 use read_fonts::{
     tables::{
         gpos::{
@@ -91,120 +91,116 @@ pub struct LookupData<'a> {
 
 /// Cache containing lookup and subtable information for a single GSUB or
 /// GPOS table.
+///
+/// Entries are created on first use so that the cost of building the cache
+/// scales with the lookups that are actually applied. Without the `std`
+/// feature, all entries are created up front.
 #[derive(Clone, Default)]
 pub struct LookupCache {
-    pub lookups: Vec<LookupInfo>,
-    pub subtables: Vec<SubtableInfo>,
+    entries: Vec<LookupCell>,
+}
+
+/// Lookup information along with the subtables it owns.
+#[derive(Clone)]
+struct LookupEntry {
+    info: LookupInfo,
+    subtables: Vec<SubtableInfo>,
+}
+
+#[cfg(feature = "std")]
+type LookupCell = std::sync::OnceLock<Option<LookupEntry>>;
+
+/// Eagerly initialized stand-in for `OnceLock` in `no_std` builds.
+#[cfg(not(feature = "std"))]
+#[derive(Clone, Default)]
+struct LookupCell(Option<LookupEntry>);
+
+#[cfg(not(feature = "std"))]
+impl LookupCell {
+    fn get_or_init(&self, _f: impl FnOnce() -> Option<LookupEntry>) -> &Option<LookupEntry> {
+        &self.0
+    }
 }
 
 impl LookupCache {
-    pub fn new() -> Self {
-        Self::default()
+    pub fn new<'a>(host: &impl LookupHost<'a>) -> Self {
+        let count = host.lookup_count();
+        #[cfg(feature = "std")]
+        let entries = (0..count).map(|_| LookupCell::new()).collect();
+        #[cfg(not(feature = "std"))]
+        let entries = (0..count)
+            .map(|index| LookupCell(create_entry(host, index).ok()))
+            .collect();
+        Self { entries }
     }
 
-    pub fn clear(&mut self) {
-        self.lookups.clear();
-        self.subtables.clear();
+    /// Returns the lookup at the given index, creating it if necessary.
+    pub fn get<'a>(&self, host: &impl LookupHost<'a>, index: u16) -> Option<&LookupInfo> {
+        self.entry(host, index).map(|entry| &entry.info)
     }
 
-    pub fn create_all<'a>(&mut self, host: &impl LookupHost<'a>) {
-        self.clear();
-        let count = host.lookup_count();
-        self.lookups.resize(count as usize, Default::default());
-        for i in 0..count {
-            let _ = self.get_or_create(host, i);
-        }
+    fn entry<'a>(&self, host: &impl LookupHost<'a>, index: u16) -> Option<&LookupEntry> {
+        self.entries
+            .get(index as usize)?
+            .get_or_init(|| create_entry(host, index).ok())
+            .as_ref()
     }
 
-    pub fn get(&self, index: u16) -> Option<&LookupInfo> {
-        let entry = self.lookups.get(index as usize)?;
-        match entry.state {
-            LookupState::Ready => Some(entry),
-            _ => None,
-        }
+    /// Returns the subtables of a lookup previously returned by
+    /// [`Self::get`].
+    pub fn subtables(&self, lookup: &LookupInfo) -> Option<&[SubtableInfo]> {
+        #[cfg(feature = "std")]
+        let entry = self.entries.get(lookup.index as usize)?.get()?.as_ref()?;
+        #[cfg(not(feature = "std"))]
+        let entry = self.entries.get(lookup.index as usize)?.0.as_ref()?;
+        Some(&entry.subtables)
     }
 
-    pub fn get_or_create<'a>(
-        &mut self,
-        cx: &impl LookupHost<'a>,
-        index: u16,
-    ) -> Result<&LookupInfo, ReadError> {
-        let index = index as usize;
-        if index >= self.lookups.len() {
-            self.lookups.resize(index + 1, LookupInfo::default());
-        }
-        let entry = &mut self.lookups[index];
-        if entry.state != LookupState::Vacant {
-            return Ok(entry);
-        }
-        entry.state = LookupState::Error;
-        let data = cx.lookup_data(index as u16)?;
-        entry.is_subst = data.is_subst;
-        let lookup_data = data
-            .table_data
-            .split_off(data.offset)
-            .ok_or(ReadError::OutOfBounds)?;
-        let lookup: Lookup<()> = Lookup::read(lookup_data)?;
-        let kind = lookup.lookup_type();
-        let lookup_flag = lookup.lookup_flag();
-        entry.props = u32::from(lookup.lookup_flag().to_bits());
-        if lookup_flag.to_bits() & LookupFlag::USE_MARK_FILTERING_SET.to_bits() != 0 {
-            entry.props |= (lookup.mark_filtering_set().unwrap_or_default() as u32) << 16;
+    /// Writes all entries, creating them first if necessary.
+    pub fn write<'a>(&self, host: &impl LookupHost<'a>, writer: &mut Writer) {
+        writer.write_len(self.entries.len());
+        for index in 0..self.entries.len() {
+            let Some(entry) = self.entry(host, index as u16) else {
+                writer.write_bool(false);
+                continue;
+            };
+            writer.write_bool(true);
+            entry.info.write(writer);
+            writer.write_len(entry.subtables.len());
+            for subtable in &entry.subtables {
+                subtable.write(writer);
+            }
         }
-        entry.is_rtl = lookup_flag.to_bits() & LookupFlag::RIGHT_TO_LEFT.to_bits() != 0;
-        if data.is_subst {
-            entry.is_reversed =
-                is_reversed(data.table_data, &lookup, data.offset).unwrap_or_default();
+    }
+
+    /// Reads entries written by [`Self::write`], rejecting data that doesn't
+    /// match the lookup list of `host`.
+    pub fn read<'a>(host: &impl LookupHost<'a>, reader: &mut Reader) -> Option<Self> {
+        let count = reader.read_len(1)?;
+        if count != usize::from(host.lookup_count()) {
+            return None;
         }
-        entry.subtables_start = self
-            .subtables
-            .len()
-            .try_into()
-            .map_err(|_| ReadError::MalformedData("too many subtables"))?;
-        entry.state = LookupState::Ready;
-        let mut process_subtable = |mut subtable_offset: usize| {
-            let mut subtable_kind = kind;
-            match (data.is_subst, kind) {
-                (true, 7) | (false, 9) => {
-                    let subtable_data = data
-                        .table_data
-                        .split_off(subtable_offset)
-                        .ok_or(ReadError::OutOfBounds)?;
-                    let ext = ExtensionSubstFormat1::<()>::read(subtable_data)?;
-                    subtable_kind = ext.extension_lookup_type();
-                    let ext_offset = ext.extension_offset().to_usize();
-                    subtable_offset += ext_offset;
+        let mut entries = Vec::with_capacity(count);
+        for index in 0..count {
+            let entry = if reader.read_bool()? {
+                let info = LookupInfo::read(reader)?;
+                if usize::from(info.index) != index {
+                    return None;
                 }
-                _ => {}
-            }
-            let mut subtable_info = SubtableInfo {
-                offset: subtable_offset
-                    .try_into()
-                    .map_err(|_| ReadError::OutOfBounds)?,
-                coverage_offset: 0,
-                is_subst: data.is_subst,
-                lookup_type: subtable_kind as u8,
-                digest: hb_set_digest_t::new(),
+                let subtables_count = reader.read_len(SubtableInfo::ENCODED_SIZE)?;
+                let subtables = (0..subtables_count)
+                    .map(|_| SubtableInfo::read(reader))
+                    .collect::<Option<Vec<_>>>()?;
+                Some(LookupEntry { info, subtables })
+            } else {
+                None
             };
-            let subtable = subtable_info.materialize(data.table_data.as_bytes())?;
-            let (coverage, coverage_offset) = subtable.coverage_and_offset()?;
-            subtable_info.digest.add_coverage(&coverage);
-            entry.digest.add_coverage(&coverage);
-            subtable_info.coverage_offset = coverage_offset;
-            self.subtables.push(subtable_info);
-            entry.subtables_count += 1;
-            Ok::<(), ReadError>(())
-        };
-        for subtable_offset in lookup.subtable_offsets() {
-            let subtable_offset = subtable_offset.get().to_usize() + data.offset;
-            // Just drop subtables with errors
-            let _ = process_subtable(subtable_offset);
+            #[cfg(feature = "std")]
+            entries.push(LookupCell::from(entry));
+            #[cfg(not(feature = "std"))]
+            entries.push(LookupCell(entry));
         }
-        Ok(entry)
-    }
-
-    pub fn subtables(&self, entry: &LookupInfo) -> Option<&[SubtableInfo]> {
-        self.subtables.get(entry.subtables_range())
+        Some(Self { entries })
     }
 
     fn load_subtable<'a>(
@@ -219,6 +215,73 @@ impl LookupCache {
     }
 }
 
+fn create_entry<'a>(host: &impl LookupHost<'a>, index: u16) -> Result<LookupEntry, ReadError> {
+    let data = host.lookup_data(index)?;
+    let mut entry = LookupEntry {
+        info: LookupInfo {
+            index,
+            is_subst: data.is_subst,
+            ..Default::default()
+        },
+        subtables: Vec::new(),
+    };
+    let lookup_data = data
+        .table_data
+        .split_off(data.offset)
+        .ok_or(ReadError::OutOfBounds)?;
+    let lookup: Lookup<()> = Lookup::read(lookup_data)?;
+    let kind = lookup.lookup_type();
+    let lookup_flag = lookup.lookup_flag();
+    let info = &mut entry.info;
+    info.props = u32::from(lookup.lookup_flag().to_bits());
+    if lookup_flag.to_bits() & LookupFlag::USE_MARK_FILTERING_SET.to_bits() != 0 {
+        info.props |= (lookup.mark_filtering_set().unwrap_or_default() as u32) << 16;
+    }
+    info.is_rtl = lookup_flag.to_bits() & LookupFlag::RIGHT_TO_LEFT.to_bits() != 0;
+    if data.is_subst {
+        info.is_reversed = is_reversed(data.table_data, &lookup, data.offset).unwrap_or_default();
+    }
+    let mut process_subtable = |mut subtable_offset: usize| {
+        let mut subtable_kind = kind;
+        match (data.is_subst, kind) {
+            (true, 7) | (false, 9) => {
+                let subtable_data = data
+                    .table_data
+                    .split_off(subtable_offset)
+                    .ok_or(ReadError::OutOfBounds)?;
+                let ext = ExtensionSubstFormat1::<()>::read(subtable_data)?;
+                subtable_kind = ext.extension_lookup_type();
+                let ext_offset = ext.extension_offset().to_usize();
+                subtable_offset += ext_offset;
+            }
+            _ => {}
+        }
+        let mut subtable_info = SubtableInfo {
+            offset: subtable_offset
+                .try_into()
+                .map_err(|_| ReadError::OutOfBounds)?,
+            coverage_offset: 0,
+            is_subst: data.is_subst,
+            lookup_type: subtable_kind as u8,
+            digest: hb_set_digest_t::new(),
+        };
+        let subtable = subtable_info.materialize(data.table_data.as_bytes())?;
+        let (coverage, coverage_offset) = subtable.coverage_and_offset()?;
+        subtable_info.digest.add_coverage(&coverage);
+        entry.info.digest.add_coverage(&coverage);
+        subtable_info.coverage_offset = coverage_offset;
+        entry.subtables.push(subtable_info);
+        entry.info.subtables_count += 1;
+        Ok::<(), ReadError>(())
+    };
+    for subtable_offset in lookup.subtable_offsets() {
+        let subtable_offset = subtable_offset.get().to_usize() + data.offset;
+        // Just drop subtables with errors
+        let _ = process_subtable(subtable_offset);
+    }
+    Ok(entry)
+}
+
 fn is_reversed(table_data: FontData, lookup: &Lookup<()>, lookup_offset: usize) -> Option<bool> {
     match lookup.lookup_type() {
         // Reverse chain context
@@ -234,34 +297,18 @@ fn is_reversed(table_data: FontData, lookup: &Lookup<()>, lookup_offset: usize)
     }
 }
 
-/// Current state of a lookup cache entry.
-#[derive(Copy, Clone, PartialEq, Eq, Default, Debug)]
-#[repr(u8)]
-pub enum LookupState {
-    /// Lookup has not been cached yet. This supports
-    /// lazy population of the lookup cache.
-    #[default]
-    Vacant,
-    /// Lookup is available for use.
-    Ready,
-    /// An error occured while reading this lookup.
-    Error,
-}
-
 /// Cached information about a lookup.
 #[derive(Clone, Default)]
 pub struct LookupInfo {
-    /// Current state of this lookup info entry.
-    pub state: LookupState,
+    /// Index of this lookup in the lookup list.
+    pub index: u16,
     pub props: u32,
     pub is_subst: bool,
     /// Indicates RTL processing for cursive lookups.
     pub is_rtl: bool,
     /// True if glyphs should be processed in reverse for this lookup.
     pub is_reversed: bool,
-    /// Index of the first subtable in the cache subtables vector.
-    pub subtables_start: u32,
-    /// Number of subtables in the cache subtables vector.
+    /// Number of subtables in the lookup.
     pub subtables_count: u16,
     /// Bloom filter representing the set of glyphs from the primary
     /// coverage of all subtables in the lookup.
@@ -269,9 +316,26 @@ pub struct LookupInfo {
 }
 
 impl LookupInfo {
-    pub fn subtables_range(&self) -> Range<usize> {
-        let start = self.subtables_start as usize;
-        start..start + self.subtables_count as usize
+    fn write(&self, writer: &mut Writer) {
+        writer.write_u16(self.index);
+        writer.write_u32(self.props);
+        writer.write_bool(self.is_subst);
+        writer.write_bool(self.is_rtl);
+        writer.write_bool(self.is_reversed);
+        writer.write_u16(self.subtables_count);
+        self.digest.write(writer);
+    }
+
+    fn read(reader: &mut Reader) -> Option<Self> {
+        Some(Self {
+            index: reader.read_u16()?,
+            props: reader.read_u32()?,
+            is_subst: reader.read_bool()?,
+            is_rtl: reader.read_bool()?,
+            is_reversed: reader.read_bool()?,
+            subtables_count: reader.read_u16()?,
+            digest: hb_set_digest_t::read(reader)?,
+        })
     }
 
     pub fn props(&self) -> u32 {
@@ -394,6 +458,26 @@ pub struct SubtableInfo {
 }
 
 impl SubtableInfo {
+    const ENCODED_SIZE: usize = 8 + 3 * 8;
+
+    fn write(&self, writer: &mut Writer) {
+        writer.write_u32(self.offset);
+        writer.write_u16(self.coverage_offset);
+        writer.write_bool(self.is_subst);
+        writer.write_u8(self.lookup_type);
+        self.digest.write(writer);
+    }
+
+    fn read(reader: &mut Reader) -> Option<Self> {
+        Some(Self {
+            offset: reader.read_u32()?,
+            coverage_offset: reader.read_u16()?,
+            is_subst: reader.read_bool()?,
+            lookup_type: reader.read_u8()?,
+            digest: hb_set_digest_t::read(reader)?,
+        })
+    }
+
     pub(crate) fn _primary_coverage_table<'a>(
         &self,
         table_data: &'a [u8],
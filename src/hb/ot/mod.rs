@@ -1,11 +1,14 @@
+use super::buffer::GlyphPropsFlags;
+use super::cache::hb_cache_t;
 use super::ot_layout::TableIndex;
+use super::persist::{Reader, Writer};
 use super::{common::TagExt, set_digest::hb_set_digest_t};
 use crate::hb::hb_tag_t;
 use alloc::vec::Vec;
 use lookup::{LookupCache, LookupInfo, SubtableCache};
 use read_fonts::{
     tables::{
-        gdef::Gdef,
+        gdef::{Gdef, LigGlyph},
         gpos::{AnchorTable, DeviceOrVariationIndex, Gpos},
         gsub::{ClassDef, FeatureList, FeatureVariations, Gsub, ScriptList},
         layout::{Feature, LangSys, Script},
@@ -21,29 +24,24 @@ pub mod gpos;
 pub mod gsub;
 pub mod lookup;
 
+pub type glyph_props_cache_t = hb_cache_t<16, 16, 256, 32>;
+
 pub struct OtCache {
     pub gsub: LookupCache,
     pub gpos: LookupCache,
     pub gdef_mark_set_digests: Vec<hb_set_digest_t>,
+    pub gdef_glyph_props_cache: glyph_props_cache_t,
 }
 
 impl OtCache {
     pub fn new(font: &FontRef) -> Self {
         let gsub = font
             .gsub()
-            .map(|t| {
-                let mut cache = LookupCache::new();
-                cache.create_all(&t);
-                cache
-            })
+            .map(|t| LookupCache::new(&t))
             .unwrap_or_default();
         let gpos = font
             .gpos()
-            .map(|t| {
-                let mut cache = LookupCache::new();
-                cache.create_all(&t);
-                cache
-            })
+            .map(|t| LookupCache::new(&t))
             .unwrap_or_default();
         let mut gdef_mark_set_digests = Vec::new();
         if let Ok(gdef) = font.gdef() {
@@ -59,8 +57,47 @@ impl OtCache {
             gsub,
             gpos,
             gdef_mark_set_digests,
+            gdef_glyph_props_cache: glyph_props_cache_t::new(),
         }
     }
+
+    /// Writes the lookup caches and mark set digests. Runtime caches are
+    /// not persisted.
+    pub fn write(&self, font: &FontRef, writer: &mut Writer) {
+        match font.gsub() {
+            Ok(gsub) => self.gsub.write(&gsub, writer),
+            Err(_) => writer.write_len(0),
+        }
+        match font.gpos() {
+            Ok(gpos) => self.gpos.write(&gpos, writer),
+            Err(_) => writer.write_len(0),
+        }
+        writer.write_len(self.gdef_mark_set_digests.len());
+        for digest in &self.gdef_mark_set_digests {
+            digest.write(writer);
+        }
+    }
+
+    pub fn read(font: &FontRef, reader: &mut Reader) -> Option<Self> {
+        let gsub = match font.gsub() {
+            Ok(gsub) => LookupCache::read(&gsub, reader)?,
+            Err(_) => (reader.read_len(1)? == 0).then(LookupCache::default)?,
+        };
+        let gpos = match font.gpos() {
+            Ok(gpos) => LookupCache::read(&gpos, reader)?,
+            Err(_) => (reader.read_len(1)? == 0).then(LookupCache::default)?,
+        };
+        let count = reader.read_len(3 * 8)?;
+        let gdef_mark_set_digests = (0..count)
+            .map(|_| hb_set_digest_t::read(reader))
+            .collect::<Option<Vec<_>>>()?;
+        Some(Self {
+            gsub,
+            gpos,
+            gdef_mark_set_digests,
+            gdef_glyph_props_cache: glyph_props_cache_t::new(),
+        })
+    }
 }
 
 #[derive(Clone)]
@@ -74,7 +111,7 @@ impl crate::hb::ot_layout::LayoutTable for GsubTable<'_> {
     const IN_PLACE: bool = false;
 
     fn get_lookup(&self, index: u16) -> Option<&LookupInfo> {
-        let lookup = self.lookups.get(index)?;
+        let lookup = self.lookups.get(&self.table, index)?;
         (lookup.subtables_count > 0).then_some(lookup)
     }
 }
@@ -90,7 +127,7 @@ impl crate::hb::ot_layout::LayoutTable for GposTable<'_> {
     const IN_PLACE: bool = true;
 
     fn get_lookup(&self, index: u16) -> Option<&LookupInfo> {
-        let lookup = self.lookups.get(index)?;
+        let lookup = self.lookups.get(&self.table, index)?;
         (lookup.subtables_count > 0).then_some(lookup)
     }
 }
@@ -124,6 +161,13 @@ impl<'a> GdefTable<'a> {
             Self::default()
         }
     }
+
+    /// Returns the caret values of a ligature glyph from the `LigCaretList`.
+    pub fn lig_glyph(&self, glyph: GlyphId) -> Option<LigGlyph<'a>> {
+        let list = self.table.as_ref()?.lig_caret_list()?.ok()?;
+        let index = list.coverage().ok()?.get(glyph)?;
+        list.lig_glyphs().get(index as usize).ok()
+    }
 }
 
 #[derive(Clone)]
@@ -132,6 +176,7 @@ pub struct OtTables<'a> {
     pub gpos: Option<GposTable<'a>>,
     pub gdef: GdefTable<'a>,
     pub gdef_mark_set_digests: &'a [hb_set_digest_t],
+    pub gdef_glyph_props_cache: &'a glyph_props_cache_t,
     pub coords: &'a [F2Dot14],
     pub var_store: Option<ItemVariationStore<'a>>,
 }
@@ -164,6 +209,7 @@ impl<'a> OtTables<'a> {
             gpos,
             gdef,
             gdef_mark_set_digests: &cache.gdef_mark_set_digests,
+            gdef_glyph_props_cache: &cache.gdef_glyph_props_cache,
             var_store,
             coords,
         }
@@ -189,6 +235,28 @@ impl<'a> OtTables<'a> {
             .unwrap_or(0)
     }
 
+    /// Returns the glyph properties derived from the GDEF glyph class and
+    /// mark attachment class.
+    pub fn glyph_props(&self, glyph_id: u32) -> u16 {
+        if let Some(props) = self.gdef_glyph_props_cache.get(glyph_id) {
+            return props as u16;
+        }
+        let props = match self.glyph_class(glyph_id) {
+            1 => GlyphPropsFlags::BASE_GLYPH.bits(),
+            2 => GlyphPropsFlags::LIGATURE.bits(),
+            3 => {
+                let class = self.glyph_mark_attachment_class(glyph_id);
+                (class << 8) | GlyphPropsFlags::MARK.bits()
+            }
+            _ => 0,
+        };
+        // Don't populate the cache for a missing table.
+        if self.gdef.table.is_some() {
+            self.gdef_glyph_props_cache.set(glyph_id, u32::from(props));
+        }
+        props
+    }
+
     pub fn is_mark_glyph(&self, glyph_id: u32, set_index: u16) -> bool {
         if self
             .gdef_mark_set_digests
@@ -236,30 +304,57 @@ impl<'a> OtTables<'a> {
         lookup_index: u16,
     ) -> Option<SubtableCache<'a>> {
         let (table_data, lookups) = self.table_data_and_lookups(table_index)?;
-        let lookup = lookups.get(lookup_index)?;
+        let lookup = if table_index == TableIndex::GSUB {
+            lookups.get(&self.gsub.as_ref()?.table, lookup_index)?
+        } else {
+            lookups.get(&self.gpos.as_ref()?.table, lookup_index)?
+        };
         Some(SubtableCache::new(table_data, lookups, lookup.clone()))
     }
 
     pub(super) fn resolve_anchor(&self, anchor: &AnchorTable) -> (i32, i32) {
-        let mut x = anchor.x_coordinate() as i32;
-        let mut y = anchor.y_coordinate() as i32;
-        if let Some(vs) = self.var_store.as_ref() {
-            let delta = |val: Option<Result<DeviceOrVariationIndex<'_>, ReadError>>| match val {
-                Some(Ok(DeviceOrVariationIndex::VariationIndex(varix))) => vs
-                    .compute_delta(
-                        DeltaSetIndex {
-                            outer: varix.delta_set_outer_index(),
-                            inner: varix.delta_set_inner_index(),
-                        },
-                        self.coords,
-                    )
-                    .unwrap_or_default(),
-                _ => 0,
-            };
-            x += delta(anchor.x_device());
-            y += delta(anchor.y_device());
-        }
-        (x, y)
+        let delta = |device: Option<Result<DeviceOrVariationIndex<'_>, ReadError>>| {
+            device
+                .and_then(Result::ok)
+                .map_or(0, |device| self.resolve_device(&device))
+        };
+        (
+            anchor.x_coordinate() as i32 + delta(anchor.x_device()),
+            anchor.y_coordinate() as i32 + delta(anchor.y_device()),
+        )
+    }
+
+    /// Returns the delta of a device or variation index table, using the
+    /// GDEF variation store.
+    pub(crate) fn resolve_device(&self, device: &DeviceOrVariationIndex) -> i32 {
+        device_delta(device, self.var_store.as_ref(), self.coords)
+    }
+}
+
+/// Returns the delta of a device or variation index table.
+///
+/// Variation indices are resolved in `var_store` at `coords`. Device tables
+/// only adjust hinted sizes, so they are ignored everywhere, as in GPOS
+/// value records, since the shaper works in font units.
+pub(crate) fn device_delta(
+    device: &DeviceOrVariationIndex,
+    var_store: Option<&ItemVariationStore>,
+    coords: &[F2Dot14],
+) -> i32 {
+    match device {
+        DeviceOrVariationIndex::VariationIndex(varix) => var_store
+            .and_then(|vs| {
+                vs.compute_delta(
+                    DeltaSetIndex {
+                        outer: varix.delta_set_outer_index(),
+                        inner: varix.delta_set_inner_index(),
+                    },
+                    coords,
+                )
+                .ok()
+            })
+            .unwrap_or_default(),
+        DeviceOrVariationIndex::Device(_) => 0,
     }
 }
 
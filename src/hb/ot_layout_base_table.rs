@@ -0,0 +1,375 @@
+use read_fonts::tables::base::{Axis, Base, BaseCoord, BaseScript};
+use read_fonts::tables::variations::ItemVariationStore;
+use read_fonts::TableProvider;
+
+use super::common::TagExt;
+use super::face::{hb_font_extents_t, hb_font_t};
+use super::hb_tag_t;
+use super::ot::device_delta;
+use super::ot_metrics::MetricsTag;
+use super::tag;
+use crate::{script, Direction, FontExtents, Language, Script, Tag};
+
+/// A baseline, as used by
+/// [`Shaper::baseline`](crate::Shaper::baseline).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BaselineTag {
+    /// The baseline of Latin, Greek and Cyrillic, `romn`.
+    Roman,
+    /// The hanging baseline of Indic scripts like Devanagari, `hang`.
+    Hanging,
+    /// The bottom or left edge of the ideographic character face, `icfb`.
+    IdeoFaceBottomOrLeft,
+    /// The top or right edge of the ideographic character face, `icft`.
+    IdeoFaceTopOrRight,
+    /// The center of the ideographic character face, `Icfc`.
+    IdeoFaceCentral,
+    /// The bottom or left edge of the ideographic em box, `ideo`.
+    IdeoEmboxBottomOrLeft,
+    /// The top or right edge of the ideographic em box, `idtp`.
+    IdeoEmboxTopOrRight,
+    /// The center of the ideographic em box, `Ideo`.
+    IdeoEmboxCentral,
+    /// The baseline about which mathematical characters are centered,
+    /// `math`.
+    Math,
+}
+
+impl BaselineTag {
+    /// Returns the `BASE` tag of the baseline.
+    pub fn tag(self) -> Tag {
+        Tag::new(match self {
+            Self::Roman => b"romn",
+            Self::Hanging => b"hang",
+            Self::IdeoFaceBottomOrLeft => b"icfb",
+            Self::IdeoFaceTopOrRight => b"icft",
+            Self::IdeoFaceCentral => b"Icfc",
+            Self::IdeoEmboxBottomOrLeft => b"ideo",
+            Self::IdeoEmboxTopOrRight => b"idtp",
+            Self::IdeoEmboxCentral => b"Ideo",
+            Self::Math => b"math",
+        })
+    }
+}
+
+impl hb_font_t<'_> {
+    /// Returns the position of a baseline for a script and language in font
+    /// units for the current instance.
+    ///
+    /// The position is a y coordinate for horizontal directions and an x
+    /// coordinate for vertical ones. It is read from the `BASE` table, with
+    /// variation deltas applied, using the script's record or else the
+    /// default one. Device tables only adjust hinted sizes and are ignored.
+    /// Baselines don't depend on the language, which is only used for the
+    /// script tags it may specify.
+    ///
+    /// Missing baselines are synthesized like HarfBuzz's
+    /// `hb_ot_layout_get_baseline_with_fallback`: from the opposite edge of
+    /// the ideographic em box, the font extents, the extents of
+    /// representative glyphs or fixed fractions of the em.
+    pub fn baseline(
+        &self,
+        baseline: BaselineTag,
+        direction: Direction,
+        script: Option<Script>,
+        language: Option<&Language>,
+    ) -> i32 {
+        let (script_tag, _) = ot_tags(script, language);
+        self.baseline_with_fallback(baseline, direction, script, script_tag)
+    }
+
+    /// Returns the extents of the font for a script and language in font
+    /// units for the current instance.
+    ///
+    /// Like HarfBuzz's `hb_ot_layout_get_font_extents`, the ascender and
+    /// descender are the maximum and minimum extents of the `BASE` table for
+    /// the language or else the script's defaults, with no line gap. Fonts
+    /// without them use the ascender, descender and line gap of the font
+    /// for the direction.
+    pub fn font_extents(
+        &self,
+        direction: Direction,
+        script: Option<Script>,
+        language: Option<&Language>,
+    ) -> FontExtents {
+        let (script_tag, language_tag) = ot_tags(script, language);
+        if let Some((min, max)) = self.base_min_max(direction, script_tag, language_tag) {
+            return FontExtents {
+                ascender: max,
+                descender: min,
+                line_gap: 0,
+            };
+        }
+        self.extents_for_direction(direction)
+    }
+
+    /// Returns a baseline from the `BASE` table.
+    fn base_baseline(
+        &self,
+        baseline: BaselineTag,
+        direction: Direction,
+        script_tag: hb_tag_t,
+    ) -> Option<i32> {
+        let base = self.font.base().ok()?;
+        let axis = base_axis(&base, direction)?;
+        let tags = axis.base_tag_list()?.ok()?;
+        let index = tags
+            .baseline_tags()
+            .binary_search_by(|tag| tag.get().cmp(&baseline.tag()))
+            .ok()?;
+        let script = base_script(&axis, script_tag)?;
+        let coord = script.base_values()?.ok()?.base_coords().get(index).ok()?;
+        Some(self.base_coord(&base, &coord))
+    }
+
+    /// Returns the minimum and maximum extents from the `BASE` table.
+    fn base_min_max(
+        &self,
+        direction: Direction,
+        script_tag: hb_tag_t,
+        language_tag: hb_tag_t,
+    ) -> Option<(i32, i32)> {
+        let base = self.font.base().ok()?;
+        let axis = base_axis(&base, direction)?;
+        let script = base_script(&axis, script_tag)?;
+        let records = script.base_lang_sys_records();
+        let min_max = match records
+            .binary_search_by(|record| record.base_lang_sys_tag().cmp(&language_tag))
+        {
+            Ok(i) => records[i].min_max(script.offset_data()).ok()?,
+            Err(_) => script.default_min_max()?.ok()?,
+        };
+        let coord = |coord: Option<Result<BaseCoord, _>>| {
+            coord
+                .and_then(Result::ok)
+                .map_or(0, |coord| self.base_coord(&base, &coord))
+        };
+        Some((coord(min_max.min_coord()), coord(min_max.max_coord())))
+    }
+
+    fn base_coord(&self, base: &Base, coord: &BaseCoord) -> i32 {
+        let value = i32::from(coord.coordinate());
+        let BaseCoord::Format3(coord) = coord else {
+            return value;
+        };
+        let Some(Ok(device)) = coord.device() else {
+            return value;
+        };
+        let coords = self.ot_tables.coords;
+        let var_store: Option<ItemVariationStore> = if coords.is_empty() {
+            None
+        } else {
+            base.item_var_store().and_then(Result::ok)
+        };
+        value + device_delta(&device, var_store.as_ref(), coords)
+    }
+
+    fn baseline_with_fallback(
+        &self,
+        baseline: BaselineTag,
+        direction: Direction,
+        script: Option<Script>,
+        script_tag: hb_tag_t,
+    ) -> i32 {
+        if let Some(coord) = self.base_baseline(baseline, direction, script_tag) {
+            return coord;
+        }
+
+        // Synthesize missing baselines.
+        // See https://www.w3.org/TR/css-inline-3/#baseline-synthesis-fonts
+        let upem = i32::from(self.units_per_em);
+        let fallback =
+            |baseline| self.baseline_with_fallback(baseline, direction, script, script_tag);
+        match baseline {
+            BaselineTag::Roman => 0,
+            BaselineTag::Math => {
+                let glyph = self
+                    .charmap()
+                    .map('\u{2212}')
+                    .or_else(|| self.charmap().map('-'));
+                match glyph.and_then(|glyph| self.glyph_extents(glyph)) {
+                    Some(extents) if !is_vertical(direction) => {
+                        extents.y_bearing + extents.height / 2
+                    }
+                    _ => self.x_height_with_fallback() / 2,
+                }
+            }
+            BaselineTag::IdeoFaceTopOrRight | BaselineTag::IdeoFaceBottomOrLeft => {
+                let embox_top = fallback(BaselineTag::IdeoEmboxTopOrRight);
+                let embox_bottom = fallback(BaselineTag::IdeoEmboxBottomOrLeft);
+                if baseline == BaselineTag::IdeoFaceTopOrRight {
+                    embox_top + (embox_bottom - embox_top) / 10
+                } else {
+                    embox_bottom + (embox_top - embox_bottom) / 10
+                }
+            }
+            BaselineTag::IdeoEmboxTopOrRight => {
+                match self.base_baseline(BaselineTag::IdeoEmboxBottomOrLeft, direction, script_tag)
+                {
+                    Some(bottom) => bottom + upem,
+                    None => self.extents_for_direction(direction).ascender,
+                }
+            }
+            BaselineTag::IdeoEmboxBottomOrLeft => {
+                match self.base_baseline(BaselineTag::IdeoEmboxTopOrRight, direction, script_tag) {
+                    Some(top) => top - upem,
+                    None => self.extents_for_direction(direction).descender,
+                }
+            }
+            BaselineTag::Hanging => {
+                if is_vertical(direction) {
+                    return upem * 6 / 10;
+                }
+                script
+                    .and_then(hanging_char)
+                    .and_then(|c| self.charmap().map(c))
+                    .and_then(|glyph| self.glyph_extents(glyph))
+                    .map_or(upem * 6 / 10, |extents| extents.y_bearing)
+            }
+            BaselineTag::IdeoFaceCentral => {
+                let top = fallback(BaselineTag::IdeoFaceTopOrRight);
+                let bottom = fallback(BaselineTag::IdeoFaceBottomOrLeft);
+                (top + bottom) / 2
+            }
+            BaselineTag::IdeoEmboxCentral => {
+                let top = fallback(BaselineTag::IdeoEmboxTopOrRight);
+                let bottom = fallback(BaselineTag::IdeoEmboxBottomOrLeft);
+                (top + bottom) / 2
+            }
+        }
+    }
+
+    /// Returns the x-height, falling back to the top of `x` or half an em.
+    fn x_height_with_fallback(&self) -> i32 {
+        self.metric(MetricsTag::XHeight)
+            .or_else(|| {
+                let glyph = self.charmap().map('x')?;
+                Some(self.glyph_extents(glyph)?.y_bearing)
+            })
+            .unwrap_or(i32::from(self.units_per_em) / 2)
+    }
+
+    /// Returns the ascender, descender and line gap of the font for a
+    /// direction, like HarfBuzz's `hb_font_get_extents_for_direction`.
+    pub(crate) fn extents_for_direction(&self, direction: Direction) -> hb_font_extents_t {
+        let upem = i32::from(self.units_per_em);
+        let (ascender, descender, line_gap) = if is_vertical(direction) {
+            (
+                MetricsTag::VerticalAscender,
+                MetricsTag::VerticalDescender,
+                MetricsTag::VerticalLineGap,
+            )
+        } else {
+            (
+                MetricsTag::HorizontalAscender,
+                MetricsTag::HorizontalDescender,
+                MetricsTag::HorizontalLineGap,
+            )
+        };
+        match (
+            self.metric(ascender),
+            self.metric(descender),
+            self.metric(line_gap),
+        ) {
+            (Some(ascender), Some(descender), Some(line_gap)) => hb_font_extents_t {
+                ascender,
+                descender,
+                line_gap,
+            },
+            _ => {
+                let ascender = if is_vertical(direction) {
+                    upem / 2
+                } else {
+                    upem * 8 / 10
+                };
+                hb_font_extents_t {
+                    ascender,
+                    descender: ascender - upem,
+                    line_gap: 0,
+                }
+            }
+        }
+    }
+}
+
+fn is_vertical(direction: Direction) -> bool {
+    matches!(direction, Direction::TopToBottom | Direction::BottomToTop)
+}
+
+/// Returns the OpenType script and language tags `BASE` records are looked
+/// up with: the last of the candidate tags, like HarfBuzz's
+/// `hb_ot_layout_get_baseline2`.
+fn ot_tags(script: Option<Script>, language: Option<&Language>) -> (hb_tag_t, hb_tag_t) {
+    let (scripts, languages) = tag::tags_from_script_and_language(script, language);
+    (
+        scripts
+            .last()
+            .copied()
+            .unwrap_or(hb_tag_t::default_script()),
+        languages
+            .last()
+            .copied()
+            .unwrap_or(hb_tag_t::default_language()),
+    )
+}
+
+fn base_axis<'a>(base: &Base<'a>, direction: Direction) -> Option<Axis<'a>> {
+    if is_vertical(direction) {
+        base.vert_axis()?.ok()
+    } else {
+        base.horiz_axis()?.ok()
+    }
+}
+
+/// Returns the record of a script, or else the default one.
+fn base_script<'a>(axis: &Axis<'a>, script_tag: hb_tag_t) -> Option<BaseScript<'a>> {
+    let list = axis.base_script_list().ok()?;
+    let records = list.base_script_records();
+    let find = |tag: hb_tag_t| {
+        records
+            .binary_search_by(|record| record.base_script_tag().cmp(&tag))
+            .ok()
+    };
+    let index = find(script_tag).or_else(|| find(hb_tag_t::default_script()))?;
+    records[index].base_script(list.offset_data()).ok()
+}
+
+/// Returns a character whose top is at the hanging baseline.
+fn hanging_char(script: Script) -> Option<char> {
+    Some(match script {
+        // Unicode-1.1 additions
+        script::BENGALI => '\u{0995}',
+        script::DEVANAGARI => '\u{0915}',
+        script::GUJARATI => '\u{0A95}',
+        script::GURMUKHI => '\u{0A15}',
+        // Unicode-2.0 additions
+        script::TIBETAN => '\u{0F40}',
+        // Unicode-4.0 additions
+        script::LIMBU => '\u{1901}',
+        // Unicode-4.1 additions
+        script::SYLOTI_NAGRI => '\u{A807}',
+        // Unicode-5.0 additions
+        script::PHAGS_PA => '\u{A840}',
+        // Unicode-5.2 additions
+        script::MEETEI_MAYEK => '\u{ABC0}',
+        // Unicode-6.1 additions
+        script::SHARADA => '\u{11191}',
+        script::TAKRI => '\u{1168C}',
+        // Unicode-7.0 additions
+        script::MODI => '\u{1160E}',
+        script::SIDDHAM => '\u{11590}',
+        script::TIRHUTA => '\u{1148F}',
+        // Unicode-9.0 additions
+        script::MARCHEN => '\u{11C72}',
+        script::NEWA => '\u{1140E}',
+        // Unicode-10.0 additions
+        script::SOYOMBO => '\u{11A5C}',
+        script::ZANABAZAR_SQUARE => '\u{11A0B}',
+        // Unicode-11.0 additions
+        script::DOGRA => '\u{1180A}',
+        script::GUNJALA_GONDI => '\u{11D6C}',
+        // Unicode-12.0 additions
+        script::NANDINAGARI => '\u{119B0}',
+        _ => return None,
+    })
+}
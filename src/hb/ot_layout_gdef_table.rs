@@ -0,0 +1,38 @@
+use alloc::vec::Vec;
+use read_fonts::tables::gdef::CaretValue;
+use read_fonts::types::GlyphId;
+
+use super::common::Direction;
+use super::hb_font_t;
+
+/// Appends the carets of a ligature glyph from the GDEF `LigCaretList` to
+/// `carets`. Returns `false` if the glyph has no caret values.
+pub(crate) fn get_lig_carets(
+    face: &hb_font_t,
+    direction: Direction,
+    glyph: GlyphId,
+    carets: &mut Vec<i32>,
+) -> bool {
+    let Some(lig_glyph) = face.ot_tables.gdef.lig_glyph(glyph) else {
+        return false;
+    };
+
+    let start = carets.len();
+    for caret in lig_glyph.caret_values().iter().flatten() {
+        let position = match caret {
+            CaretValue::Format1(caret) => i32::from(caret.coordinate()),
+            CaretValue::Format2(caret) => face
+                .glyph_contour_point_for_origin(glyph, caret.caret_value_point_index(), direction)
+                .map(|(x, y)| if direction.is_horizontal() { x } else { y })
+                .unwrap_or_default(),
+            CaretValue::Format3(caret) => {
+                let delta = caret
+                    .device()
+                    .map_or(0, |device| face.ot_tables.resolve_device(&device));
+                i32::from(caret.coordinate()) + delta
+            }
+        };
+        carets.push(position);
+    }
+    carets.len() > start
+}
@@ -2,6 +2,8 @@
 #[allow(unused_imports)]
 use core_maths::CoreFloat;
 
+use alloc::vec::Vec;
+
 use super::buffer::*;
 use super::hb_font_t;
 use super::ot_layout::*;
@@ -17,6 +19,21 @@ pub mod attach_type {
     pub const CURSIVE: u8 = 2;
 }
 
+/// The kind of a mark attachment, kept for
+/// [`BufferFlags::PRESERVE_ATTACHMENTS`](crate::BufferFlags::PRESERVE_ATTACHMENTS).
+///
+/// The low two bits hold the lookup type and the others the ligature
+/// component of marks attached to ligatures.
+pub mod attach_kind {
+    pub const MARK_TO_BASE: u8 = 0;
+    pub const MARK_TO_LIGATURE: u8 = 1;
+    pub const MARK_TO_MARK: u8 = 2;
+
+    pub fn mark_to_ligature(component: u16) -> u8 {
+        MARK_TO_LIGATURE | (component.min(0x3F) as u8) << 2
+    }
+}
+
 fn propagate_attachment_offsets(
     pos: &mut [GlyphPosition],
     len: usize,
@@ -88,9 +105,20 @@ pub mod GPOS {
 
         // Handle attachments
         if buffer.scratch_flags & HB_BUFFER_SCRATCH_FLAG_HAS_GPOS_ATTACHMENT != 0 {
+            // Propagation clears the chains, so save them if they are to be kept.
+            let chains: Vec<i16> = if buffer.preserves_attachments() {
+                buffer.pos[..len].iter().map(|p| p.attach_chain()).collect()
+            } else {
+                Vec::new()
+            };
+
             for i in 0..len {
                 propagate_attachment_offsets(&mut buffer.pos, len, i, direction);
             }
+
+            for (pos, chain) in buffer.pos.iter_mut().zip(chains) {
+                pos.set_attach_chain(chain);
+            }
         }
     }
 }
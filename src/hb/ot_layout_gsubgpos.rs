@@ -630,9 +630,8 @@ pub mod OT {
             // match_props has the set index.
             if lookup_flags & lookup_flags::USE_MARK_FILTERING_SET != 0 {
                 let set_index = (match_props >> 16) as u16;
-                // TODO: harfbuzz uses a digest here to speed things up if HB_NO_GDEF_CACHE
-                // is enabled. But a bit harder to implement for us since it's taken care of by
-                // ttf-parser
+                // The mark set digests reject most glyphs before touching the
+                // coverage table.
                 return face
                     .ot_tables
                     .is_mark_glyph(info.as_glyph().to_u32(), set_index);
@@ -694,6 +693,20 @@ pub mod OT {
             }
         }
 
+        /// Calls `f` with a match positions array of length 4, reusing the
+        /// storage kept in the buffer.
+        pub fn with_match_positions<R>(
+            &mut self,
+            f: impl FnOnce(&mut Self, &mut smallvec::SmallVec<[usize; 4]>) -> R,
+        ) -> R {
+            let mut match_positions = self.buffer.match_positions.pop().unwrap_or_default();
+            match_positions.clear();
+            match_positions.resize(4, 0);
+            let result = f(self, &mut match_positions);
+            self.buffer.match_positions.push(match_positions);
+            result
+        }
+
         pub fn random_number(&mut self) -> u32 {
             // http://www.cplusplus.com/reference/random/minstd_rand/
             self.random_state = self.random_state.wrapping_mul(48271) % 2147483647;
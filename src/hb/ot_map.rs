@@ -132,6 +132,15 @@ impl hb_ot_map_t {
         &self.lookups[table_index][index]
     }
 
+    /// Returns `true` if any substitution lookup picks random alternates,
+    /// which makes the result depend on all preceding glyphs.
+    #[inline]
+    pub fn has_random_lookups(&self) -> bool {
+        self.lookups[TableIndex::GSUB]
+            .iter()
+            .any(|lookup| lookup.random)
+    }
+
     #[inline]
     pub fn stage_lookups(&self, table_index: TableIndex, stage: usize) -> &[lookup_map_t] {
         &self.lookups[table_index][self.stage_lookup_range(table_index, stage)]
@@ -478,15 +487,14 @@ impl<'a> hb_ot_map_builder_t<'a> {
         let mut map_lookups = [Vec::new(), Vec::new()];
         let mut map_stages = [Vec::new(), Vec::new()];
 
+        let variation_indices = self.face.feature_variation_indices();
+
         for table_index in TableIndex::iter() {
             // Collect lookup indices for features.
             let mut stage_index = 0;
             let mut last_lookup = 0;
 
-            let variation_index = self
-                .face
-                .layout_table(table_index)
-                .and_then(|t| t.feature_variation_index(self.face.ot_tables.coords));
+            let variation_index = variation_indices[table_index as usize];
 
             for stage in 0..self.current_stage[table_index] {
                 if let Some(feature_index) = required_feature_index[table_index] {
@@ -0,0 +1,246 @@
+use read_fonts::tables::os2::SelectionFlags;
+use read_fonts::types::Fixed;
+use read_fonts::TableProvider;
+
+use super::face::hb_font_t;
+use crate::Tag;
+
+/// A font-wide metric, as returned by
+/// [`Shaper::metric`](crate::Shaper::metric).
+///
+/// Each metric is identified by its `MVAR` value tag.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MetricsTag {
+    /// Horizontal ascender, from the OS/2 typographic ascender if
+    /// `USE_TYPO_METRICS` is set, otherwise from `hhea`.
+    HorizontalAscender,
+    /// Horizontal descender, negative, from the same table as the
+    /// ascender.
+    HorizontalDescender,
+    /// Horizontal line gap, from the same table as the ascender.
+    HorizontalLineGap,
+    /// OS/2 Windows ascent.
+    HorizontalClippingAscent,
+    /// OS/2 Windows descent, positive.
+    HorizontalClippingDescent,
+    /// Vertical ascender from `vhea`.
+    VerticalAscender,
+    /// Vertical descender from `vhea`, negative.
+    VerticalDescender,
+    /// Vertical line gap from `vhea`.
+    VerticalLineGap,
+    /// Horizontal caret slope rise from `hhea`.
+    HorizontalCaretRise,
+    /// Horizontal caret slope run from `hhea`.
+    HorizontalCaretRun,
+    /// Horizontal caret offset from `hhea`.
+    HorizontalCaretOffset,
+    /// Vertical caret slope rise from `vhea`.
+    VerticalCaretRise,
+    /// Vertical caret slope run from `vhea`.
+    VerticalCaretRun,
+    /// Vertical caret offset from `vhea`.
+    VerticalCaretOffset,
+    /// OS/2 x-height, from version 2.
+    XHeight,
+    /// OS/2 cap height, from version 2.
+    CapHeight,
+    /// OS/2 subscript horizontal size.
+    SubscriptEmXSize,
+    /// OS/2 subscript vertical size.
+    SubscriptEmYSize,
+    /// OS/2 subscript horizontal offset.
+    SubscriptEmXOffset,
+    /// OS/2 subscript vertical offset.
+    SubscriptEmYOffset,
+    /// OS/2 superscript horizontal size.
+    SuperscriptEmXSize,
+    /// OS/2 superscript vertical size.
+    SuperscriptEmYSize,
+    /// OS/2 superscript horizontal offset.
+    SuperscriptEmXOffset,
+    /// OS/2 superscript vertical offset.
+    SuperscriptEmYOffset,
+    /// OS/2 strikeout thickness.
+    StrikeoutSize,
+    /// OS/2 strikeout position.
+    StrikeoutOffset,
+    /// `post` underline thickness.
+    UnderlineSize,
+    /// `post` underline position.
+    UnderlineOffset,
+}
+
+impl MetricsTag {
+    /// All metrics, in the order of HarfBuzz's `hb_ot_metrics_tag_t`.
+    pub const ALL: [MetricsTag; 28] = [
+        Self::HorizontalAscender,
+        Self::HorizontalDescender,
+        Self::HorizontalLineGap,
+        Self::HorizontalClippingAscent,
+        Self::HorizontalClippingDescent,
+        Self::VerticalAscender,
+        Self::VerticalDescender,
+        Self::VerticalLineGap,
+        Self::HorizontalCaretRise,
+        Self::HorizontalCaretRun,
+        Self::HorizontalCaretOffset,
+        Self::VerticalCaretRise,
+        Self::VerticalCaretRun,
+        Self::VerticalCaretOffset,
+        Self::XHeight,
+        Self::CapHeight,
+        Self::SubscriptEmXSize,
+        Self::SubscriptEmYSize,
+        Self::SubscriptEmXOffset,
+        Self::SubscriptEmYOffset,
+        Self::SuperscriptEmXSize,
+        Self::SuperscriptEmYSize,
+        Self::SuperscriptEmXOffset,
+        Self::SuperscriptEmYOffset,
+        Self::StrikeoutSize,
+        Self::StrikeoutOffset,
+        Self::UnderlineSize,
+        Self::UnderlineOffset,
+    ];
+
+    /// Returns the `MVAR` value tag of the metric.
+    pub fn tag(self) -> Tag {
+        Tag::new(match self {
+            Self::HorizontalAscender => b"hasc",
+            Self::HorizontalDescender => b"hdsc",
+            Self::HorizontalLineGap => b"hlgp",
+            Self::HorizontalClippingAscent => b"hcla",
+            Self::HorizontalClippingDescent => b"hcld",
+            Self::VerticalAscender => b"vasc",
+            Self::VerticalDescender => b"vdsc",
+            Self::VerticalLineGap => b"vlgp",
+            Self::HorizontalCaretRise => b"hcrs",
+            Self::HorizontalCaretRun => b"hcrn",
+            Self::HorizontalCaretOffset => b"hcof",
+            Self::VerticalCaretRise => b"vcrs",
+            Self::VerticalCaretRun => b"vcrn",
+            Self::VerticalCaretOffset => b"vcof",
+            Self::XHeight => b"xhgt",
+            Self::CapHeight => b"cpht",
+            Self::SubscriptEmXSize => b"sbxs",
+            Self::SubscriptEmYSize => b"sbys",
+            Self::SubscriptEmXOffset => b"sbxo",
+            Self::SubscriptEmYOffset => b"sbyo",
+            Self::SuperscriptEmXSize => b"spxs",
+            Self::SuperscriptEmYSize => b"spys",
+            Self::SuperscriptEmXOffset => b"spxo",
+            Self::SuperscriptEmYOffset => b"spyo",
+            Self::StrikeoutSize => b"strs",
+            Self::StrikeoutOffset => b"stro",
+            Self::UnderlineSize => b"unds",
+            Self::UnderlineOffset => b"undo",
+        })
+    }
+
+    /// Returns the metric with the given `MVAR` value tag.
+    pub fn from_tag(tag: Tag) -> Option<Self> {
+        Self::ALL.into_iter().find(|metric| metric.tag() == tag)
+    }
+}
+
+impl hb_font_t<'_> {
+    /// Returns a font-wide metric in font units for the current instance.
+    ///
+    /// Like HarfBuzz's `hb_ot_metrics_get_position`, the value is read from
+    /// the `OS/2`, `hhea`, `vhea` or `post` table, with the `MVAR` delta for
+    /// the current coordinates added. Returns `None` if the table the metric
+    /// comes from is missing, or for the x-height and cap height, if the
+    /// `OS/2` table is older than version 2.
+    ///
+    /// The horizontal ascender, descender and line gap come from the `OS/2`
+    /// typographic metrics if its `USE_TYPO_METRICS` flag is set, and from
+    /// `hhea` otherwise or if there is no `OS/2` table. Ascenders are made
+    /// positive and descenders negative.
+    pub fn metric(&self, metric: MetricsTag) -> Option<i32> {
+        let value = match metric {
+            MetricsTag::HorizontalAscender
+            | MetricsTag::HorizontalDescender
+            | MetricsTag::HorizontalLineGap => self.horizontal_line_metric(metric)?,
+            MetricsTag::HorizontalClippingAscent => self.font.os2().ok()?.us_win_ascent().into(),
+            MetricsTag::HorizontalClippingDescent => self.font.os2().ok()?.us_win_descent().into(),
+            MetricsTag::VerticalAscender => self.font.vhea().ok()?.ascender().to_i16().into(),
+            MetricsTag::VerticalDescender => self.font.vhea().ok()?.descender().to_i16().into(),
+            MetricsTag::VerticalLineGap => self.font.vhea().ok()?.line_gap().to_i16().into(),
+            MetricsTag::HorizontalCaretRise => self.font.hhea().ok()?.caret_slope_rise().into(),
+            MetricsTag::HorizontalCaretRun => self.font.hhea().ok()?.caret_slope_run().into(),
+            MetricsTag::HorizontalCaretOffset => self.font.hhea().ok()?.caret_offset().into(),
+            MetricsTag::VerticalCaretRise => self.font.vhea().ok()?.caret_slope_rise().into(),
+            MetricsTag::VerticalCaretRun => self.font.vhea().ok()?.caret_slope_run().into(),
+            MetricsTag::VerticalCaretOffset => self.font.vhea().ok()?.caret_offset().into(),
+            MetricsTag::XHeight => self.font.os2().ok()?.sx_height()?.into(),
+            MetricsTag::CapHeight => self.font.os2().ok()?.s_cap_height()?.into(),
+            MetricsTag::SubscriptEmXSize => self.font.os2().ok()?.y_subscript_x_size().into(),
+            MetricsTag::SubscriptEmYSize => self.font.os2().ok()?.y_subscript_y_size().into(),
+            MetricsTag::SubscriptEmXOffset => self.font.os2().ok()?.y_subscript_x_offset().into(),
+            MetricsTag::SubscriptEmYOffset => self.font.os2().ok()?.y_subscript_y_offset().into(),
+            MetricsTag::SuperscriptEmXSize => self.font.os2().ok()?.y_superscript_x_size().into(),
+            MetricsTag::SuperscriptEmYSize => self.font.os2().ok()?.y_superscript_y_size().into(),
+            MetricsTag::SuperscriptEmXOffset => {
+                self.font.os2().ok()?.y_superscript_x_offset().into()
+            }
+            MetricsTag::SuperscriptEmYOffset => {
+                self.font.os2().ok()?.y_superscript_y_offset().into()
+            }
+            MetricsTag::StrikeoutSize => self.font.os2().ok()?.y_strikeout_size().into(),
+            MetricsTag::StrikeoutOffset => self.font.os2().ok()?.y_strikeout_position().into(),
+            MetricsTag::UnderlineSize => {
+                self.font.post().ok()?.underline_thickness().to_i16().into()
+            }
+            MetricsTag::UnderlineOffset => {
+                self.font.post().ok()?.underline_position().to_i16().into()
+            }
+        };
+
+        let value = (Fixed::from_i32(value) + self.metric_delta(metric)).to_i32();
+        Some(match metric {
+            MetricsTag::HorizontalAscender | MetricsTag::VerticalAscender => value.abs(),
+            MetricsTag::HorizontalDescender | MetricsTag::VerticalDescender => -value.abs(),
+            _ => value,
+        })
+    }
+
+    /// Returns the raw horizontal ascender, descender or line gap, in the
+    /// fallback order of HarfBuzz's `_hb_ot_metrics_get_position_common`.
+    fn horizontal_line_metric(&self, metric: MetricsTag) -> Option<i32> {
+        if let Ok(os2) = self.font.os2() {
+            if os2
+                .fs_selection()
+                .contains(SelectionFlags::USE_TYPO_METRICS)
+            {
+                return Some(match metric {
+                    MetricsTag::HorizontalAscender => os2.s_typo_ascender(),
+                    MetricsTag::HorizontalDescender => os2.s_typo_descender(),
+                    _ => os2.s_typo_line_gap(),
+                } as i32);
+            }
+        }
+        let hhea = self.font.hhea().ok()?;
+        Some(
+            match metric {
+                MetricsTag::HorizontalAscender => hhea.ascender(),
+                MetricsTag::HorizontalDescender => hhea.descender(),
+                _ => hhea.line_gap(),
+            }
+            .to_i16() as i32,
+        )
+    }
+
+    /// Returns the `MVAR` delta of a metric for the current coordinates.
+    fn metric_delta(&self, metric: MetricsTag) -> Fixed {
+        let coords = self.ot_tables.coords;
+        if coords.is_empty() {
+            return Fixed::ZERO;
+        }
+        self.font
+            .mvar()
+            .ok()
+            .and_then(|mvar| mvar.metric_delta(metric.tag(), coords).ok())
+            .unwrap_or_default()
+    }
+}
@@ -2,6 +2,10 @@ use super::buffer::*;
 use super::ot_layout::*;
 use super::ot_layout_gpos_table::GPOS;
 use super::ot_map::*;
+use super::ot_shape_normalize::{
+    HB_OT_SHAPE_NORMALIZATION_MODE_AUTO, HB_OT_SHAPE_NORMALIZATION_MODE_COMPOSED_DIACRITICS,
+    HB_OT_SHAPE_NORMALIZATION_MODE_NONE,
+};
 use super::ot_shape_plan::hb_ot_shape_plan_t;
 use super::ot_shaper::*;
 use super::unicode::{hb_unicode_general_category_t, CharExt, GeneralCategoryExt};
@@ -24,6 +28,7 @@ pub struct hb_ot_shape_planner_t<'a> {
     pub face: &'a hb_font_t<'a>,
     pub direction: Direction,
     pub script: Option<Script>,
+    pub language: Option<Language>,
     pub ot_map: hb_ot_map_builder_t<'a>,
     pub apply_morx: bool,
     pub script_zero_marks: bool,
@@ -65,6 +70,7 @@ impl<'a> hb_ot_shape_planner_t<'a> {
             face,
             direction,
             script,
+            language: language.cloned(),
             ot_map,
             apply_morx,
             script_zero_marks,
@@ -263,10 +269,24 @@ impl<'a> hb_ot_shape_planner_t<'a> {
                 .iter()
                 .any(|table| table.tag() == "STAT");
 
+        // Text preprocessing may insert non-ASCII characters, and some
+        // normalization modes always decompose.
+        let ascii_fast_path = self.shaper.preprocess_text.is_none()
+            && matches!(
+                self.shaper.normalization_preference,
+                HB_OT_SHAPE_NORMALIZATION_MODE_NONE
+                    | HB_OT_SHAPE_NORMALIZATION_MODE_COMPOSED_DIACRITICS
+                    | HB_OT_SHAPE_NORMALIZATION_MODE_AUTO
+            );
+
         let mut plan = hb_ot_shape_plan_t {
             direction: self.direction,
             script: self.script,
+            language: self.language,
             shaper: self.shaper,
+            variation_indices: self.face.feature_variation_indices(),
+            #[cfg(feature = "std")]
+            id: hb_ot_shape_plan_t::next_id(),
             ot_map,
             data: None,
             frac_mask,
@@ -288,6 +308,7 @@ impl<'a> hb_ot_shape_planner_t<'a> {
             apply_kerx,
             apply_morx,
             apply_trak,
+            ascii_fast_path,
         };
 
         if let Some(func) = self.shaper.create_data {
@@ -312,10 +333,15 @@ pub fn shape_internal(ctx: &mut hb_ot_shape_context_t) {
     ctx.buffer.enter();
 
     initialize_masks(ctx);
-    set_unicode_props(ctx.buffer);
-    insert_dotted_circle(ctx.buffer, ctx.face);
+    if ctx.plan.ascii_fast_path && is_ascii(ctx.buffer) {
+        // No graphemes to form and no marks to attach to a dotted circle.
+        set_ascii_unicode_props(ctx.buffer);
+    } else {
+        set_unicode_props(ctx.buffer);
+        insert_dotted_circle(ctx.buffer, ctx.face);
 
-    form_clusters(ctx.buffer);
+        form_clusters(ctx.buffer);
+    }
 
     ensure_native_direction(ctx.buffer);
 
@@ -395,6 +421,14 @@ fn position(ctx: &mut hb_ot_shape_context_t) {
 
     if ctx.buffer.direction.is_backward() {
         ctx.buffer.reverse();
+
+        // Attachment chains are relative to the glyph.
+        if ctx.buffer.preserves_attachments() {
+            let len = ctx.buffer.len;
+            for pos in &mut ctx.buffer.pos[..len] {
+                pos.set_attach_chain(pos.attach_chain().wrapping_neg());
+            }
+        }
     }
 }
 
@@ -673,6 +707,20 @@ pub(crate) fn syllabic_clear_var(
     false
 }
 
+fn is_ascii(buffer: &hb_buffer_t) -> bool {
+    buffer.info[..buffer.len]
+        .iter()
+        .all(|info| info.glyph_id < 0x80)
+}
+
+/// ASCII has no marks, joiners, emoji or other grapheme extenders, so only
+/// the general category needs to be set.
+fn set_ascii_unicode_props(buffer: &mut hb_buffer_t) {
+    for info in &mut buffer.info[..buffer.len] {
+        info.init_unicode_props(&mut buffer.scratch_flags);
+    }
+}
+
 fn insert_dotted_circle(buffer: &mut hb_buffer_t, face: &hb_font_t) {
     if !buffer
         .flags
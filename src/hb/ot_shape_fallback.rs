@@ -137,7 +137,7 @@ fn position_mark(
     combining_class: u8,
 ) {
     let mut mark_extents = hb_glyph_extents_t::default();
-    if !face.glyph_extents(glyph, &mut mark_extents) {
+    if !face.get_glyph_extents(glyph, &mut mark_extents) {
         return;
     };
 
@@ -267,7 +267,7 @@ fn position_around_base(
     let base_glyph = base_info.as_glyph();
 
     let mut base_extents = hb_glyph_extents_t::default();
-    if !face.glyph_extents(base_glyph, &mut base_extents) {
+    if !face.get_glyph_extents(base_glyph, &mut base_extents) {
         zero_mark_advances(buffer, base + 1, end, adjust_offsets_when_zeroing);
         return;
     };
@@ -280,6 +280,22 @@ fn compare_combining_class(pa: &hb_glyph_info_t, pb: &hb_glyph_info_t) -> bool {
     a > b
 }
 
+/// Maps an ASCII-only buffer to glyphs in place. Returns `false` if the
+/// buffer contains other characters or characters not supported by the
+/// font. Characters may be left mapped in that case.
+fn map_ascii_glyphs(buffer: &mut hb_buffer_t, face: &hb_font_t) -> bool {
+    for info in &mut buffer.info[..buffer.len] {
+        if info.glyph_id >= 0x80 {
+            return false;
+        }
+        match face.get_nominal_glyph(info.glyph_id) {
+            Some(glyph_id) => info.set_glyph_index(u32::from(glyph_id)),
+            None => return false,
+        }
+    }
+    true
+}
+
 pub fn _hb_ot_shape_normalize(
     plan: &hb_ot_shape_plan_t,
     buffer: &mut hb_buffer_t,
@@ -300,6 +316,13 @@ pub fn _hb_ot_shape_normalize(
         }
     }
 
+    // ASCII characters are never marks and never decompose, so if the font
+    // supports all of them, the first round would only map them to glyphs
+    // and the other rounds would do nothing.
+    if plan.ascii_fast_path && map_ascii_glyphs(buffer, face) {
+        return;
+    }
+
     let mut ctx = hb_ot_shape_normalize_context_t {
         plan,
         buffer,
@@ -1,6 +1,10 @@
 use alloc::boxed::Box;
+#[cfg(feature = "std")]
+use alloc::vec::Vec;
 use core::any::Any;
 
+#[cfg(feature = "std")]
+use super::hb_tag_t;
 use super::ot_map::*;
 use super::ot_shape::*;
 use super::ot_shaper::*;
@@ -10,7 +14,14 @@ use super::{hb_font_t, hb_mask_t, Direction, Feature, Language, Script};
 pub struct hb_ot_shape_plan_t {
     pub(crate) direction: Direction,
     pub(crate) script: Option<Script>,
+    pub(crate) language: Option<Language>,
     pub(crate) shaper: &'static hb_ot_shaper_t,
+    /// The FeatureVariations record selected for GSUB and GPOS by the
+    /// instance the plan was created for.
+    pub(crate) variation_indices: [Option<u32>; 2],
+    /// Unique identifier of the plan, used as a key by the word cache.
+    #[cfg(feature = "std")]
+    pub(crate) id: usize,
     pub(crate) ot_map: hb_ot_map_t,
     pub(crate) data: Option<Box<dyn Any + Send + Sync>>,
 
@@ -35,6 +46,10 @@ pub struct hb_ot_shape_plan_t {
     pub(crate) apply_kerx: bool,
     pub(crate) apply_morx: bool,
     pub(crate) apply_trak: bool,
+
+    /// Whether ASCII-only buffers can skip grapheme setup and full
+    /// normalization.
+    pub(crate) ascii_fast_path: bool,
 }
 
 impl hb_ot_shape_plan_t {
@@ -53,11 +68,143 @@ impl hb_ot_shape_plan_t {
         planner.compile()
     }
 
+    /// Returns `true` if the unsafe-to-concat glyph flags produced with this
+    /// plan cover all interactions between glyphs.
+    ///
+    /// Random alternates depend on all preceding glyphs, and the `kern` and
+    /// `kerx` tables only flag the pairs that are actually kerned.
+    pub(crate) fn has_reliable_concat_flags(&self) -> bool {
+        !(self.apply_kern || self.apply_kerx || self.ot_map.has_random_lookups())
+    }
+
+    /// Returns `true` if the plan was created for a shaper instance that
+    /// selects the same feature variations as `shaper`.
+    ///
+    /// [`Shaper::shape_with_plan`](crate::Shaper::shape_with_plan) accepts
+    /// any plan created for the same font, but a matching plan avoids a
+    /// plan lookup on every call.
+    pub fn matches_instance(&self, shaper: &hb_font_t) -> bool {
+        self.variation_indices == shaper.feature_variation_indices()
+    }
+
+    /// Returns a new unique plan identifier.
+    #[cfg(feature = "std")]
+    pub(crate) fn next_id() -> usize {
+        use core::sync::atomic::{AtomicUsize, Ordering};
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+        NEXT_ID.fetch_add(1, Ordering::Relaxed)
+    }
+
     pub(crate) fn data<T: 'static>(&self) -> &T {
         self.data.as_ref().unwrap().downcast_ref().unwrap()
     }
 }
 
+/// The properties that determine the shape plan for a given font.
+///
+/// Mirrors `hb_shape_plan_key_t`: only the tag, value and globalness of
+/// user features matter, and the instance is represented by the selected
+/// FeatureVariations record of each layout table.
+#[cfg(feature = "std")]
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(crate) struct hb_shape_plan_key_t {
+    direction: Direction,
+    script: Option<Script>,
+    language: Option<Language>,
+    user_features: Vec<(hb_tag_t, u32, bool)>,
+    variation_indices: [Option<u32>; 2],
+}
+
+#[cfg(feature = "std")]
+impl hb_shape_plan_key_t {
+    pub fn new(
+        face: &hb_font_t,
+        direction: Direction,
+        script: Option<Script>,
+        language: Option<&Language>,
+        user_features: &[Feature],
+    ) -> Self {
+        Self {
+            direction,
+            script,
+            language: language.cloned(),
+            user_features: user_features
+                .iter()
+                .map(|feature| (feature.tag, feature.value, feature.is_global()))
+                .collect(),
+            variation_indices: face.feature_variation_indices(),
+        }
+    }
+
+    /// Returns `true` if a key created from the arguments would be equal to
+    /// this one, without allocating.
+    pub fn matches(
+        &self,
+        direction: Direction,
+        script: Option<Script>,
+        language: Option<&Language>,
+        user_features: &[Feature],
+        variation_indices: [Option<u32>; 2],
+    ) -> bool {
+        self.direction == direction
+            && self.script == script
+            && self.language.as_ref() == language
+            && self.variation_indices == variation_indices
+            && self.user_features.len() == user_features.len()
+            && self
+                .user_features
+                .iter()
+                .zip(user_features)
+                .all(|(key, feature)| *key == (feature.tag, feature.value, feature.is_global()))
+    }
+}
+
+/// Maximum number of plans kept by a [`hb_shape_plan_cache_t`].
+#[cfg(feature = "std")]
+const MAX_CACHED_PLANS: usize = 32;
+
+/// A bounded, thread-safe cache of shape plans for a single font.
+///
+/// Plans are kept in most recently used order and the least recently used
+/// plan is evicted when the cache is full.
+#[cfg(feature = "std")]
+#[derive(Default)]
+pub(crate) struct hb_shape_plan_cache_t {
+    plans: std::sync::Mutex<Vec<(hb_shape_plan_key_t, std::sync::Arc<hb_ot_shape_plan_t>)>>,
+}
+
+#[cfg(feature = "std")]
+impl hb_shape_plan_cache_t {
+    /// Returns the cached plan with a key accepted by `matches`, creating
+    /// it and its key with `create` if necessary.
+    pub fn get_or_create(
+        &self,
+        matches: impl Fn(&hb_shape_plan_key_t) -> bool,
+        create: impl FnOnce() -> (hb_shape_plan_key_t, hb_ot_shape_plan_t),
+    ) -> std::sync::Arc<hb_ot_shape_plan_t> {
+        if let Ok(mut plans) = self.plans.lock() {
+            if let Some(pos) = plans.iter().position(|(k, _)| matches(k)) {
+                let entry = plans.remove(pos);
+                let plan = entry.1.clone();
+                plans.insert(0, entry);
+                return plan;
+            }
+        }
+        // Don't hold the lock while compiling the plan. If another thread
+        // races us here, we end up with a duplicate that is dropped below.
+        let (key, plan) = create();
+        let plan = std::sync::Arc::new(plan);
+        if let Ok(mut plans) = self.plans.lock() {
+            if let Some(pos) = plans.iter().position(|(k, _)| *k == key) {
+                return plans[pos].1.clone();
+            }
+            plans.truncate(MAX_CACHED_PLANS - 1);
+            plans.insert(0, (key, plan.clone()));
+        }
+        plan
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::hb_ot_shape_plan_t;
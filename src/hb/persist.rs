@@ -0,0 +1,154 @@
+//! Binary encoding of precomputed [`ShaperData`](crate::ShaperData).
+//!
+//! The format is private to this crate version. All integers are little
+//! endian. The header holds a magic number, the format version and a
+//! checksum of the font tables the data was computed from.
+
+use alloc::vec::Vec;
+use read_fonts::{types::Tag, FontRef};
+
+const MAGIC: [u8; 4] = *b"HRSD";
+const VERSION: u16 = 3;
+
+/// Tables that the persisted data is derived from.
+const TABLES: [Tag; 6] = [
+    Tag::new(b"GSUB"),
+    Tag::new(b"GPOS"),
+    Tag::new(b"GDEF"),
+    Tag::new(b"morx"),
+    Tag::new(b"kerx"),
+    Tag::new(b"maxp"),
+];
+
+/// Returns a 64-bit FNV-1a hash of the tables the persisted data depends on.
+pub fn checksum(font: &FontRef) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+    let mut hash = OFFSET_BASIS;
+    let mut feed = |bytes: &[u8]| {
+        for byte in bytes {
+            hash = (hash ^ u64::from(*byte)).wrapping_mul(PRIME);
+        }
+    };
+    for tag in TABLES {
+        let data = font.table_data(tag);
+        let bytes = data
+            .as_ref()
+            .map(|data| data.as_bytes())
+            .unwrap_or_default();
+        feed(&tag.to_be_bytes());
+        feed(&(bytes.len() as u64).to_le_bytes());
+        feed(bytes);
+    }
+    hash
+}
+
+pub struct Writer {
+    data: Vec<u8>,
+}
+
+impl Writer {
+    pub fn new(font: &FontRef) -> Self {
+        let mut writer = Self { data: Vec::new() };
+        writer.data.extend_from_slice(&MAGIC);
+        writer.write_u16(VERSION);
+        writer.write_u64(checksum(font));
+        writer
+    }
+
+    pub fn finish(self) -> Vec<u8> {
+        self.data
+    }
+
+    pub fn write_u8(&mut self, value: u8) {
+        self.data.push(value);
+    }
+
+    pub fn write_bool(&mut self, value: bool) {
+        self.write_u8(value as u8);
+    }
+
+    pub fn write_u16(&mut self, value: u16) {
+        self.data.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn write_u32(&mut self, value: u32) {
+        self.data.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn write_u64(&mut self, value: u64) {
+        self.data.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn write_len(&mut self, len: usize) {
+        self.write_u32(len as u32);
+    }
+}
+
+pub struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    /// Returns a reader positioned after the header, or `None` if the data
+    /// was not written by this version for the given font.
+    pub fn new(font: &FontRef, data: &'a [u8]) -> Option<Self> {
+        let mut reader = Self { data };
+        if reader.read_bytes(4)? != MAGIC
+            || reader.read_u16()? != VERSION
+            || reader.read_u64()? != checksum(font)
+        {
+            return None;
+        }
+        Some(reader)
+    }
+
+    /// Returns `true` if all data has been read.
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    fn read_bytes(&mut self, len: usize) -> Option<&'a [u8]> {
+        if self.data.len() < len {
+            return None;
+        }
+        let (bytes, rest) = self.data.split_at(len);
+        self.data = rest;
+        Some(bytes)
+    }
+
+    fn read_array<const N: usize>(&mut self) -> Option<[u8; N]> {
+        self.read_bytes(N)?.try_into().ok()
+    }
+
+    pub fn read_u8(&mut self) -> Option<u8> {
+        Some(self.read_array::<1>()?[0])
+    }
+
+    pub fn read_bool(&mut self) -> Option<bool> {
+        match self.read_u8()? {
+            0 => Some(false),
+            1 => Some(true),
+            _ => None,
+        }
+    }
+
+    pub fn read_u16(&mut self) -> Option<u16> {
+        self.read_array().map(u16::from_le_bytes)
+    }
+
+    pub fn read_u32(&mut self) -> Option<u32> {
+        self.read_array().map(u32::from_le_bytes)
+    }
+
+    pub fn read_u64(&mut self) -> Option<u64> {
+        self.read_array().map(u64::from_le_bytes)
+    }
+
+    /// Reads a length, rejecting values that can't fit in the remaining
+    /// data given the minimum encoded size of an element.
+    pub fn read_len(&mut self, min_element_size: usize) -> Option<usize> {
+        let len = self.read_u32()? as usize;
+        (len.checked_mul(min_element_size)? <= self.data.len()).then_some(len)
+    }
+}
@@ -0,0 +1,316 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use super::buffer::{hb_buffer_t, HB_BUFFER_SCRATCH_FLAG_HAS_BROKEN_SYLLABLE};
+use super::face::hb_font_t;
+use super::ot_shape_plan::hb_ot_shape_plan_t;
+use super::unicode::{is_grapheme_continuation, CharExt};
+use crate::{BufferFlags, Feature, GlyphBuffer, UnicodeBuffer};
+
+/// The number of windows tried by [`hb_font_t::reshape_edit`] before it
+/// reshapes the whole text.
+const MAX_WINDOW_ATTEMPTS: usize = 4;
+
+impl hb_font_t<'_> {
+    /// Reshapes a paragraph after an edit, reusing the glyphs of the
+    /// previous result that the edit can't affect.
+    ///
+    /// `previous` must be the result of shaping `old_text` with cluster
+    /// values set to byte offsets in `old_text`, as done by
+    /// [`UnicodeBuffer::push_str`], and `features` must be those it was
+    /// shaped with. `edit` is the byte range of `old_text` replaced with
+    /// `replacement`.
+    ///
+    /// The window to reshape extends from the edit to the nearest cluster
+    /// boundaries that aren't
+    /// [unsafe to concatenate](crate::GlyphInfo::unsafe_to_concat). It is
+    /// shaped with the surrounding text as context and spliced between the
+    /// old glyphs if its edges are safe to concatenate in the edited text as
+    /// well. Otherwise the window is widened to the next boundaries, up to a
+    /// few times before falling back to reshaping the whole text.
+    ///
+    /// This requires the previous buffer to be shaped with
+    /// [`BufferFlags::PRODUCE_UNSAFE_TO_CONCAT`]. Without it, with
+    /// non-global features, with non-monotonic clusters, or when broken
+    /// syllables get dotted circles, the whole text is reshaped. Either way,
+    /// the returned buffer has the properties of the previous one and its
+    /// cluster values are byte offsets in the edited text.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `edit` is out of bounds or doesn't lie on character
+    /// boundaries of `old_text`.
+    pub fn reshape_edit(
+        &self,
+        previous: GlyphBuffer,
+        old_text: &str,
+        edit: Range<usize>,
+        replacement: &str,
+        features: &[Feature],
+    ) -> GlyphBuffer {
+        let mut old = previous.0;
+        let mut text = String::with_capacity(old_text.len() - edit.len() + replacement.len());
+        text.push_str(&old_text[..edit.start]);
+        text.push_str(replacement);
+        text.push_str(&old_text[edit.end..]);
+        let delta = replacement.len() as isize - edit.len() as isize;
+
+        let plan = self.plan(old.direction, old.script, old.language.as_ref(), features);
+        if !old.flags.contains(BufferFlags::PRODUCE_UNSAFE_TO_CONCAT)
+            || features.iter().any(|feature| !feature.is_global())
+            || !plan.has_reliable_concat_flags()
+            || has_broken_syllables(&old)
+        {
+            let range = 0..text.len();
+            return GlyphBuffer(self.shape_range(&plan, &old, &text, range, features));
+        }
+        let Some(starts) = cluster_starts(&old, old_text) else {
+            let range = 0..text.len();
+            return GlyphBuffer(self.shape_range(&plan, &old, &text, range, features));
+        };
+
+        // Candidate boundaries as (text offset, logical glyph index), from
+        // the nearest to the edit outwards. The edges of the text are
+        // always valid boundaries.
+        let lefts: Vec<(usize, usize)> = starts
+            .iter()
+            .filter(|start| start.safe_to_concat && start.offset > 0 && start.offset <= edit.start)
+            .filter(|start| is_independent_boundary(&text, start.offset))
+            .rev()
+            .map(|start| (start.offset, start.glyph))
+            .chain([(0, 0)])
+            .collect();
+        let rights: Vec<(usize, usize)> = starts
+            .iter()
+            .filter(|start| start.safe_to_concat && start.offset >= edit.end)
+            .filter(|start| is_independent_boundary(&text, start.offset.wrapping_add_signed(delta)))
+            .map(|start| (start.offset, start.glyph))
+            .chain([(old_text.len(), old.len)])
+            .collect();
+
+        let backward = old.direction.is_backward();
+        let (mut left, mut right) = (0, 0);
+        for _ in 0..MAX_WINDOW_ATTEMPTS {
+            let (start, start_glyph) = lefts[left];
+            let (end, end_glyph) = rights[right];
+            let at_start = start == 0;
+            let at_end = end == old_text.len();
+            if at_start && at_end {
+                break;
+            }
+
+            // The cluster after the window is shaped along with it, so that
+            // lookups reaching past the window flag its first glyph. Its
+            // glyphs are then dropped in favor of the old ones.
+            let next = starts.partition_point(|cluster| cluster.offset <= end);
+            let overlap_end = starts
+                .get(next)
+                .map_or(old_text.len(), |cluster| cluster.offset);
+            let boundary = end.wrapping_add_signed(delta);
+            let range = start..overlap_end.wrapping_add_signed(delta);
+            let shaped = if range.is_empty() {
+                hb_buffer_t::new()
+            } else {
+                self.shape_range(&plan, &old, &text, range, features)
+            };
+            if has_broken_syllables(&shaped) {
+                break;
+            }
+
+            let logical = |i: usize| {
+                let infos = &shaped.info[..shaped.len];
+                if backward {
+                    &infos[infos.len() - 1 - i]
+                } else {
+                    &infos[i]
+                }
+            };
+            let split = (0..shaped.len)
+                .position(|i| logical(i).cluster as usize >= boundary)
+                .unwrap_or(shaped.len);
+            let start_safe = at_start || split == 0 || !logical(0).unsafe_to_concat();
+            let end_safe = at_end
+                || (split < shaped.len
+                    && logical(split).cluster as usize == boundary
+                    && !logical(split).unsafe_to_concat()
+                    && (split..shaped.len).all(|i| logical(i).cluster as usize >= boundary));
+            if start_safe && end_safe {
+                let glyphs = if backward {
+                    shaped.len - split..shaped.len
+                } else {
+                    0..split
+                };
+                splice(&mut old, start_glyph..end_glyph, &shaped, glyphs, delta);
+                return GlyphBuffer(old);
+            }
+
+            if !start_safe {
+                left += 1;
+            }
+            if !end_safe {
+                right += 1;
+            }
+        }
+
+        // Lookups with long contexts can keep the edges unsafe for many
+        // clusters, and reshaping ever wider windows would cost more than
+        // reshaping the text once.
+        let range = 0..text.len();
+        GlyphBuffer(self.shape_range(&plan, &old, &text, range, features))
+    }
+
+    /// Shapes `text[range]` with the properties of `template`, using byte
+    /// offsets in `text` as cluster values.
+    ///
+    /// The text around the range is used as context. At the edges of `text`,
+    /// the context and the beginning and end of text flags of `template` are
+    /// kept instead.
+    pub(crate) fn shape_range(
+        &self,
+        plan: &hb_ot_shape_plan_t,
+        template: &hb_buffer_t,
+        text: &str,
+        range: Range<usize>,
+        features: &[Feature],
+    ) -> hb_buffer_t {
+        let mut buffer = UnicodeBuffer(empty_like(template));
+        for (offset, c) in text[range.clone()].char_indices() {
+            buffer.add(c, (range.start + offset) as u32);
+        }
+        if range.start == 0 {
+            buffer.0.context[0] = template.context[0];
+            buffer.0.context_len[0] = template.context_len[0];
+        } else {
+            buffer.0.flags.remove(BufferFlags::BEGINNING_OF_TEXT);
+            buffer.set_pre_context(&text[..range.start]);
+        }
+        if range.end == text.len() {
+            buffer.0.context[1] = template.context[1];
+            buffer.0.context_len[1] = template.context_len[1];
+        } else {
+            buffer.0.flags.remove(BufferFlags::END_OF_TEXT);
+            buffer.set_post_context(&text[range.end..]);
+        }
+
+        self.shape_with_plan(plan, buffer, features).0
+    }
+}
+
+/// Returns an empty buffer with the properties of `template`.
+pub(crate) fn empty_like(template: &hb_buffer_t) -> hb_buffer_t {
+    let mut buffer = hb_buffer_t::new();
+    buffer.flags = template.flags;
+    buffer.cluster_level = template.cluster_level;
+    buffer.replacement = template.replacement;
+    buffer.invisible = template.invisible;
+    buffer.not_found_variation_selector = template.not_found_variation_selector;
+    buffer.direction = template.direction;
+    buffer.script = template.script;
+    buffer.language = template.language.clone();
+    buffer
+}
+
+/// Returns `true` if the buffer has broken syllables that get dotted circles.
+///
+/// Whether a broken syllable gets one depends on the serial numbers of the
+/// syllables before it, so the result can't be spliced.
+fn has_broken_syllables(buffer: &hb_buffer_t) -> bool {
+    buffer.scratch_flags & HB_BUFFER_SCRATCH_FLAG_HAS_BROKEN_SYLLABLE != 0
+        && !buffer
+            .flags
+            .contains(BufferFlags::DO_NOT_INSERT_DOTTED_CIRCLE)
+}
+
+/// Returns `true` if the characters on both sides of `offset` can't be
+/// joined by cluster formation, normalization or syllable structure.
+///
+/// These don't depend on lookups, so glyph flags don't reflect them.
+pub(crate) fn is_independent_boundary(text: &str, offset: usize) -> bool {
+    // A virama or a joiner lets the following character extend a syllable.
+    let before = text[..offset].chars().next_back();
+    if before.is_some_and(|c| c.combining_class() == 9 || c.is_default_ignorable()) {
+        return false;
+    }
+    let Some(c) = text[offset..].chars().next() else {
+        return true;
+    };
+    // Whether the character before continues a grapheme cluster only
+    // matters for regional indicators, which are assumed to pair up.
+    !(is_grapheme_continuation(before.map(|b| (b, false)), c)
+        || c.is_default_ignorable()
+        // Hangul vowel and trailing jamo compose with the syllable before.
+        || matches!(c as u32, 0x1160..=0x11FF | 0xD7B0..=0xD7FF))
+}
+
+/// The first glyph of a cluster, in logical order.
+#[derive(Clone, Copy)]
+pub(crate) struct ClusterStart {
+    /// Byte offset of the cluster in the text.
+    pub offset: usize,
+    /// Logical index of the glyph.
+    pub glyph: usize,
+    /// Whether the text is safe to concatenate at the start of the cluster.
+    pub safe_to_concat: bool,
+    /// Whether the text is safe to break at the start of the cluster.
+    pub safe_to_break: bool,
+}
+
+/// Returns the starts of all clusters in logical order, or `None` if the
+/// cluster values are not monotonic character boundaries of `text`.
+pub(crate) fn cluster_starts(buffer: &hb_buffer_t, text: &str) -> Option<Vec<ClusterStart>> {
+    let backward = buffer.direction.is_backward();
+    let infos = &buffer.info[..buffer.len];
+    let mut starts: Vec<ClusterStart> = Vec::new();
+    for glyph in 0..infos.len() {
+        let info = if backward {
+            &infos[infos.len() - 1 - glyph]
+        } else {
+            &infos[glyph]
+        };
+        let offset = info.cluster as usize;
+        match starts.last() {
+            Some(last) if last.offset == offset => continue,
+            Some(last) if last.offset > offset => return None,
+            _ => {}
+        }
+        if offset >= text.len() || !text.is_char_boundary(offset) {
+            return None;
+        }
+        starts.push(ClusterStart {
+            offset,
+            glyph,
+            safe_to_concat: !info.unsafe_to_concat(),
+            safe_to_break: !info.unsafe_to_break(),
+        });
+    }
+    Some(starts)
+}
+
+/// Replaces the glyphs in the logical range `glyphs` of `buffer` with the
+/// glyphs in the storage range `new` of `shaped`, shifting the clusters that
+/// follow by `delta`.
+fn splice(
+    buffer: &mut hb_buffer_t,
+    glyphs: Range<usize>,
+    shaped: &hb_buffer_t,
+    new: Range<usize>,
+    delta: isize,
+) {
+    let len = buffer.len;
+    buffer.info.truncate(len);
+    buffer.pos.truncate(len);
+    let (range, following) = if buffer.direction.is_backward() {
+        (len - glyphs.end..len - glyphs.start, 0..len - glyphs.end)
+    } else {
+        (glyphs.clone(), glyphs.end..len)
+    };
+    for info in &mut buffer.info[following] {
+        info.cluster = (info.cluster as usize).wrapping_add_signed(delta) as u32;
+    }
+    buffer
+        .info
+        .splice(range.clone(), shaped.info[new.clone()].iter().copied());
+    buffer.pos.splice(range, shaped.pos[new].iter().copied());
+    buffer.len = buffer.info.len();
+}
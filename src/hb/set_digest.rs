@@ -1,3 +1,4 @@
+use super::persist::{Reader, Writer};
 use read_fonts::{tables::layout::CoverageTable, types::GlyphId};
 
 type mask_t = u64;
@@ -35,7 +36,7 @@ impl hb_set_digest_t {
         self.masks = [0; N];
     }
 
-    pub fn _full() -> Self {
+    pub fn full() -> Self {
         Self { masks: [ALL; N] }
     }
 
@@ -104,6 +105,20 @@ impl hb_set_digest_t {
         true
     }
 
+    pub fn write(&self, writer: &mut Writer) {
+        for mask in self.masks {
+            writer.write_u64(mask);
+        }
+    }
+
+    pub fn read(reader: &mut Reader) -> Option<Self> {
+        let mut digest = Self::new();
+        for mask in &mut digest.masks {
+            *mask = reader.read_u64()?;
+        }
+        Some(digest)
+    }
+
     pub fn may_intersect(&self, other: &Self) -> bool {
         for i in 0..N {
             if self.masks[i] & other.masks[i] == 0 {
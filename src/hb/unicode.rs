@@ -1,4 +1,5 @@
 use core::convert::TryFrom;
+use core::ops::Range;
 
 use super::ucd_table::ucd::*;
 use crate::hb::algs::*;
@@ -424,6 +425,26 @@ impl GeneralCategoryExt for hb_unicode_general_category_t {
     }
 }
 
+/// Returns the general category of an ASCII character without a UCD lookup.
+fn ascii_general_category(c: u8) -> hb_unicode_general_category_t {
+    use hb_unicode_general_category_t::*;
+    match c {
+        b'A'..=b'Z' => UppercaseLetter,
+        b'a'..=b'z' => LowercaseLetter,
+        b'0'..=b'9' => DecimalNumber,
+        b' ' => SpaceSeparator,
+        b'$' => CurrencySymbol,
+        b'(' | b'[' | b'{' => OpenPunctuation,
+        b')' | b']' | b'}' => ClosePunctuation,
+        b'+' | b'<' | b'=' | b'>' | b'|' | b'~' => MathSymbol,
+        b'-' => DashPunctuation,
+        b'^' | b'`' => ModifierSymbol,
+        b'_' => ConnectorPunctuation,
+        0x00..=0x1F | 0x7F => Control,
+        _ => OtherPunctuation,
+    }
+}
+
 pub trait CharExt {
     fn script(self) -> Script;
     fn general_category(self) -> hb_unicode_general_category_t;
@@ -443,6 +464,9 @@ impl CharExt for char {
     }
 
     fn general_category(self) -> hb_unicode_general_category_t {
+        if self.is_ascii() {
+            return ascii_general_category(self as u8);
+        }
         hb_unicode_general_category_t::from_u32(_hb_ucd_gc(self as usize) as u32)
     }
 
@@ -866,6 +890,48 @@ pub fn decompose_hangul(ab: char) -> Option<(char, char)> {
     Some((char::try_from(a).unwrap(), char::try_from(b).unwrap()))
 }
 
+/// Returns `true` if `c` continues the grapheme cluster before it.
+///
+/// Follows the continuations that shaping sets in `set_unicode_props`:
+/// marks, emoji modifiers, ZWJ and ZWJ,
+/// Extended_Pictographic sequences, the second of a pair of regional
+/// indicators and the non-mark `Other_Grapheme_Extend` characters other than
+/// ZWNJ are continuations. `prev` is the previous character and whether it
+/// was a continuation itself.
+///
+/// https://www.unicode.org/reports/tr29/#Regex_Definitions
+pub fn is_grapheme_continuation(prev: Option<(char, bool)>, c: char) -> bool {
+    match c as u32 {
+        0x200D | 0x1F3FB..=0x1F3FF | 0xFF9E..=0xFF9F | 0xE0020..=0xE007F => true,
+        0x1F1E6..=0x1F1FF => prev.is_some_and(|(prev, continuation)| {
+            matches!(prev as u32, 0x1F1E6..=0x1F1FF) && !continuation
+        }),
+        _ => {
+            (prev.is_some_and(|(prev, _)| prev == '\u{200D}') && c.is_emoji_extended_pictographic())
+                || c.general_category().is_mark()
+        }
+    }
+}
+
+/// Returns an iterator over the byte ranges of the grapheme clusters of
+/// `text`, as approximated by [`is_grapheme_continuation`].
+pub fn graphemes(text: &str) -> impl Iterator<Item = Range<usize>> + '_ {
+    let mut chars = text.char_indices().peekable();
+    core::iter::from_fn(move || {
+        let (start, first) = chars.next()?;
+        let mut prev = (first, is_grapheme_continuation(None, first));
+        while let Some(&(_, c)) = chars.peek() {
+            if !is_grapheme_continuation(Some(prev), c) {
+                break;
+            }
+            chars.next();
+            prev = (c, true);
+        }
+        let end = chars.peek().map_or(text.len(), |&(end, _)| end);
+        Some(start..end)
+    })
+}
+
 pub mod hb_gc {
     pub const HB_UNICODE_GENERAL_CATEGORY_CONTROL: u32 = 0;
     pub const HB_UNICODE_GENERAL_CATEGORY_FORMAT: u32 = 1;
@@ -898,3 +964,19 @@ pub mod hb_gc {
     pub const HB_UNICODE_GENERAL_CATEGORY_PARAGRAPH_SEPARATOR: u32 = 28;
     pub const HB_UNICODE_GENERAL_CATEGORY_SPACE_SEPARATOR: u32 = 29;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ascii_general_category_matches_ucd() {
+        for c in 0u8..0x80 {
+            assert_eq!(
+                ascii_general_category(c),
+                hb_unicode_general_category_t::from_u32(_hb_ucd_gc(c as usize) as u32),
+                "{c:#04x}"
+            );
+        }
+    }
+}
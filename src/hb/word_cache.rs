@@ -0,0 +1,362 @@
+use alloc::vec::Vec;
+use std::collections::HashMap;
+
+use super::buffer::{glyph_flag, hb_buffer_t, hb_glyph_info_t, GlyphPosition, CONTEXT_LENGTH};
+use super::face::hb_font_t;
+use super::ot_shape_plan::hb_ot_shape_plan_t;
+use super::unicode::{CharExt, GeneralCategoryExt};
+use crate::{BufferFlags, Feature, GlyphBuffer, NormalizedCoord, UnicodeBuffer};
+
+/// Default number of cached runs.
+const DEFAULT_CAPACITY: usize = 1024;
+
+/// Default maximum length of a cached run, in characters.
+const DEFAULT_MAX_WORD_LEN: usize = 32;
+
+/// Everything the shaping result of a short run depends on.
+///
+/// Clusters are stored relative to the first cluster of the run, so the
+/// same word shaped at different offsets of a string shares an entry.
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct WordKey {
+    plan_id: usize,
+    coords: Vec<NormalizedCoord>,
+    pixels_per_em: Option<(u16, u16)>,
+    points_per_em: Option<u32>,
+    flags: u32,
+    cluster_level: u32,
+    invisible: Option<u32>,
+    not_found_variation_selector: Option<u32>,
+    context: [[char; CONTEXT_LENGTH]; 2],
+    context_len: [usize; 2],
+    text: Vec<(u32, u32)>,
+}
+
+struct ShapedWord {
+    infos: Vec<hb_glyph_info_t>,
+    positions: Vec<GlyphPosition>,
+}
+
+/// A cache of shaping results for short runs of text.
+///
+/// UI text is dominated by a small set of repeated words. This cache stores
+/// the glyphs and positions produced for runs of up to
+/// [`max_word_len`](Self::max_word_len) characters, keyed by the text, the
+/// shape plan, the variation coordinates and the buffer properties.
+///
+/// Longer runs are split after spaces and every word is looked up or shaped
+/// on its own. The words are only concatenated if none of the clusters at
+/// their edges is unsafe to concatenate, otherwise the whole run is shaped
+/// normally. The result is therefore the same as that of
+/// [`Shaper::shape_with_plan`](crate::Shaper::shape_with_plan).
+///
+/// Entries are evicted in approximately least recently used order: the
+/// cache keeps two generations of at most half the capacity each, and
+/// drops the older generation when the newer one fills up.
+///
+/// A cache should only be used with shapers created from a single font.
+pub struct WordCache {
+    current: HashMap<WordKey, ShapedWord>,
+    previous: HashMap<WordKey, ShapedWord>,
+    capacity: usize,
+    max_word_len: usize,
+}
+
+impl WordCache {
+    /// Creates a new cache holding up to `capacity` runs of at most
+    /// `max_word_len` characters each.
+    pub fn new(capacity: usize, max_word_len: usize) -> Self {
+        WordCache {
+            current: HashMap::new(),
+            previous: HashMap::new(),
+            capacity,
+            max_word_len,
+        }
+    }
+
+    /// Returns the maximum number of cached runs.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the maximum length of a cached run, in characters.
+    pub fn max_word_len(&self) -> usize {
+        self.max_word_len
+    }
+
+    /// Returns the number of cached runs.
+    pub fn len(&self) -> usize {
+        self.current.len() + self.previous.len()
+    }
+
+    /// Returns `true` if the cache holds no runs.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes all cached runs.
+    pub fn clear(&mut self) {
+        self.current.clear();
+        self.previous.clear();
+    }
+
+    fn get(&mut self, key: &WordKey) -> Option<&ShapedWord> {
+        if !self.current.contains_key(key) {
+            let (key, word) = self.previous.remove_entry(key)?;
+            self.insert(key, word);
+        }
+        self.current.get(key)
+    }
+
+    fn insert(&mut self, key: WordKey, word: ShapedWord) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.current.len() >= self.capacity.div_ceil(2) {
+            self.previous = core::mem::take(&mut self.current);
+        }
+        self.current.insert(key, word);
+    }
+}
+
+impl Default for WordCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY, DEFAULT_MAX_WORD_LEN)
+    }
+}
+
+impl core::fmt::Debug for WordCache {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        fmt.debug_struct("WordCache")
+            .field("len", &self.len())
+            .field("capacity", &self.capacity)
+            .field("max_word_len", &self.max_word_len)
+            .finish()
+    }
+}
+
+impl hb_font_t<'_> {
+    /// Shapes the buffer content using the provided plan, reusing results
+    /// for repeated words from `cache`.
+    ///
+    /// Produces the same output as [`shape_with_plan`](Self::shape_with_plan).
+    /// Buffers using non-global features bypass the cache, as do plans with
+    /// randomized alternates or legacy `kern` and `kerx` kerning, whose
+    /// results can't be reliably concatenated.
+    pub fn shape_with_word_cache(
+        &self,
+        plan: &hb_ot_shape_plan_t,
+        buffer: UnicodeBuffer,
+        features: &[Feature],
+        cache: &mut WordCache,
+    ) -> GlyphBuffer {
+        let buffer = buffer.0;
+        if buffer.len == 0
+            || cache.capacity == 0
+            || features.iter().any(|feature| !feature.is_global())
+            || !plan.has_reliable_concat_flags()
+        {
+            return self.shape_with_plan(plan, UnicodeBuffer(buffer), features);
+        }
+
+        if buffer.len <= cache.max_word_len {
+            let key = self.word_key(plan, &buffer, 0, buffer.len, buffer.flags, [true, true]);
+            let base = buffer.info[0].cluster;
+            if let Some(word) = cache.get(&key) {
+                let (infos, positions) = (word.infos.clone(), word.positions.clone());
+                return word_buffer(buffer, infos, positions, base);
+            }
+            let result = self.shape_with_plan(plan, UnicodeBuffer(buffer), features);
+            cache.insert(key, shaped_word(&result.0, base));
+            return result;
+        }
+
+        let words = split_words(&buffer);
+        if words.len() < 2 || words.iter().any(|w| w.end - w.start > cache.max_word_len) {
+            return self.shape_with_plan(plan, UnicodeBuffer(buffer), features);
+        }
+
+        // Word edges have to be checked for unsafe-to-concat flags.
+        let flags = buffer.flags | BufferFlags::PRODUCE_UNSAFE_TO_CONCAT;
+        let backward = plan.direction.is_backward();
+        let last = words.len() - 1;
+        let mut infos = Vec::with_capacity(buffer.len);
+        let mut positions = Vec::with_capacity(buffer.len);
+        for i in 0..words.len() {
+            // Glyphs of a backward run are in reverse logical order.
+            let i = if backward { last - i } else { i };
+            let (start, end) = (words[i].start, words[i].end);
+            let edges = [i == 0, i == last];
+            let base = buffer.info[start].cluster;
+            let key = self.word_key(plan, &buffer, start, end, flags, edges);
+            if cache.get(&key).is_none() {
+                let segment = segment_buffer(&buffer, start, end, flags, edges);
+                let result = self.shape_with_plan(plan, UnicodeBuffer(segment), features);
+                cache.insert(key.clone(), shaped_word(&result.0, base));
+            }
+            match cache.get(&key) {
+                Some(word) if concat_safe(word, edges, backward) => {
+                    infos.extend(word.infos.iter().map(|info| hb_glyph_info_t {
+                        cluster: info.cluster.wrapping_add(base),
+                        ..*info
+                    }));
+                    positions.extend_from_slice(&word.positions);
+                }
+                _ => return self.shape_with_plan(plan, UnicodeBuffer(buffer), features),
+            }
+        }
+
+        if !buffer.flags.contains(BufferFlags::PRODUCE_UNSAFE_TO_CONCAT) {
+            for info in &mut infos {
+                info.mask &= !glyph_flag::UNSAFE_TO_CONCAT;
+            }
+        }
+        word_buffer(buffer, infos, positions, 0)
+    }
+
+    fn word_key(
+        &self,
+        plan: &hb_ot_shape_plan_t,
+        buffer: &hb_buffer_t,
+        start: usize,
+        end: usize,
+        flags: BufferFlags,
+        edges: [bool; 2],
+    ) -> WordKey {
+        let base = buffer.info[start].cluster;
+        WordKey {
+            plan_id: plan.id,
+            coords: self.ot_tables.coords.to_vec(),
+            pixels_per_em: self.pixels_per_em(),
+            points_per_em: self.points_per_em.map(f32::to_bits),
+            flags: segment_flags(flags, edges).bits(),
+            cluster_level: buffer.cluster_level,
+            invisible: buffer.invisible.map(|glyph| glyph.to_u32()),
+            not_found_variation_selector: buffer.not_found_variation_selector,
+            context: buffer.context,
+            context_len: [
+                if edges[0] { buffer.context_len[0] } else { 0 },
+                if edges[1] { buffer.context_len[1] } else { 0 },
+            ],
+            text: buffer.info[start..end]
+                .iter()
+                .map(|info| (info.glyph_id, info.cluster.wrapping_sub(base)))
+                .collect(),
+        }
+    }
+}
+
+/// Splits the buffer after runs of spaces, unless the following character
+/// could interact with the space during normalization or cluster formation.
+fn split_words(buffer: &hb_buffer_t) -> Vec<core::ops::Range<usize>> {
+    let mut words = Vec::new();
+    let mut start = 0;
+    for i in 1..buffer.len {
+        if buffer.info[i - 1].glyph_id != 0x20 {
+            continue;
+        }
+        let Some(c) = char::from_u32(buffer.info[i].glyph_id) else {
+            continue;
+        };
+        if c == ' ' || c.general_category().is_mark() || c.is_default_ignorable() {
+            continue;
+        }
+        words.push(start..i);
+        start = i;
+    }
+    words.push(start..buffer.len);
+    words
+}
+
+/// Flags to shape a word with: only the first word is at the beginning of
+/// text and only the last one at its end.
+fn segment_flags(flags: BufferFlags, edges: [bool; 2]) -> BufferFlags {
+    let mut flags = flags;
+    if !edges[0] {
+        flags.remove(BufferFlags::BEGINNING_OF_TEXT);
+    }
+    if !edges[1] {
+        flags.remove(BufferFlags::END_OF_TEXT);
+    }
+    flags
+}
+
+fn segment_buffer(
+    buffer: &hb_buffer_t,
+    start: usize,
+    end: usize,
+    flags: BufferFlags,
+    edges: [bool; 2],
+) -> hb_buffer_t {
+    let mut segment = hb_buffer_t::new();
+    segment.flags = segment_flags(flags, edges);
+    segment.cluster_level = buffer.cluster_level;
+    segment.invisible = buffer.invisible;
+    segment.not_found_variation_selector = buffer.not_found_variation_selector;
+    segment.direction = buffer.direction;
+    segment.script = buffer.script;
+    segment.language = buffer.language.clone();
+    segment.context = buffer.context;
+    segment.context_len = [
+        if edges[0] { buffer.context_len[0] } else { 0 },
+        if edges[1] { buffer.context_len[1] } else { 0 },
+    ];
+    if segment.ensure(end - start) {
+        segment.info[..end - start].copy_from_slice(&buffer.info[start..end]);
+        segment.len = end - start;
+    }
+    segment
+}
+
+/// Returns `true` if the word can be concatenated with its neighbours at
+/// the edges that are not the edges of the whole text.
+fn concat_safe(word: &ShapedWord, edges: [bool; 2], backward: bool) -> bool {
+    let (Some(first), Some(last)) = (word.infos.first(), word.infos.last()) else {
+        return true;
+    };
+    // Glyph flags are uniform within a cluster, so checking a single glyph
+    // of the first and last clusters is enough.
+    let (start, end) = if backward {
+        (last, first)
+    } else {
+        (first, last)
+    };
+    (edges[0] || !start.unsafe_to_concat()) && (edges[1] || !end.unsafe_to_concat())
+}
+
+fn shaped_word(buffer: &hb_buffer_t, base: u32) -> ShapedWord {
+    let infos = buffer.info[..buffer.len]
+        .iter()
+        .map(|info| hb_glyph_info_t {
+            glyph_id: info.glyph_id,
+            mask: info.mask & glyph_flag::DEFINED,
+            cluster: info.cluster.wrapping_sub(base),
+            var1: 0,
+            var2: 0,
+        })
+        .collect();
+    ShapedWord {
+        infos,
+        positions: buffer.pos[..buffer.len].to_vec(),
+    }
+}
+
+/// Replaces the buffer content with shaped glyphs, adding `base` to their
+/// clusters.
+fn word_buffer(
+    mut buffer: hb_buffer_t,
+    mut infos: Vec<hb_glyph_info_t>,
+    positions: Vec<GlyphPosition>,
+    base: u32,
+) -> GlyphBuffer {
+    for info in &mut infos {
+        info.cluster = info.cluster.wrapping_add(base);
+    }
+    buffer.clear_output();
+    buffer.len = infos.len();
+    buffer.idx = 0;
+    buffer.info = infos;
+    buffer.pos = positions;
+    buffer.have_positions = true;
+    GlyphBuffer(buffer)
+}
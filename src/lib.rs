@@ -15,11 +15,23 @@ mod hb;
 
 pub use read_fonts::{types::Tag, FontRef};
 
+pub use hb::attachment::{Attachment, AttachmentKind};
+pub use hb::batch::ShapeItem;
 pub use hb::buffer::hb_glyph_info_t as GlyphInfo;
 pub use hb::buffer::{GlyphBuffer, GlyphPosition, UnicodeBuffer};
+pub use hb::charmap::Charmap;
+pub use hb::clusters::{Cluster, Clusters, Hit};
 pub use hb::common::{script, Direction, Feature, Language, Script, Variation};
-pub use hb::face::{hb_font_t as Shaper, ShaperBuilder, ShaperData, ShaperInstance};
+pub use hb::face::hb_font_extents_t as FontExtents;
+pub use hb::face::hb_glyph_extents_t as GlyphExtents;
+pub use hb::face::{hb_font_t as Shaper, OwnedShaper, ShaperBuilder, ShaperData, ShaperInstance};
+pub use hb::fallback::{FallbackRuns, FontRun};
+pub use hb::line_break::{Line, LineBreaks};
+pub use hb::ot_layout_base_table::BaselineTag;
+pub use hb::ot_metrics::MetricsTag;
 pub use hb::ot_shape_plan::hb_ot_shape_plan_t as ShapePlan;
+#[cfg(feature = "std")]
+pub use hb::word_cache::WordCache;
 
 /// Type alias for a normalized variation coordinate.
 pub type NormalizedCoord = read_fonts::types::F2Dot14;
@@ -44,8 +56,10 @@ bitflags::bitflags! {
         const PRODUCE_UNSAFE_TO_CONCAT      = 0x00000040;
         /// Indicates that the `SAFE_TO_INSERT_TATWEEL` glyph-flag should be produced by the shaper. By default it will not be produced.
         const PRODUCE_SAFE_TO_INSERT_TATWEEL      = 0x00000080;
+        /// Indicates that mark attachments and cursive chains made during positioning should be kept in the output, so that they can be queried with [`GlyphBuffer::attachment`]. By default they are discarded once the glyph offsets have been computed.
+        const PRESERVE_ATTACHMENTS          = 0x00000100;
         /// All currently defined flags
-        const DEFINED = 0x000000FF;
+        const DEFINED = 0x000001FF;
     }
 }
 
@@ -0,0 +1,118 @@
+//! Lives in its own test binary, so that the counting allocator doesn't
+//! replace the allocator of the shaping tests.
+
+use harfrust::{Feature, FontRef, ShaperData, UnicodeBuffer};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::str::FromStr;
+
+/// Counts the allocations made by each thread, so that tests running in
+/// parallel don't see each other's allocations.
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Shapes `text` a few times with a reused buffer and returns the number of
+/// allocations made by the last call.
+fn steady_state_allocations(path: &str, text: &str, features: &[&str]) -> usize {
+    let font_data = std::fs::read(path).unwrap();
+    let font = FontRef::new(&font_data).unwrap();
+    let data = ShaperData::new(&font);
+    let shaper = data.shaper(&font).build();
+    let features: Vec<_> = features
+        .iter()
+        .map(|feature| Feature::from_str(feature).unwrap())
+        .collect();
+
+    let mut buffer = UnicodeBuffer::new();
+    let mut allocations = 0;
+    for _ in 0..3 {
+        buffer.push_str(text);
+        buffer.guess_segment_properties();
+        let before = ALLOCATIONS.with(Cell::get);
+        let glyphs = shaper.shape(buffer, &features);
+        allocations = ALLOCATIONS.with(Cell::get) - before;
+        buffer = glyphs.clear();
+    }
+    allocations
+}
+
+#[test]
+fn steady_state_shaping_does_not_allocate() {
+    let cases: &[(&str, &str, &[&str])] = &[
+        (
+            "tests/fonts/rb_custom/PT_Sans-Caption-Web-Regular.ttf",
+            "To be or not to be, AVA office",
+            &[],
+        ),
+        (
+            "tests/fonts/rb_custom/PT_Sans-Caption-Web-Regular.ttf",
+            "To be or not to be, AVA office",
+            &["-liga", "kern[3:8]=0"],
+        ),
+        (
+            "tests/fonts/in-house/NotoNastaliqUrdu-Regular.ttf",
+            "\u{0644}\u{0627} \u{0628}\u{0633}\u{0645}",
+            &[],
+        ),
+        (
+            "tests/fonts/in-house/3c96e7a303c58475a8c750bf4289bbe73784f37d.ttf",
+            "\u{0C95}\u{0CCD}\u{0CB0}\u{0C95}",
+            &[],
+        ),
+        (
+            "tests/fonts/in-house/b6031119874ae9ff1dd65383a335e361c0962220.ttf",
+            "\u{179F}\u{17CA}\u{17BE}\u{1794}",
+            &[],
+        ),
+        (
+            "tests/fonts/in-house/065b01e54f35f0d849fd43bd5b936212739a50cb.ttf",
+            "\u{101A}\u{1035}",
+            &[],
+        ),
+        (
+            "tests/fonts/in-house/e39391c77a6321c2ac7a2d644de0396470cd4bfe.ttf",
+            "AV ck ck",
+            &[],
+        ),
+        ("tests/fonts/in-house/MORXTwentyeight.ttf", "AxEyDyy", &[]),
+        (
+            "tests/fonts/in-house/MORXTwentyeight.ttf",
+            "AxEyDyy",
+            &["liga[2:5]=0"],
+        ),
+        (
+            "tests/fonts/text-rendering-tests/TestMORXFourtyone.ttf",
+            "acac ab",
+            &[],
+        ),
+    ];
+    for (path, text, features) in cases {
+        assert_eq!(
+            steady_state_allocations(path, text, features),
+            0,
+            "{path} {features:?}"
+        );
+    }
+}
@@ -0,0 +1,49 @@
+use harfrust::{FontRef, SerializeFlags, Shaper, ShaperData, UnicodeBuffer};
+
+fn shape(shaper: &Shaper, text: &str, ned: bool) -> String {
+    let flags = if ned {
+        SerializeFlags::NO_CLUSTERS | SerializeFlags::NO_ADVANCES
+    } else {
+        SerializeFlags::default()
+    };
+    let mut buffer = UnicodeBuffer::new();
+    buffer.push_str(text);
+    buffer.guess_segment_properties();
+    shaper.shape(buffer, &[]).serialize(shaper, flags)
+}
+
+// Ligatures leave deleted glyphs in the buffer until the end of `morx`, so
+// the machine class cache has to give them the deleted glyph class rather
+// than a cached class for another glyph, whether it is cold or warm.
+#[test]
+fn morx_deleted_glyphs_with_class_cache() {
+    let cases = [
+        (
+            "tests/fonts/in-house/MORXTwentyeight.ttf",
+            "AxEyDyy",
+            false,
+            "A_E_D=0+1394|x=0+529|y=0+510|y=5+510|y=6+510",
+        ),
+        (
+            "tests/fonts/text-rendering-tests/TestMORXTwentyeight.ttf",
+            "AxED",
+            true,
+            "A_E_D|x@1394,0",
+        ),
+        (
+            "tests/fonts/text-rendering-tests/TestMORXFourtyone.ttf",
+            "ac",
+            true,
+            "a_c",
+        ),
+    ];
+    for (path, text, ned, expected) in cases {
+        let font_data = std::fs::read(path).unwrap();
+        let font = FontRef::new(&font_data).unwrap();
+        let data = ShaperData::new(&font);
+        let shaper = data.shaper(&font).build();
+        for _ in 0..3 {
+            assert_eq!(shape(&shaper, text, ned), expected, "{path}");
+        }
+    }
+}
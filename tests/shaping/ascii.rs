@@ -0,0 +1,61 @@
+use harfrust::{
+    BufferClusterLevel, Feature, FontRef, GlyphBuffer, SerializeFlags, Shaper, ShaperData, Tag,
+    UnicodeBuffer,
+};
+
+fn shape(
+    shaper: &Shaper,
+    text: &str,
+    cluster_level: BufferClusterLevel,
+    features: &[Feature],
+) -> GlyphBuffer {
+    let mut buffer = UnicodeBuffer::new();
+    buffer.push_str(text);
+    buffer.set_cluster_level(cluster_level);
+    buffer.guess_segment_properties();
+    shaper.shape(buffer, features)
+}
+
+/// Checks that ASCII text shapes like it does after the fast path is
+/// turned off by a non-ASCII character that follows a space. Texts that
+/// end in a context of a lookup should end with a space themselves.
+fn check(path: &str, texts: &[&str], features: &[Feature]) {
+    let font_data = std::fs::read(path).unwrap();
+    let font = FontRef::new(&font_data).unwrap();
+    let data = ShaperData::new(&font);
+    let shaper = data.shaper(&font).build();
+    for level in [
+        BufferClusterLevel::MonotoneGraphemes,
+        BufferClusterLevel::Characters,
+    ] {
+        for text in texts {
+            let ascii = shape(&shaper, text, level, features);
+            let mixed = shape(&shaper, &format!("{text} \u{E9}"), level, features);
+            let ascii = ascii.serialize(&shaper, SerializeFlags::GLYPH_FLAGS);
+            let mixed = mixed.serialize(&shaper, SerializeFlags::GLYPH_FLAGS);
+            let n = ascii.split('|').count();
+            assert!(mixed.split('|').count() > n);
+            let prefix: Vec<_> = mixed.split('|').take(n).collect();
+            assert_eq!(ascii, prefix.join("|"), "{text:?}");
+        }
+    }
+}
+
+#[test]
+fn matches_full_path() {
+    let texts = ["Office affine", "AVATAR", "a-b, c; d!", "\t1 + 2 = 3\n"];
+    let pt_sans = "tests/fonts/rb_custom/PT_Sans-Caption-Web-Regular.ttf";
+    check(pt_sans, &texts, &[]);
+    check(pt_sans, &texts, &[Feature::new(Tag::new(b"liga"), 0, ..)]);
+    check(pt_sans, &texts, &[Feature::new(Tag::new(b"kern"), 0, 2..)]);
+    check(
+        "tests/fonts/text-rendering-tests/TestGSUBOne.otf",
+        &["a a ", "ab "],
+        &[],
+    );
+    check(
+        "tests/fonts/in-house/NotoNastaliqUrdu-Regular.ttf",
+        &["12 34", "x"],
+        &[],
+    );
+}
@@ -0,0 +1,109 @@
+use harfrust::{
+    Attachment, AttachmentKind, BufferFlags, Feature, FontRef, GlyphBuffer, SerializeFlags, Shaper,
+    ShaperData, Tag, UnicodeBuffer,
+};
+
+const NASTALIQ: &str = "tests/fonts/in-house/NotoNastaliqUrdu-Regular.ttf";
+
+fn with_shaper(path: &str, f: impl FnOnce(&Shaper)) {
+    let font_data = std::fs::read(path).unwrap();
+    let font = FontRef::new(&font_data).unwrap();
+    let data = ShaperData::new(&font);
+    let shaper = data.shaper(&font).build();
+    f(&shaper);
+}
+
+fn shape(shaper: &Shaper, text: &str, flags: BufferFlags, features: &[Feature]) -> GlyphBuffer {
+    let mut buffer = UnicodeBuffer::new();
+    buffer.push_str(text);
+    buffer.set_flags(flags);
+    buffer.guess_segment_properties();
+    shaper.shape(buffer, features)
+}
+
+fn attachments(glyphs: &GlyphBuffer) -> Vec<Option<Attachment>> {
+    (0..glyphs.len()).map(|i| glyphs.attachment(i)).collect()
+}
+
+fn attached(glyph: usize, kind: AttachmentKind) -> Option<Attachment> {
+    Some(Attachment { glyph, kind })
+}
+
+#[test]
+fn mark_to_base_and_mark() {
+    with_shaper(NASTALIQ, |shaper| {
+        // KasraNS|ShaddaNS|OneDotBelowNS|BehxSep
+        let glyphs = shape(shaper, "بِّ", BufferFlags::PRESERVE_ATTACHMENTS, &[]);
+        assert_eq!(
+            attachments(&glyphs),
+            [
+                attached(2, AttachmentKind::MarkToMark),
+                attached(3, AttachmentKind::MarkToBase),
+                attached(3, AttachmentKind::MarkToBase),
+                None,
+            ]
+        );
+        let bases: Vec<usize> = (0..glyphs.len())
+            .map(|i| glyphs.attachment_base(i))
+            .collect();
+        assert_eq!(bases, [3, 3, 3, 3]);
+    });
+}
+
+#[test]
+fn cursive() {
+    with_shaper(NASTALIQ, |shaper| {
+        // MeemFin|SeenMed|KasraNS|OneDotBelowNS|sp0|BehxIni
+        let glyphs = shape(shaper, "بِسم", BufferFlags::PRESERVE_ATTACHMENTS, &[]);
+        assert_eq!(
+            attachments(&glyphs),
+            [
+                None,
+                attached(0, AttachmentKind::Cursive),
+                attached(3, AttachmentKind::MarkToMark),
+                attached(5, AttachmentKind::MarkToBase),
+                None,
+                attached(1, AttachmentKind::Cursive),
+            ]
+        );
+        // Cursive chains are not followed to a base.
+        assert_eq!(glyphs.attachment_base(2), 5);
+        assert_eq!(glyphs.attachment_base(5), 5);
+    });
+}
+
+#[test]
+fn mark_to_ligature() {
+    with_shaper("tests/fonts/aots/gpos5_font1.otf", |shaper| {
+        let features = [Feature::new(Tag::new(b"test"), 1, ..)];
+        let flags = BufferFlags::PRESERVE_ATTACHMENTS;
+        // The mark follows the first or the second ligature component.
+        let first = shape(shaper, "\u{11}\u{1E}\u{13}\u{1F}\u{11}", flags, &features);
+        assert_eq!(
+            first.attachment(2),
+            attached(1, AttachmentKind::MarkToLigature { component: 0 })
+        );
+        let second = shape(shaper, "\u{11}\u{1E}\u{1F}\u{13}\u{11}", flags, &features);
+        assert_eq!(
+            second.attachment(2),
+            attached(1, AttachmentKind::MarkToLigature { component: 1 })
+        );
+        assert_eq!(second.attachment_base(2), 1);
+    });
+}
+
+#[test]
+fn not_preserved() {
+    with_shaper(NASTALIQ, |shaper| {
+        let text = "بِسم";
+        let plain = shape(shaper, text, BufferFlags::default(), &[]);
+        let preserved = shape(shaper, text, BufferFlags::PRESERVE_ATTACHMENTS, &[]);
+        assert!(attachments(&plain).iter().all(Option::is_none));
+        assert_eq!(plain.attachment_base(2), 2);
+        // Preserving attachments doesn't change the positions.
+        assert_eq!(
+            plain.serialize(shaper, SerializeFlags::default()),
+            preserved.serialize(shaper, SerializeFlags::default())
+        );
+    });
+}
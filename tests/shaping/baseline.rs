@@ -0,0 +1,114 @@
+use harfrust::{
+    script, BaselineTag, Direction, FontExtents, FontRef, Script, Shaper, ShaperData,
+    ShaperInstance, Variation,
+};
+use std::str::FromStr;
+
+const CJK: &str = "tests/fonts/rb_custom/NotoSansCJK.subset1.otf";
+const CJK_VF: &str = "tests/fonts/in-house/NotoSansCJK-VF.abc.otf";
+// Only has the `ideo` and `romn` baselines.
+const RASA: &str = "tests/fonts/rb_custom/Rasa.subset1.otf";
+
+const IDEOGRAPHIC: [BaselineTag; 7] = [
+    BaselineTag::Roman,
+    BaselineTag::IdeoFaceBottomOrLeft,
+    BaselineTag::IdeoFaceTopOrRight,
+    BaselineTag::IdeoFaceCentral,
+    BaselineTag::IdeoEmboxBottomOrLeft,
+    BaselineTag::IdeoEmboxTopOrRight,
+    BaselineTag::IdeoEmboxCentral,
+];
+
+fn with_shaper(path: &str, variations: &[&str], f: impl FnOnce(&Shaper)) {
+    let font_data = std::fs::read(path).unwrap();
+    let font = FontRef::new(&font_data).unwrap();
+    let data = ShaperData::new(&font);
+    let variations: Vec<_> = variations
+        .iter()
+        .map(|variation| Variation::from_str(variation).unwrap())
+        .collect();
+    let instance =
+        (!variations.is_empty()).then(|| ShaperInstance::from_variations(&font, &variations));
+    let shaper = data.shaper(&font).instance(instance.as_ref()).build();
+    f(&shaper);
+}
+
+fn baselines(shaper: &Shaper, direction: Direction, script: Script) -> [i32; 7] {
+    IDEOGRAPHIC.map(|baseline| shaper.baseline(baseline, direction, Some(script), None))
+}
+
+#[test]
+fn horizontal() {
+    with_shaper(CJK, &[], |shaper| {
+        assert_eq!(
+            baselines(shaper, Direction::LeftToRight, script::HAN),
+            [0, -74, 834, 380, -120, 880, 380]
+        );
+    });
+}
+
+#[test]
+fn vertical() {
+    with_shaper(CJK, &[], |shaper| {
+        assert_eq!(
+            baselines(shaper, Direction::TopToBottom, script::HAN),
+            [120, 46, 954, 500, 0, 1000, 500]
+        );
+    });
+}
+
+#[test]
+fn variations() {
+    // The default instance is the lightest.
+    for (variations, face_bottom, face_top) in [(&[][..], -67, 827), (&["wght=900"], -94, 854)] {
+        with_shaper(CJK_VF, variations, |shaper| {
+            let baseline =
+                |tag| shaper.baseline(tag, Direction::LeftToRight, Some(script::HAN), None);
+            assert_eq!(baseline(BaselineTag::IdeoFaceBottomOrLeft), face_bottom);
+            assert_eq!(baseline(BaselineTag::IdeoFaceTopOrRight), face_top);
+            // The em box doesn't vary.
+            assert_eq!(baseline(BaselineTag::IdeoEmboxBottomOrLeft), -120);
+            assert_eq!(baseline(BaselineTag::IdeoEmboxTopOrRight), 880);
+        });
+    }
+}
+
+#[test]
+fn synthesized() {
+    with_shaper(RASA, &[], |shaper| {
+        let [roman, face_bottom, face_top, face_central, bottom, top, central] =
+            baselines(shaper, Direction::LeftToRight, script::GUJARATI);
+        assert_eq!([roman, bottom], [0, -236]);
+        // The top of the em box is one em above its bottom, the centers are
+        // halfway, and the character face is inset from the em box.
+        assert_eq!(top, bottom + 1000);
+        assert_eq!(central, (bottom + top) / 2);
+        assert_eq!(face_central, (face_bottom + face_top) / 2);
+        assert!(bottom < face_bottom && face_top < top);
+    });
+}
+
+#[test]
+fn font_extents() {
+    // Without `BASE` extents, the line metrics of the font are used.
+    with_shaper(CJK, &[], |shaper| {
+        assert_eq!(
+            shaper.font_extents(Direction::LeftToRight, Some(script::HAN), None),
+            FontExtents {
+                ascender: 1160,
+                descender: -288,
+                line_gap: 0,
+            }
+        );
+    });
+    with_shaper(RASA, &[], |shaper| {
+        assert_eq!(
+            shaper.font_extents(Direction::LeftToRight, Some(script::GUJARATI), None),
+            FontExtents {
+                ascender: 728,
+                descender: -272,
+                line_gap: 218,
+            }
+        );
+    });
+}
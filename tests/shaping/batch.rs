@@ -0,0 +1,138 @@
+use harfrust::{
+    BufferClusterLevel, BufferFlags, Direction, FontRef, GlyphBuffer, SerializeFlags, ShapeItem,
+    Shaper, ShaperData, UnicodeBuffer,
+};
+
+const PATH: &str = "tests/fonts/in-house/NotoNastaliqUrdu-Regular.ttf";
+
+fn with_shaper(f: impl FnOnce(&Shaper)) {
+    let font_data = std::fs::read(PATH).unwrap();
+    let font = FontRef::new(&font_data).unwrap();
+    let data = ShaperData::new(&font);
+    let shaper = data.shaper(&font).build();
+    f(&shaper);
+}
+
+/// Shapes an item on its own, like a batch does.
+fn shape_item(shaper: &Shaper, text: &str, item: &ShapeItem) -> GlyphBuffer {
+    let mut buffer = UnicodeBuffer::new();
+    buffer.add_utf8(text, item.range.start, item.range.len());
+    buffer.set_direction(item.direction);
+    buffer.set_script(item.script);
+    if let Some(language) = &item.language {
+        buffer.set_language(language.clone());
+    }
+    buffer.set_flags(item.flags);
+    buffer.set_cluster_level(item.cluster_level);
+    shaper.shape(buffer, item.features)
+}
+
+fn serialize(shaper: &Shaper, buffer: &GlyphBuffer) -> String {
+    buffer.serialize(shaper, SerializeFlags::GLYPH_FLAGS)
+}
+
+/// A paragraph of Urdu words, with every word as an item that has
+/// beginning and end of text flags at the paragraph ends, unsafe to concat
+/// flags and alternating cluster levels.
+fn items(text: &str) -> Vec<ShapeItem<'static>> {
+    let mut items = Vec::new();
+    let mut start = 0;
+    for (i, word) in text.split_inclusive(' ').enumerate() {
+        let end = start + word.len();
+        let mut item = ShapeItem::new(start..end, Direction::RightToLeft, "Arab".parse().unwrap());
+        item.flags = BufferFlags::PRODUCE_UNSAFE_TO_CONCAT;
+        item.flags.set(BufferFlags::BEGINNING_OF_TEXT, start == 0);
+        item.flags.set(BufferFlags::END_OF_TEXT, end == text.len());
+        if i % 2 == 1 {
+            item.cluster_level = BufferClusterLevel::Characters;
+        }
+        items.push(item);
+        start = end;
+    }
+    items
+}
+
+const TEXT: &str = "اُردو زبان کی ترویج و ترقی کے لیے بِسم اللہ سے شروع کریں۔";
+
+#[test]
+fn matches_single_items() {
+    with_shaper(|shaper| {
+        let items = items(TEXT);
+        let mut outputs = Vec::new();
+        shaper.shape_batch(TEXT, &items, &mut outputs);
+        assert_eq!(outputs.len(), items.len());
+        for (item, output) in items.iter().zip(&outputs) {
+            let expected = shape_item(shaper, TEXT, item);
+            assert_eq!(serialize(shaper, output), serialize(shaper, &expected));
+        }
+    });
+}
+
+#[test]
+fn parallel_matches_sequential() {
+    with_shaper(|shaper| {
+        let items = items(TEXT);
+        let mut sequential = Vec::new();
+        shaper.shape_batch(TEXT, &items, &mut sequential);
+        for threads in [0, 1, 2, 4] {
+            let mut parallel = Vec::new();
+            shaper.shape_batch_parallel(TEXT, &items, &mut parallel, threads);
+            assert_eq!(parallel.len(), sequential.len());
+            for (parallel, sequential) in parallel.iter().zip(&sequential) {
+                assert_eq!(serialize(shaper, parallel), serialize(shaper, sequential));
+            }
+        }
+    });
+}
+
+#[test]
+fn item_properties() {
+    with_shaper(|shaper| {
+        let text = "بِسم";
+        let item = ShapeItem::new(
+            0..text.len(),
+            Direction::RightToLeft,
+            "Arab".parse().unwrap(),
+        );
+        let mut characters = item.clone();
+        characters.cluster_level = BufferClusterLevel::Characters;
+        let mut concat = item.clone();
+        concat.flags = BufferFlags::PRODUCE_UNSAFE_TO_CONCAT;
+        let mut outputs = Vec::new();
+        shaper.shape_batch(text, &[item, characters, concat], &mut outputs);
+
+        let clusters = |buffer: &GlyphBuffer| -> Vec<u32> {
+            buffer
+                .glyph_infos()
+                .iter()
+                .map(|info| info.cluster)
+                .collect()
+        };
+        // The kasra gets a cluster of its own at the character level.
+        assert!(!clusters(&outputs[0]).contains(&2));
+        assert!(clusters(&outputs[1]).contains(&2));
+        assert!(!outputs[0]
+            .glyph_infos()
+            .iter()
+            .any(|info| info.unsafe_to_concat()));
+        assert!(outputs[2]
+            .glyph_infos()
+            .iter()
+            .any(|info| info.unsafe_to_concat()));
+    });
+}
+
+#[test]
+fn reuses_outputs() {
+    with_shaper(|shaper| {
+        let items = items(TEXT);
+        let mut outputs = Vec::new();
+        shaper.shape_batch(TEXT, &items, &mut outputs);
+        shaper.shape_batch(TEXT, &items[..2], &mut outputs);
+        assert_eq!(outputs.len(), 2);
+        for (item, output) in items.iter().zip(&outputs) {
+            let expected = shape_item(shaper, TEXT, item);
+            assert_eq!(serialize(shaper, output), serialize(shaper, &expected));
+        }
+    });
+}
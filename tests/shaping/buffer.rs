@@ -0,0 +1,164 @@
+use harfrust::{FontRef, SerializeFlags, Shaper, ShaperData, UnicodeBuffer};
+
+const PATH: &str = "tests/fonts/rb_custom/PT_Sans-Caption-Web-Regular.ttf";
+
+fn with_shaper(f: impl FnOnce(&Shaper)) {
+    let font_data = std::fs::read(PATH).unwrap();
+    let font = FontRef::new(&font_data).unwrap();
+    let data = ShaperData::new(&font);
+    let shaper = data.shaper(&font).build();
+    f(&shaper);
+}
+
+fn shape(shaper: &Shaper, mut buffer: UnicodeBuffer) -> String {
+    buffer.guess_segment_properties();
+    shaper
+        .shape(buffer, &[])
+        .serialize(shaper, SerializeFlags::NO_POSITIONS)
+}
+
+fn shape_bytes(shaper: &Shaper, bytes: &[u8], replacement: Option<char>) -> String {
+    let mut buffer = UnicodeBuffer::new();
+    if let Some(replacement) = replacement {
+        buffer.set_replacement_codepoint(replacement);
+    }
+    buffer.push_utf8_bytes(bytes);
+    shape(shaper, buffer)
+}
+
+#[test]
+fn push_utf8_bytes() {
+    with_shaper(|shaper| {
+        assert_eq!(
+            shape_bytes(shaper, "añb".as_bytes(), None),
+            "a=0|ntilde=1|b=3"
+        );
+        // A stray continuation byte, a truncated sequence in the middle and
+        // one at the end are each replaced once, at the offset they start.
+        assert_eq!(
+            shape_bytes(shaper, b"a\xffb\xe2\x82c\xf0\x9f\x98", None),
+            "a=0|.notdef=1|b=2|.notdef=3|c=5|.notdef=6"
+        );
+        // Every byte of an encoded surrogate is ill-formed on its own.
+        assert_eq!(
+            shape_bytes(shaper, b"a\xed\xa0\x80", None),
+            "a=0|.notdef=1|.notdef=2|.notdef=3"
+        );
+    });
+}
+
+#[test]
+fn custom_replacement() {
+    with_shaper(|shaper| {
+        let mut buffer = UnicodeBuffer::new();
+        assert_eq!(buffer.replacement_codepoint(), '\u{FFFD}');
+        buffer.set_replacement_codepoint('?');
+        assert_eq!(buffer.replacement_codepoint(), '?');
+        assert_eq!(
+            shape_bytes(shaper, b"a\xffb\xe2\x82c", Some('?')),
+            "a=0|question=1|b=2|question=3|c=5"
+        );
+    });
+}
+
+#[test]
+fn invisible_glyph() {
+    with_shaper(|shaper| {
+        let shape_zwsp = |invisible| {
+            let mut buffer = UnicodeBuffer::new();
+            buffer.set_invisible_glyph(invisible);
+            assert_eq!(buffer.invisible_glyph(), invisible);
+            buffer.push_str("a\u{200B}b");
+            shape(shaper, buffer)
+        };
+        assert_eq!(shape_zwsp(Some(5)), "a=0|quotedbl=1|b=4");
+        assert_eq!(shape_zwsp(None), "a=0|space=1|b=4");
+    });
+}
+
+#[test]
+fn clear_resets_properties() {
+    with_shaper(|shaper| {
+        let mut buffer = UnicodeBuffer::new();
+        buffer.set_replacement_codepoint('?');
+        buffer.set_invisible_glyph(Some(5));
+        buffer.push_str("a");
+        buffer.guess_segment_properties();
+        let buffer = shaper.shape(buffer, &[]).clear();
+        assert_eq!(buffer.replacement_codepoint(), '\u{FFFD}');
+        assert_eq!(buffer.invisible_glyph(), None);
+
+        let mut buffer = buffer;
+        buffer.set_replacement_codepoint('?');
+        buffer.set_invisible_glyph(Some(5));
+        buffer.clear();
+        assert_eq!(buffer.replacement_codepoint(), '\u{FFFD}');
+        assert_eq!(buffer.invisible_glyph(), None);
+    });
+}
+
+#[test]
+fn add_utf8_item() {
+    with_shaper(|shaper| {
+        let text = "añb c";
+        let mut buffer = UnicodeBuffer::new();
+        buffer.add_utf8(text, 1, 3);
+        assert_eq!(shape(shaper, buffer), "ntilde=1|b=3");
+    });
+}
+
+#[test]
+fn add_utf16_item() {
+    with_shaper(|shaper| {
+        let text: Vec<u16> = "a😀ñb".encode_utf16().collect();
+        let mut buffer = UnicodeBuffer::new();
+        buffer.add_utf16(&text, 0, text.len());
+        assert_eq!(shape(shaper, buffer), "a=0|.notdef=1|ntilde=3|b=4");
+
+        let mut buffer = UnicodeBuffer::new();
+        buffer.add_utf16(&text, 3, 2);
+        assert_eq!(shape(shaper, buffer), "ntilde=3|b=4");
+
+        // An unpaired surrogate is replaced.
+        let mut buffer = UnicodeBuffer::new();
+        buffer.set_replacement_codepoint('?');
+        buffer.add_utf16(&[0x61, 0xD800, 0x62], 0, 3);
+        assert_eq!(shape(shaper, buffer), "a=0|question=1|b=2");
+    });
+}
+
+#[test]
+fn add_codepoints_item() {
+    with_shaper(|shaper| {
+        let text = [0x61, 0xF1, 0x110000, 0xDC00, 0x62];
+        let mut buffer = UnicodeBuffer::new();
+        buffer.set_replacement_codepoint('?');
+        buffer.add_codepoints(&text, 1, 3);
+        assert_eq!(shape(shaper, buffer), "ntilde=1|question=2|question=3");
+    });
+}
+
+#[test]
+fn item_context() {
+    let font_data = std::fs::read("tests/fonts/in-house/NotoNastaliqUrdu-Regular.ttf").unwrap();
+    let font = FontRef::new(&font_data).unwrap();
+    let data = ShaperData::new(&font);
+    let shaper = data.shaper(&font).build();
+    // The lam joins the characters around it.
+    let text = "سلام";
+    let utf16: Vec<u16> = text.encode_utf16().collect();
+    let codepoints: Vec<u32> = text.chars().map(u32::from).collect();
+
+    let mut buffer = UnicodeBuffer::new();
+    buffer.push_str("ل");
+    assert_eq!(shape(&shaper, buffer), "LamSep=0");
+    let mut buffer = UnicodeBuffer::new();
+    buffer.add_utf8(text, 2, 2);
+    assert_eq!(shape(&shaper, buffer), "LamMed=2");
+    let mut buffer = UnicodeBuffer::new();
+    buffer.add_utf16(&utf16, 1, 1);
+    assert_eq!(shape(&shaper, buffer), "LamMed=1");
+    let mut buffer = UnicodeBuffer::new();
+    buffer.add_codepoints(&codepoints, 1, 1);
+    assert_eq!(shape(&shaper, buffer), "LamMed=1");
+}
@@ -0,0 +1,81 @@
+use harfrust::{Direction, FontRef, Shaper, ShaperData, ShaperInstance, Variation};
+use std::str::FromStr;
+
+/// TestGPOSFour.ttf with a GDEF `LigCaretList`, for glyphs:
+///
+/// - 1: a caret on point 62, on the right edge, and one at 500.
+/// - 2: a caret at 300 with a variation index that adds 12 at `wdth=70`, and
+///   one at 400 with a Device table that adds 3 at 12 ppem.
+/// - 5: a caret on point 94, at the top of its second component, glyph 11,
+///   and one on point 1000, which doesn't exist.
+const PATH: &str = "tests/fonts/rb_custom/TestGPOSFour-LigCarets.ttf";
+
+fn with_shaper(variations: &[&str], f: impl FnOnce(&Shaper)) {
+    let font_data = std::fs::read(PATH).unwrap();
+    let font = FontRef::new(&font_data).unwrap();
+    let data = ShaperData::new(&font);
+    let variations: Vec<_> = variations
+        .iter()
+        .map(|variation| Variation::from_str(variation).unwrap())
+        .collect();
+    let instance =
+        (!variations.is_empty()).then(|| ShaperInstance::from_variations(&font, &variations));
+    let shaper = data.shaper(&font).instance(instance.as_ref()).build();
+    f(&shaper);
+}
+
+/// Returns the right edge and the top of the extents of `glyph`.
+fn right_and_top(shaper: &Shaper, glyph: u32) -> (i32, i32) {
+    let extents = shaper.glyph_extents(glyph).unwrap();
+    (extents.x_bearing + extents.width, extents.y_bearing)
+}
+
+#[test]
+fn carets_at_default_instance() {
+    with_shaper(&[], |shaper| {
+        let ltr = Direction::LeftToRight;
+        assert_eq!(shaper.ligature_carets(1, ltr), [1159, 500]);
+        // Device tables are ignored.
+        assert_eq!(shaper.ligature_carets(2, ltr), [300, 400]);
+        // Point 5 of glyph 11, (124, 775), offset by (679, -131).
+        assert_eq!(shaper.ligature_carets(5, ltr), [803, 0]);
+        assert_eq!(right_and_top(shaper, 5).1, 644);
+        assert!(shaper.ligature_carets(3, ltr).is_empty());
+    });
+}
+
+#[test]
+fn carets_at_varied_instance() {
+    with_shaper(&["wght=900"], |shaper| {
+        let (right, _) = right_and_top(shaper, 1);
+        assert_eq!(right, 1421);
+        assert_eq!(
+            shaper.ligature_carets(1, Direction::LeftToRight),
+            [right, 500]
+        );
+
+        // The caret point of the composite glyph follows the varied offset
+        // of its component.
+        let ttb = Direction::TopToBottom;
+        let (_, top) = right_and_top(shaper, 5);
+        let (_, origin) = shaper.glyph_origin(5, ttb);
+        assert_eq!(top, 821);
+        assert_eq!(shaper.ligature_carets(5, ttb), [top - origin, 0]);
+    });
+}
+
+#[test]
+fn caret_variation_deltas() {
+    with_shaper(&["wdth=70"], |shaper| {
+        assert_eq!(
+            shaper.ligature_carets(2, Direction::LeftToRight),
+            [312, 400]
+        );
+    });
+    with_shaper(&["wght=900"], |shaper| {
+        assert_eq!(
+            shaper.ligature_carets(2, Direction::LeftToRight),
+            [300, 400]
+        );
+    });
+}
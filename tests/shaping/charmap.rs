@@ -0,0 +1,161 @@
+use harfrust::{Charmap, FontRef, ShaperData};
+
+const PT_SANS: &str = "tests/fonts/rb_custom/PT_Sans-Caption-Web-Regular.ttf";
+// Format 4 subtable mapping X, x and U+030A.
+const FORMAT_4: &str = "tests/fonts/in-house/4fac3929fc3332834e93673780ec0fe94342d193.ttf";
+// Format 12 subtable mapping U+10300 and U+10301.
+const FORMAT_12: &str = "tests/fonts/in-house/73e84dac2fc6a2d1bc9250d1414353661088937d.ttf";
+
+fn with_charmap(font_data: &[u8], f: impl FnOnce(&Charmap)) {
+    let font = FontRef::new(font_data).unwrap();
+    let data = ShaperData::new(&font);
+    let shaper = data.shaper(&font).build();
+    f(shaper.charmap());
+}
+
+/// Returns the missing clusters of `text` as pairs of byte offsets.
+fn missing(charmap: &Charmap, text: &str) -> Vec<(usize, usize)> {
+    let clusters = charmap.missing_clusters(text);
+    clusters
+        .iter()
+        .map(|range| (range.start, range.end))
+        .collect()
+}
+
+fn read(path: &str) -> Vec<u8> {
+    std::fs::read(path).unwrap()
+}
+
+fn be16(data: &[u8], offset: usize) -> u16 {
+    u16::from_be_bytes([data[offset], data[offset + 1]])
+}
+
+fn be32(data: &[u8], offset: usize) -> usize {
+    u32::from_be_bytes(data[offset..offset + 4].try_into().unwrap()) as usize
+}
+
+/// Turns the only subtable of the format 4 font into a symbol one, moving
+/// the segments of U+0000..U+00FF to U+F000..U+F0FF.
+fn to_symbol_font(mut data: Vec<u8>) -> Vec<u8> {
+    let num_tables = usize::from(be16(&data, 4));
+    let cmap = (0..num_tables)
+        .map(|i| 12 + i * 16)
+        .find(|&record| &data[record..record + 4] == b"cmap")
+        .map(|record| be32(&data, record + 8))
+        .unwrap();
+    // A single Windows Unicode BMP encoding record.
+    assert_eq!(data[cmap + 2..cmap + 8], [0, 1, 0, 3, 0, 1]);
+    data[cmap + 7] = 0;
+
+    let subtable = cmap + be32(&data, cmap + 8);
+    assert_eq!(be16(&data, subtable), 4);
+    let seg_count_x2 = usize::from(be16(&data, subtable + 6));
+    let end_codes = subtable + 14;
+    let start_codes = end_codes + seg_count_x2 + 2;
+    let deltas = start_codes + seg_count_x2;
+    let range_offsets = deltas + seg_count_x2;
+    let mut segments: Vec<[u16; 4]> = (0..seg_count_x2)
+        .step_by(2)
+        .map(|i| {
+            [end_codes, start_codes, deltas, range_offsets].map(|array| be16(&data, array + i))
+        })
+        .collect();
+    for [end, start, delta, range_offset] in &mut segments {
+        if *end <= 0xFF {
+            assert_eq!(*range_offset, 0);
+            *end += 0xF000;
+            *start += 0xF000;
+            *delta = delta.wrapping_sub(0xF000);
+        }
+    }
+    segments.sort_by_key(|segment| segment[0]);
+    for (i, segment) in segments.iter().enumerate() {
+        for (array, value) in [end_codes, start_codes, deltas, range_offsets]
+            .into_iter()
+            .zip(segment)
+        {
+            data[array + i * 2..array + i * 2 + 2].copy_from_slice(&value.to_be_bytes());
+        }
+    }
+    data
+}
+
+#[test]
+fn mappings_format_4() {
+    with_charmap(&read(FORMAT_4), |charmap| {
+        let mappings: Vec<_> = charmap.mappings().collect();
+        assert_eq!(mappings, [('X', 1), ('x', 2), ('\u{30A}', 3)]);
+        assert_eq!(charmap.map('x'), Some(2));
+        assert_eq!(charmap.map('y'), None);
+    });
+}
+
+#[test]
+fn mappings_format_12() {
+    with_charmap(&read(FORMAT_12), |charmap| {
+        let mappings: Vec<_> = charmap.mappings().collect();
+        assert_eq!(mappings, [('\u{10300}', 1), ('\u{10301}', 2)]);
+        assert_eq!(charmap.map('\u{10301}'), Some(2));
+        assert_eq!(charmap.map('\u{10302}'), None);
+    });
+}
+
+#[test]
+fn mappings_symbol() {
+    with_charmap(&to_symbol_font(read(FORMAT_4)), |charmap| {
+        let mappings: Vec<_> = charmap.mappings().collect();
+        // The U+F0xx characters are duplicated at U+00xx.
+        assert_eq!(
+            mappings,
+            [
+                ('\u{30A}', 3),
+                ('\u{F058}', 1),
+                ('\u{F078}', 2),
+                ('X', 1),
+                ('x', 2),
+            ]
+        );
+        assert_eq!(charmap.map('x'), Some(2));
+        assert_eq!(charmap.map('\u{F078}'), Some(2));
+        assert_eq!(charmap.map('\u{178}'), None);
+    });
+}
+
+#[test]
+fn missing_clusters() {
+    with_charmap(&read(FORMAT_4), |charmap| {
+        assert_eq!(missing(charmap, "x\u{30A}X"), []);
+        assert_eq!(missing(charmap, "Xx\u{30A}\u{30A}x\u{308}"), [(6, 9)]);
+        // Emoji modifiers and regional indicator pairs are single clusters.
+        assert_eq!(missing(charmap, "👍🏽x"), [(0, 8)]);
+        assert_eq!(
+            missing(charmap, "\u{1F1E6}\u{1F1E6}\u{1F1E6}"),
+            [(0, 8), (8, 12)]
+        );
+        // ZWJ joins the emoji to the cluster of the x.
+        assert_eq!(missing(charmap, "x\u{200D}👍"), [(0, 8)]);
+    });
+}
+
+#[test]
+fn missing_clusters_decomposition() {
+    with_charmap(&read(PT_SANS), |charmap| {
+        // U+01D8 decomposes to ü and an acute, U+212B to Å.
+        assert_eq!(charmap.map('\u{1D8}'), None);
+        assert_eq!(charmap.map('\u{212B}'), None);
+        assert_eq!(missing(charmap, "\u{1D8}\u{212B}"), []);
+        // Neither U+1E8B nor its dot above is supported.
+        assert_eq!(missing(charmap, "a\u{1E8B}b"), [(1, 4)]);
+    });
+}
+
+#[test]
+fn missing_clusters_composition() {
+    with_charmap(&read(PT_SANS), |charmap| {
+        // U+0308 only composes with the base to supported characters, the
+        // second time with the acute that is supported on its own.
+        assert_eq!(charmap.map('\u{308}'), None);
+        assert_eq!(missing(charmap, "a\u{308}u\u{308}\u{301}"), []);
+        assert_eq!(missing(charmap, "x\u{308}a\u{308}"), [(0, 3)]);
+    });
+}
@@ -0,0 +1,295 @@
+use harfrust::{
+    BufferClusterLevel, BufferFlags, Direction, FontRef, GlyphBuffer, Hit, Shaper, ShaperData,
+    UnicodeBuffer,
+};
+use std::ops::Range;
+
+const PT_SANS: &str = "tests/fonts/rb_custom/PT_Sans-Caption-Web-Regular.ttf";
+const NASTALIQ: &str = "tests/fonts/in-house/NotoNastaliqUrdu-Regular.ttf";
+const DEVANAGARI: &str = "tests/fonts/text-rendering-tests/FDArrayTest257.otf";
+
+fn with_shaper(path: &str, f: impl FnOnce(&Shaper)) {
+    let font_data = std::fs::read(path).unwrap();
+    let font = FontRef::new(&font_data).unwrap();
+    let data = ShaperData::new(&font);
+    let shaper = data.shaper(&font).build();
+    f(&shaper);
+}
+
+fn shape(shaper: &Shaper, text: &str) -> GlyphBuffer {
+    let mut buffer = UnicodeBuffer::new();
+    buffer.push_str(text);
+    buffer.guess_segment_properties();
+    shaper.shape(buffer, &[])
+}
+
+fn shape_with(
+    shaper: &Shaper,
+    text: &str,
+    cluster_level: BufferClusterLevel,
+    flags: BufferFlags,
+) -> GlyphBuffer {
+    let mut buffer = UnicodeBuffer::new();
+    buffer.push_str(text);
+    buffer.set_cluster_level(cluster_level);
+    buffer.set_flags(flags);
+    buffer.guess_segment_properties();
+    shaper.shape(buffer, &[])
+}
+
+/// Returns the text range, glyph range and advance of every cluster.
+fn clusters(glyphs: &GlyphBuffer, text: &str) -> Vec<(Range<usize>, Range<usize>, i32)> {
+    glyphs
+        .clusters(0..text.len())
+        .map(|cluster| (cluster.text, cluster.glyphs, cluster.advance))
+        .collect()
+}
+
+fn hit(text: Range<usize>, trailing: bool) -> Hit {
+    Hit { text, trailing }
+}
+
+#[test]
+fn ltr_ligature() {
+    with_shaper(PT_SANS, |shaper| {
+        // O=0+747|f=1+364|fi=2+643|c=4+506|e=5+598
+        let text = "Office";
+        let glyphs = shape(shaper, text);
+        assert_eq!(glyphs.len(), 5);
+        let fi = 747 + 364;
+        let offsets: Vec<i32> = (0..=text.len())
+            .map(|index| glyphs.caret_offset(shaper, text, index))
+            .collect();
+        // The ligature is divided equally between "f" and "i".
+        assert_eq!(
+            offsets,
+            [0, 747, fi, fi + 321, fi + 643, fi + 643 + 506, 2858]
+        );
+
+        assert_eq!(glyphs.hit_test(shaper, text, fi + 10), hit(2..3, false));
+        assert_eq!(glyphs.hit_test(shaper, text, fi + 200), hit(2..3, true));
+        assert_eq!(glyphs.hit_test(shaper, text, fi + 330), hit(3..4, false));
+        assert_eq!(glyphs.hit_test(shaper, text, fi + 600), hit(3..4, true));
+        assert_eq!(glyphs.hit_test(shaper, text, -100), hit(0..1, false));
+        assert_eq!(glyphs.hit_test(shaper, text, 5000), hit(5..6, true));
+    });
+}
+
+#[test]
+fn ltr_ligature_at_start() {
+    with_shaper(PT_SANS, |shaper| {
+        // fi=0+643|t=2+405
+        let text = "fit";
+        let glyphs = shape(shaper, text);
+        assert_eq!(glyphs.len(), 2);
+        // Positions before the ligature hit the leading side of the "f".
+        assert_eq!(glyphs.hit_test(shaper, text, -5), hit(0..1, false));
+        assert_eq!(glyphs.hit_test(shaper, text, 10), hit(0..1, false));
+        assert_eq!(glyphs.hit_test(shaper, text, 630), hit(1..2, true));
+    });
+}
+
+#[test]
+fn ltr_marks() {
+    with_shaper(PT_SANS, |shaper| {
+        // x=0+592|acutecomb=0+0|y=3+531
+        let text = "x\u{301}y";
+        let glyphs = shape(shaper, text);
+        assert_eq!(glyphs.len(), 3);
+        let offsets: Vec<i32> = (0..=text.len())
+            .map(|index| glyphs.caret_offset(shaper, text, index))
+            .collect();
+        // No caret between the base and its mark.
+        assert_eq!(offsets, [0, 0, 0, 592, 1123]);
+        assert_eq!(glyphs.hit_test(shaper, text, 500), hit(0..3, true));
+        assert_eq!(glyphs.hit_test(shaper, text, 600), hit(3..4, false));
+    });
+}
+
+#[test]
+fn rtl() {
+    with_shaper(NASTALIQ, |shaper| {
+        // MeemSep=6+660|AlefSep.LA=4+297|LamFin.LA=2+201|sp0=0+0|SeenIni=0@0,95+607
+        let text = "سلام";
+        let glyphs = shape(shaper, text);
+        let total = 660 + 297 + 201 + 607;
+        let offsets: Vec<i32> = (0..=text.len())
+            .step_by(2)
+            .map(|index| glyphs.caret_offset(shaper, text, index))
+            .collect();
+        assert_eq!(offsets, [total, total - 607, 660 + 297, 660, 0]);
+
+        // The right half of a cluster is its logical start.
+        assert_eq!(glyphs.hit_test(shaper, text, total - 10), hit(0..2, false));
+        assert_eq!(glyphs.hit_test(shaper, text, 660 + 290), hit(4..6, false));
+        assert_eq!(glyphs.hit_test(shaper, text, 660 + 10), hit(4..6, true));
+        assert_eq!(glyphs.hit_test(shaper, text, -10), hit(6..8, true));
+        assert_eq!(glyphs.hit_test(shaper, text, total + 10), hit(0..2, false));
+    });
+}
+
+#[test]
+fn rtl_marks() {
+    with_shaper(NASTALIQ, |shaper| {
+        // MeemFin=6+438|SeenMed=4+665|KasraNS=0+0|OneDotBelowNS=0+0|sp0=0+0|BehxIni=0+249
+        let text = "بِسم";
+        let glyphs = shape(shaper, text);
+        let total = 438 + 665 + 249;
+        // Between the beh and its kasra is the start of the cluster.
+        assert_eq!(glyphs.caret_offset(shaper, text, 0), total);
+        assert_eq!(glyphs.caret_offset(shaper, text, 2), total);
+        assert_eq!(glyphs.caret_offset(shaper, text, 4), total - 249);
+        assert_eq!(glyphs.hit_test(shaper, text, total - 10), hit(0..4, false));
+        assert_eq!(glyphs.hit_test(shaper, text, total - 200), hit(0..4, true));
+    });
+}
+
+#[test]
+fn empty() {
+    with_shaper(PT_SANS, |shaper| {
+        let glyphs = shape(shaper, "");
+        assert_eq!(glyphs.hit_test(shaper, "", 10), hit(0..0, false));
+        assert_eq!(glyphs.caret_offset(shaper, "", 0), 0);
+    });
+}
+
+#[test]
+fn vertical() {
+    with_shaper(PT_SANS, |shaper| {
+        let text = "fi";
+        let mut buffer = UnicodeBuffer::new();
+        buffer.push_str(text);
+        buffer.guess_segment_properties();
+        buffer.set_direction(Direction::TopToBottom);
+        let glyphs = shaper.shape(buffer, &[]);
+        let advances: Vec<i32> = glyphs
+            .glyph_positions()
+            .iter()
+            .map(|pos| -pos.y_advance)
+            .collect();
+        let total: i32 = advances.iter().sum();
+        assert_eq!(glyphs.caret_offset(shaper, text, 0), 0);
+        assert_eq!(glyphs.caret_offset(shaper, text, 2), total);
+        assert_eq!(glyphs.hit_test(shaper, text, total - 1), hit(1..2, true));
+    });
+}
+
+#[test]
+fn clusters_ltr() {
+    with_shaper(PT_SANS, |shaper| {
+        let text = "Office";
+        let glyphs = shape(shaper, text);
+        assert_eq!(
+            clusters(&glyphs, text),
+            [
+                (0..1, 0..1, 747),
+                (1..2, 1..2, 364),
+                (2..4, 2..3, 643),
+                (4..5, 3..4, 506),
+                (5..6, 4..5, 598),
+            ]
+        );
+        assert_eq!(glyphs.cluster_map(0..text.len()), [0, 1, 2, 2, 3, 4]);
+    });
+}
+
+#[test]
+fn clusters_rtl() {
+    with_shaper(NASTALIQ, |shaper| {
+        let text = "سلام";
+        let glyphs = shape(shaper, text);
+        // Glyph order is visual, so the last characters come first.
+        assert_eq!(
+            clusters(&glyphs, text),
+            [
+                (6..8, 0..1, 660),
+                (4..6, 1..2, 297),
+                (2..4, 2..3, 201),
+                (0..2, 3..5, 607),
+            ]
+        );
+        assert_eq!(glyphs.cluster_map(0..text.len()), [3, 3, 2, 2, 1, 1, 0, 0]);
+    });
+}
+
+#[test]
+fn clusters_levels() {
+    with_shaper(PT_SANS, |shaper| {
+        let text = "x\u{301}y";
+        let graphemes = shape_with(
+            shaper,
+            text,
+            BufferClusterLevel::MonotoneGraphemes,
+            BufferFlags::default(),
+        );
+        assert_eq!(
+            clusters(&graphemes, text),
+            [(0..3, 0..2, 592), (3..4, 2..3, 531)]
+        );
+        assert_eq!(graphemes.cluster_map(0..text.len()), [0, 0, 0, 2]);
+
+        let characters = shape_with(
+            shaper,
+            text,
+            BufferClusterLevel::Characters,
+            BufferFlags::default(),
+        );
+        assert_eq!(
+            clusters(&characters, text),
+            [(0..1, 0..1, 592), (1..3, 1..2, 0), (3..4, 2..3, 531)]
+        );
+        assert_eq!(characters.cluster_map(0..text.len()), [0, 1, 1, 2]);
+    });
+}
+
+#[test]
+fn clusters_reordered() {
+    with_shaper(DEVANAGARI, |shaper| {
+        // The vowel sign I is reordered before the consonant.
+        let text = "\u{915}\u{93F}";
+        let graphemes = shape_with(
+            shaper,
+            text,
+            BufferClusterLevel::MonotoneGraphemes,
+            BufferFlags::default(),
+        );
+        assert_eq!(clusters(&graphemes, text), [(0..6, 0..2, 2000)]);
+        assert_eq!(graphemes.cluster_map(0..text.len()), [0; 6]);
+
+        let characters = shape_with(
+            shaper,
+            text,
+            BufferClusterLevel::Characters,
+            BufferFlags::default(),
+        );
+        assert_eq!(
+            clusters(&characters, text),
+            [(3..6, 0..1, 1000), (0..3, 1..2, 1000)]
+        );
+        assert_eq!(characters.cluster_map(0..text.len()), [1, 1, 1, 0, 0, 0]);
+    });
+}
+
+#[test]
+fn clusters_removed_default_ignorables() {
+    with_shaper(PT_SANS, |shaper| {
+        // The ZWJ has no glyph and belongs to the cluster before it.
+        let text = "a\u{200D}b";
+        let glyphs = shape_with(
+            shaper,
+            text,
+            BufferClusterLevel::Characters,
+            BufferFlags::REMOVE_DEFAULT_IGNORABLES,
+        );
+        assert_eq!(glyphs.len(), 2);
+        let advances: Vec<i32> = clusters(&glyphs, text)
+            .into_iter()
+            .map(|(_, _, advance)| advance)
+            .collect();
+        assert_eq!(
+            clusters(&glyphs, text),
+            [(0..4, 0..1, advances[0]), (4..5, 1..2, advances[1])]
+        );
+        assert_eq!(glyphs.cluster_map(0..text.len()), [0, 0, 0, 0, 1]);
+    });
+}
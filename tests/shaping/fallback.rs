@@ -0,0 +1,149 @@
+use harfrust::{FallbackRuns, FontRef, FontRun, GlyphInfo, Shaper, ShaperData, UnicodeBuffer};
+
+const PT_SANS: &str = "tests/fonts/rb_custom/PT_Sans-Caption-Web-Regular.ttf";
+const NASTALIQ: &str = "tests/fonts/in-house/NotoNastaliqUrdu-Regular.ttf";
+const CJK: &str = "tests/fonts/rb_custom/NotoSansCJK.subset1.otf";
+
+fn with_shapers(paths: &[&str], f: impl FnOnce(&[Shaper])) {
+    let font_data: Vec<Vec<u8>> = paths
+        .iter()
+        .map(|path| std::fs::read(path).unwrap())
+        .collect();
+    let fonts: Vec<FontRef> = font_data
+        .iter()
+        .map(|data| FontRef::new(data).unwrap())
+        .collect();
+    let data: Vec<ShaperData> = fonts.iter().map(ShaperData::new).collect();
+    let shapers: Vec<Shaper> = fonts
+        .iter()
+        .zip(&data)
+        .map(|(font, data)| data.shaper(font).build())
+        .collect();
+    f(&shapers);
+}
+
+fn shape(shapers: &[Shaper], text: &str) -> FallbackRuns {
+    let mut buffer = UnicodeBuffer::new();
+    buffer.push_str(text);
+    let fallbacks: Vec<&Shaper> = shapers[1..].iter().collect();
+    shapers[0].shape_with_fallback(&fallbacks, buffer, text, &[])
+}
+
+/// Returns the font and text of every run.
+fn runs<'a>(text: &'a str, result: &FallbackRuns) -> Vec<(usize, &'a str)> {
+    result
+        .runs
+        .iter()
+        .map(|run| (run.font, &text[run.text.clone()]))
+        .collect()
+}
+
+fn missing(result: &FallbackRuns) -> Vec<u32> {
+    result
+        .buffer
+        .glyph_infos()
+        .iter()
+        .filter(|info| info.glyph_id == 0)
+        .map(|info| info.cluster)
+        .collect()
+}
+
+#[test]
+fn missing_spans() {
+    with_shapers(&[PT_SANS, NASTALIQ, CJK], |shapers| {
+        let text = "ab 你好 اردو cd";
+        let result = shape(shapers, text);
+        assert_eq!(
+            runs(text, &result),
+            [(0, "ab "), (2, "你好"), (0, " "), (1, "اردو"), (0, " cd")]
+        );
+        assert!(missing(&result).is_empty());
+
+        // The glyphs of a run are those of its font.
+        let FontRun { glyphs, .. } = &result.runs[1];
+        let cjk: Vec<u32> = result.buffer.glyph_infos()[glyphs.clone()]
+            .iter()
+            .map(|info| info.glyph_id)
+            .collect();
+        assert_eq!(cjk, [1, 3]);
+        let clusters: Vec<u32> = result
+            .buffer
+            .glyph_infos()
+            .iter()
+            .map(|info| info.cluster)
+            .collect();
+        assert!(clusters.windows(2).all(|pair| pair[0] <= pair[1]));
+    });
+}
+
+#[test]
+fn fallback_chain() {
+    with_shapers(&[PT_SANS, NASTALIQ, CJK], |shapers| {
+        // The first fallback font doesn't help, the second one does.
+        let text = "你好";
+        let result = shape(shapers, text);
+        assert_eq!(runs(text, &result), [(2, "你好")]);
+        assert!(missing(&result).is_empty());
+    });
+}
+
+#[test]
+fn fallback_exhaustion() {
+    with_shapers(&[PT_SANS, NASTALIQ], |shapers| {
+        // No font has glyphs for the CJK characters, so they keep the
+        // `.notdef` glyphs of the primary font.
+        let text = "ab 你好 اردو";
+        let result = shape(shapers, text);
+        assert_eq!(runs(text, &result), [(0, "ab 你好 "), (1, "اردو")]);
+        assert_eq!(missing(&result), [3, 6]);
+    });
+    with_shapers(&[PT_SANS], |shapers| {
+        let text = "ab 你";
+        let result = shape(shapers, text);
+        assert_eq!(runs(text, &result), [(0, "ab 你")]);
+        assert_eq!(missing(&result), [3]);
+    });
+}
+
+#[test]
+fn primary_font_glyphs() {
+    with_shapers(&[PT_SANS, NASTALIQ], |shapers| {
+        // A word joiner and an unsupported variation selector in Urdu text.
+        for text in ["ار\u{2060}دو", "ار\u{FE0F}دو"] {
+            let mut buffer = UnicodeBuffer::new();
+            buffer.push_str(text);
+            buffer.set_invisible_glyph(Some(100));
+            buffer.set_not_found_variation_selector_glyph(101);
+            let result = shapers[0].shape_with_fallback(&[&shapers[1]], buffer, text, &[]);
+            assert_eq!(runs(text, &result), [(1, text)]);
+
+            // The glyphs set for the primary font are not used by the
+            // fallback font.
+            let mut buffer = UnicodeBuffer::new();
+            buffer.push_str(text);
+            buffer.guess_segment_properties();
+            let alone = shapers[1].shape(buffer, &[]);
+            let glyphs = |infos: &[GlyphInfo]| -> Vec<(u32, u32)> {
+                infos
+                    .iter()
+                    .map(|info| (info.glyph_id, info.cluster))
+                    .collect()
+            };
+            assert_eq!(
+                glyphs(result.buffer.glyph_infos()),
+                glyphs(alone.glyph_infos()),
+                "{text:?}"
+            );
+        }
+    });
+}
+
+#[test]
+#[should_panic(expected = "the buffer must be filled with the text")]
+fn mismatched_buffer() {
+    with_shapers(&[PT_SANS, NASTALIQ], |shapers| {
+        let mut buffer = UnicodeBuffer::new();
+        buffer.push_str("ab اردو");
+        shapers[0].shape_with_fallback(&[&shapers[1]], buffer, "ab اردو cd", &[]);
+    });
+}
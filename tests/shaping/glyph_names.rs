@@ -0,0 +1,95 @@
+use harfrust::{FontRef, Shaper, ShaperData};
+
+// `post` version 2.0 table.
+const PT_SANS: &str = "tests/fonts/rb_custom/PT_Sans-Caption-Web-Regular.ttf";
+// CFF table with a name-keyed charset.
+const CFF: &str = "tests/fonts/in-house/872d2955d326bd6676a06f66b8238ebbaabc212f.ttf";
+
+fn with_shaper(font_data: &[u8], f: impl FnOnce(&Shaper)) {
+    let font = FontRef::new(font_data).unwrap();
+    let data = ShaperData::new(&font);
+    let shaper = data.shaper(&font).build();
+    f(&shaper);
+}
+
+/// Returns the font with its `post` table version set to 1.0, which names
+/// the glyphs in the standard Macintosh order.
+fn to_post_v1_font(mut data: Vec<u8>) -> Vec<u8> {
+    let num_tables = usize::from(u16::from_be_bytes([data[4], data[5]]));
+    let post = (0..num_tables)
+        .map(|i| 12 + i * 16)
+        .find(|&record| &data[record..record + 4] == b"post")
+        .map(|record| u32::from_be_bytes(data[record + 8..record + 12].try_into().unwrap()))
+        .unwrap() as usize;
+    data[post..post + 4].copy_from_slice(&[0, 1, 0, 0]);
+    data
+}
+
+/// Checks that every named glyph below `num_glyphs` is found by its name
+/// and returns the number of named glyphs.
+fn check_round_trip(shaper: &Shaper, num_glyphs: u32) -> usize {
+    let mut named = 0;
+    for glyph in 0..num_glyphs {
+        let Some(name) = shaper.glyph_name(glyph) else {
+            continue;
+        };
+        assert_eq!(shaper.glyph_from_name(name), Some(glyph), "{name}");
+        named += 1;
+    }
+    named
+}
+
+#[test]
+fn round_trip_post_v1() {
+    let data = to_post_v1_font(std::fs::read(PT_SANS).unwrap());
+    with_shaper(&data, |shaper| {
+        assert_eq!(check_round_trip(shaper, 720), 258);
+        assert_eq!(shaper.glyph_name(2), Some("nonmarkingreturn"));
+        assert_eq!(shaper.glyph_name(257), Some("dcroat"));
+        assert_eq!(shaper.glyph_name(258), None);
+    });
+}
+
+#[test]
+fn round_trip_post_v2() {
+    with_shaper(&std::fs::read(PT_SANS).unwrap(), |shaper| {
+        assert_eq!(check_round_trip(shaper, 720), 720);
+        assert_eq!(shaper.glyph_name(36), Some("A"));
+        assert_eq!(shaper.glyph_name(329), Some("afii10017"));
+        assert_eq!(shaper.glyph_name(720), None);
+    });
+}
+
+#[test]
+fn round_trip_cff() {
+    with_shaper(&std::fs::read(CFF).unwrap(), |shaper| {
+        assert_eq!(check_round_trip(shaper, 77), 77);
+        assert_eq!(shaper.glyph_name(2), Some("uni0622"));
+        assert_eq!(shaper.glyph_name(11), Some("uni0625.fina"));
+        assert_eq!(shaper.glyph_name(77), None);
+    });
+}
+
+#[test]
+fn conventional_names() {
+    with_shaper(&std::fs::read(PT_SANS).unwrap(), |shaper| {
+        // Names the font doesn't use map to the nominal glyph.
+        assert_eq!(shaper.glyph_from_name("uni0041"), Some(36));
+        assert_eq!(shaper.glyph_from_name("u0041"), Some(36));
+        assert_eq!(shaper.glyph_from_name("uni0410"), Some(329));
+        assert_eq!(shaper.glyph_from_name("Omega"), Some(313));
+        // The increment sign, which the font draws as the Greek capital delta.
+        assert_eq!(shaper.glyph_from_name("Delta"), Some(312));
+        assert_eq!(shaper.glyph_from_name("gid5"), Some(5));
+        assert_eq!(shaper.glyph_from_name("5"), Some(5));
+        assert_eq!(shaper.glyph_from_name("gid720"), None);
+        assert_eq!(shaper.glyph_from_name("uni41"), None);
+        assert_eq!(shaper.glyph_from_name("uni00410042"), None);
+        assert_eq!(shaper.glyph_from_name(""), None);
+    });
+    with_shaper(&std::fs::read(CFF).unwrap(), |shaper| {
+        assert_eq!(shaper.glyph_from_name("u0622"), Some(2));
+        // Legacy Adobe Glyph List names are not known.
+        assert_eq!(shaper.glyph_from_name("afii57410"), None);
+    });
+}
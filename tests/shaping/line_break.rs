@@ -0,0 +1,173 @@
+use harfrust::{
+    BufferClusterLevel, BufferFlags, FontRef, GlyphBuffer, LineBreaks, Shaper, ShaperData,
+    UnicodeBuffer,
+};
+
+const PT_SANS: &str = "tests/fonts/rb_custom/PT_Sans-Caption-Web-Regular.ttf";
+const NASTALIQ: &str = "tests/fonts/in-house/NotoNastaliqUrdu-Regular.ttf";
+/// Reorders the Devanagari vowel sign I before its consonant, and has
+/// glyphs with an advance of 1000 for it, the consonant KA and space.
+const DEVANAGARI: &str = "tests/fonts/text-rendering-tests/FDArrayTest257.otf";
+
+fn with_shaper(path: &str, f: impl FnOnce(&Shaper)) {
+    let font_data = std::fs::read(path).unwrap();
+    let font = FontRef::new(&font_data).unwrap();
+    let data = ShaperData::new(&font);
+    let shaper = data.shaper(&font).build();
+    f(&shaper);
+}
+
+fn shape(
+    shaper: &Shaper,
+    text: &str,
+    range: std::ops::Range<usize>,
+    level: BufferClusterLevel,
+) -> GlyphBuffer {
+    let mut buffer = UnicodeBuffer::new();
+    buffer.add_utf8(text, range.start, range.len());
+    buffer.set_cluster_level(level);
+    buffer.set_flags(BufferFlags::BEGINNING_OF_TEXT | BufferFlags::END_OF_TEXT);
+    buffer.guess_segment_properties();
+    shaper.shape(buffer, &[])
+}
+
+/// Breaks `text` after every space.
+fn break_lines(
+    shaper: &Shaper,
+    text: &str,
+    level: BufferClusterLevel,
+    max_advance: i32,
+) -> LineBreaks {
+    let paragraph = shape(shaper, text, 0..text.len(), level);
+    let breaks: Vec<usize> = text.match_indices(' ').map(|(i, _)| i + 1).collect();
+    let result = shaper.break_lines(&paragraph, text, &[], &breaks, max_advance);
+    check_lines(shaper, text, level, &result);
+    result
+}
+
+/// Checks that the lines cover the text and that every line has the glyphs
+/// and advance of its text shaped on its own.
+fn check_lines(shaper: &Shaper, text: &str, level: BufferClusterLevel, result: &LineBreaks) {
+    let mut start = 0;
+    let mut glyph = 0;
+    for line in &result.lines {
+        assert_eq!(line.text.start, start);
+        assert_eq!(line.glyphs.start, glyph);
+        start = line.text.end;
+        glyph = line.glyphs.end;
+
+        let expected = shape(shaper, text, line.text.clone(), level);
+        let glyphs = |infos: &[harfrust::GlyphInfo]| -> Vec<(u32, u32)> {
+            infos
+                .iter()
+                .map(|info| (info.glyph_id, info.cluster))
+                .collect()
+        };
+        assert_eq!(
+            glyphs(&result.buffer.glyph_infos()[line.glyphs.clone()]),
+            glyphs(expected.glyph_infos()),
+            "{:?}",
+            &text[line.text.clone()]
+        );
+        let advance: i32 = expected
+            .glyph_positions()
+            .iter()
+            .map(|pos| pos.x_advance)
+            .sum();
+        assert_eq!(line.advance, advance);
+    }
+    assert_eq!(start, text.len());
+    assert_eq!(glyph, result.buffer.len());
+}
+
+fn line_texts<'a>(text: &'a str, result: &LineBreaks) -> Vec<&'a str> {
+    result
+        .lines
+        .iter()
+        .map(|line| &text[line.text.clone()])
+        .collect()
+}
+
+#[test]
+fn ltr_wrap() {
+    with_shaper(PT_SANS, |shaper| {
+        let word = shape(shaper, "ano ", 0..4, BufferClusterLevel::default());
+        let width: i32 = word.glyph_positions().iter().map(|pos| pos.x_advance).sum();
+        let text = "ano ano ano ano ano";
+        let level = BufferClusterLevel::default();
+        let result = break_lines(shaper, text, level, 2 * width);
+        assert_eq!(line_texts(text, &result), ["ano ano ", "ano ano ", "ano"]);
+        let result = break_lines(shaper, text, level, 2 * width - 1);
+        // The last line has no trailing space.
+        assert_eq!(
+            line_texts(text, &result),
+            ["ano ", "ano ", "ano ", "ano ano"]
+        );
+        // A word wider than the line overflows.
+        let result = break_lines(shaper, text, level, 10);
+        assert_eq!(result.lines.len(), 5);
+        assert!(result.lines.iter().all(|line| line.advance > 10));
+    });
+}
+
+#[test]
+fn rtl_wrap() {
+    with_shaper(NASTALIQ, |shaper| {
+        let text = "اُردو زبان کی ترویج و ترقی کے لیے بِسم اللہ سے شروع کریں۔";
+        let level = BufferClusterLevel::default();
+        for max_advance in [1000, 2000, 3000, 5000] {
+            let result = break_lines(shaper, text, level, max_advance);
+            assert!(result.lines.len() > 1);
+            for line in &result.lines[..result.lines.len() - 1] {
+                // Lines that end at a break opportunity fit.
+                if text[line.text.clone()].matches(' ').count() > 1 {
+                    assert!(line.advance <= max_advance);
+                }
+            }
+        }
+    });
+}
+
+#[test]
+fn unsafe_breaks() {
+    with_shaper(NASTALIQ, |shaper| {
+        // Break inside joined words, so that the line edges are reshaped.
+        let text = "ترویجترقی";
+        let paragraph = shape(shaper, text, 0..text.len(), BufferClusterLevel::default());
+        let breaks: Vec<usize> = text.char_indices().skip(1).map(|(i, _)| i).collect();
+        for max_advance in [500, 1000, 1500] {
+            let result = shaper.break_lines(&paragraph, text, &[], &breaks, max_advance);
+            check_lines(shaper, text, BufferClusterLevel::default(), &result);
+            assert!(result.lines.len() > 1);
+        }
+    });
+}
+
+#[test]
+fn non_monotone_clusters() {
+    with_shaper(DEVANAGARI, |shaper| {
+        // The vowel signs are reordered, so the clusters of the paragraph
+        // are not monotone at the character level.
+        let text = "कि कि किकि कि";
+        let level = BufferClusterLevel::Characters;
+        let result = break_lines(shaper, text, level, 3000);
+        assert_eq!(line_texts(text, &result), ["कि ", "कि ", "किकि ", "कि"]);
+        let advances: Vec<i32> = result.lines.iter().map(|line| line.advance).collect();
+        assert_eq!(advances, [3000, 3000, 5000, 2000]);
+        let result = break_lines(shaper, text, level, 6000);
+        assert_eq!(line_texts(text, &result), ["कि कि ", "किकि ", "कि"]);
+    });
+}
+
+#[test]
+fn safe_break_clusters() {
+    with_shaper(NASTALIQ, |shaper| {
+        let text = "اُردو زبان";
+        let paragraph = shape(shaper, text, 0..text.len(), BufferClusterLevel::default());
+        let safe: Vec<u32> = paragraph.safe_to_break_clusters().collect();
+        // Between the words, but not inside the joined ones.
+        assert!(safe.contains(&(text.find(' ').unwrap() as u32 + 1)));
+        assert!(!safe.contains(&2));
+        assert!(safe.windows(2).all(|pair| pair[0] < pair[1]));
+    });
+}
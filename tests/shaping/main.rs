@@ -1,8 +1,27 @@
+mod aat;
 mod aots;
+mod ascii;
+mod attachments;
+mod baseline;
+mod batch;
+mod buffer;
+mod carets;
+mod charmap;
+mod clusters;
 mod custom;
+mod fallback;
+mod glyph_names;
 mod in_house;
+mod line_break;
 mod macos;
+mod metrics;
+mod owned;
+mod persist;
+mod reshape;
+mod shape_plan;
+mod shaper_data;
 mod text_rendering_tests;
+mod word_cache;
 
 use harfrust::{BufferFlags, FontRef, ShaperData, ShaperInstance};
 use std::str::FromStr;
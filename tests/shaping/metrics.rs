@@ -0,0 +1,220 @@
+use harfrust::{FontRef, GlyphExtents, MetricsTag, Shaper, ShaperData, ShaperInstance, Variation};
+use std::str::FromStr;
+
+const CJK_GLYF: &str = "tests/fonts/in-house/NotoSansCJK-VF.abc.ttf";
+const CJK_CFF2: &str = "tests/fonts/in-house/NotoSansCJK-VF.abc.otf";
+
+fn with_shaper(path: &str, variations: &[&str], f: impl FnOnce(&Shaper)) {
+    with_font_data(&std::fs::read(path).unwrap(), variations, f);
+}
+
+fn with_font_data(font_data: &[u8], variations: &[&str], f: impl FnOnce(&Shaper)) {
+    let font = FontRef::new(font_data).unwrap();
+    let data = ShaperData::new(&font);
+    let variations: Vec<_> = variations
+        .iter()
+        .map(|variation| Variation::from_str(variation).unwrap())
+        .collect();
+    let instance =
+        (!variations.is_empty()).then(|| ShaperInstance::from_variations(&font, &variations));
+    let shaper = data.shaper(&font).instance(instance.as_ref()).build();
+    f(&shaper);
+}
+
+fn extents(path: &str, variations: &[&str], glyphs: &[u32]) -> Vec<Option<GlyphExtents>> {
+    let mut extents = vec![None; glyphs.len()];
+    with_shaper(path, variations, |shaper| {
+        shaper.glyph_extents_batch(glyphs, &mut extents);
+        for (glyph, batched) in glyphs.iter().zip(&extents) {
+            assert_eq!(shaper.glyph_extents(*glyph), *batched);
+        }
+    });
+    extents
+}
+
+fn glyph_extents(x_bearing: i32, y_bearing: i32, width: i32, height: i32) -> Option<GlyphExtents> {
+    Some(GlyphExtents {
+        x_bearing,
+        y_bearing,
+        width,
+        height,
+    })
+}
+
+// The expected extents are those of HarfBuzz's `hb_font_get_glyph_extents`.
+
+#[test]
+fn glyf_extents_vary() {
+    let path = "tests/fonts/in-house/NotoSans-VF.abc.ttf";
+    assert_eq!(
+        extents(path, &[], &[1, 2, 3]),
+        [
+            glyph_extents(46, 545, 434, -555),
+            glyph_extents(85, 760, 475, -770),
+            glyph_extents(55, 546, 392, -556),
+        ]
+    );
+    assert_eq!(
+        extents(path, &["wght=900"], &[1, 2, 3]),
+        [
+            glyph_extents(36, 563, 523, -573),
+            glyph_extents(66, 760, 534, -770),
+            glyph_extents(42, 563, 472, -573),
+        ]
+    );
+}
+
+#[test]
+fn composite_glyf_extents_vary() {
+    // Glyph 3 has glyph 7 as its only component.
+    let path = "tests/fonts/in-house/9d8c53cb64b8747abdd2b70755cce2ee0eb42ef7.ttf";
+    assert_eq!(
+        extents(path, &[], &[3, 7]),
+        [
+            glyph_extents(0, 896, 704, -896),
+            glyph_extents(0, 896, 704, -896),
+        ]
+    );
+    assert_eq!(
+        extents(path, &["wght=700"], &[3, 7]),
+        [
+            glyph_extents(0, 896, 799, -896),
+            glyph_extents(0, 896, 799, -896),
+        ]
+    );
+
+    // Glyph 5 is glyph 1 with glyph 11 above it, and gvar moves glyph 11
+    // up with the top of glyph 1.
+    let path = "tests/fonts/text-rendering-tests/TestGPOSFour.ttf";
+    assert_eq!(
+        extents(path, &[], &[1, 5]),
+        [
+            glyph_extents(30, 403, 1129, -635),
+            glyph_extents(30, 644, 1129, -876),
+        ]
+    );
+    assert_eq!(
+        extents(path, &["wght=900"], &[1, 5]),
+        [
+            glyph_extents(24, 516, 1397, -748),
+            glyph_extents(24, 821, 1397, -1053),
+        ]
+    );
+}
+
+#[test]
+fn cff2_extents_match_glyf() {
+    // The same outlines as glyf and as CFF2, apart from .notdef.
+    let glyphs = [1, 2, 3];
+    for variations in [&[][..], &["wght=700"], &["wght=1000"]] {
+        let glyf = extents(CJK_GLYF, variations, &glyphs);
+        assert!(glyf.iter().all(Option::is_some));
+        assert_eq!(
+            extents(CJK_CFF2, variations, &glyphs),
+            glyf,
+            "{variations:?}"
+        );
+    }
+    assert_eq!(
+        extents(CJK_CFF2, &["wght=700"], &glyphs),
+        [
+            glyph_extents(-4, 741, 649, -741),
+            glyph_extents(91, 741, 550, -741),
+            glyph_extents(54, 754, 575, -768),
+        ]
+    );
+}
+
+#[test]
+fn glyf_extents_round_halves_up() {
+    // The bottom of glyph 6 varies to -233.5, which rounds to -233.
+    let path = "tests/fonts/rb_custom/NotoSansSinhala.subset1.otf";
+    assert_eq!(
+        extents(path, &["wght=300", "wdth=80"], &[6, 7]),
+        [
+            glyph_extents(-503, 106, 450, -339),
+            glyph_extents(-698, 126, 629, -365),
+        ]
+    );
+}
+
+#[test]
+fn cff_extents() {
+    let path = "tests/fonts/text-rendering-tests/TestCFFThree.otf";
+    assert_eq!(
+        extents(path, &[], &[0, 1, 5]),
+        [
+            // An empty .notdef.
+            glyph_extents(0, 0, 0, 0),
+            glyph_extents(3, 656, 538, -656),
+            glyph_extents(128, 781, 199, -208),
+        ]
+    );
+    // Glyph 3 is glyph 1 with glyph 5 as an accent, using `seac`, with the
+    // accent offset by (15, 80).
+    assert_eq!(extents(path, &[], &[3]), [glyph_extents(3, 861, 538, -861)]);
+}
+
+#[test]
+fn extents_of_missing_glyphs() {
+    assert_eq!(extents(CJK_GLYF, &["wght=700"], &[1000]), [None]);
+    assert_eq!(extents(CJK_CFF2, &["wght=700"], &[1000]), [None]);
+}
+
+const NOTO_SANS_VF: &str = "tests/fonts/in-house/NotoSans-VF.abc.ttf";
+const CJK: &str = "tests/fonts/rb_custom/NotoSansCJK.subset1.otf";
+
+#[test]
+fn metrics_mvar() {
+    // The MVAR table varies the x-height and the strikeout offset.
+    for (variations, x_height, strikeout_offset) in [
+        (&[][..], 536, 322),
+        (&["wght=100"], 528, 317),
+        (&["wght=900"], 553, 332),
+    ] {
+        with_shaper(NOTO_SANS_VF, variations, |shaper| {
+            let metric = |tag| shaper.metric(tag);
+            assert_eq!(metric(MetricsTag::XHeight), Some(x_height));
+            assert_eq!(metric(MetricsTag::StrikeoutOffset), Some(strikeout_offset));
+            assert_eq!(metric(MetricsTag::StrikeoutSize), Some(50));
+            assert_eq!(metric(MetricsTag::CapHeight), Some(714));
+            assert_eq!(metric(MetricsTag::UnderlineOffset), Some(-100));
+            assert_eq!(metric(MetricsTag::UnderlineSize), Some(50));
+            assert_eq!(metric(MetricsTag::HorizontalAscender), Some(1069));
+            assert_eq!(metric(MetricsTag::HorizontalDescender), Some(-293));
+            assert_eq!(metric(MetricsTag::HorizontalLineGap), Some(0));
+            assert_eq!(metric(MetricsTag::HorizontalClippingDescent), Some(293));
+            // There is no `vhea` table.
+            assert_eq!(metric(MetricsTag::VerticalAscender), None);
+        });
+    }
+}
+
+#[test]
+fn metrics_use_typo_metrics() {
+    let mut font_data = std::fs::read(CJK).unwrap();
+    let line_metrics = |font_data: &[u8]| {
+        let mut metrics = [None; 3];
+        with_font_data(font_data, &[], |shaper| {
+            metrics = [
+                MetricsTag::HorizontalAscender,
+                MetricsTag::HorizontalDescender,
+                MetricsTag::HorizontalLineGap,
+            ]
+            .map(|tag| shaper.metric(tag));
+        });
+        metrics
+    };
+    // From `hhea`, which differs from the typographic metrics of `OS/2`.
+    assert_eq!(line_metrics(&font_data), [Some(1160), Some(-288), Some(0)]);
+
+    // Set `USE_TYPO_METRICS` in the `fsSelection` of `OS/2`.
+    let num_tables = usize::from(u16::from_be_bytes([font_data[4], font_data[5]]));
+    let os2 = (0..num_tables)
+        .map(|i| 12 + i * 16)
+        .find(|&record| &font_data[record..record + 4] == b"OS/2")
+        .map(|record| u32::from_be_bytes(font_data[record + 8..record + 12].try_into().unwrap()))
+        .unwrap() as usize;
+    font_data[os2 + 63] |= 0x80;
+    assert_eq!(line_metrics(&font_data), [Some(880), Some(-120), Some(0)]);
+}
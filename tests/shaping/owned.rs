@@ -0,0 +1,80 @@
+use harfrust::{
+    FontRef, OwnedShaper, SerializeFlags, Shaper, ShaperData, UnicodeBuffer, Variation,
+};
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// Substitutes `dollar.nostroke` for `dollar` from `wght=800` on with
+/// GSUB FeatureVariations.
+const VARIABLE: &str = "tests/fonts/text-rendering-tests/AdobeVFPrototype-Subset.otf";
+const PT_SANS: &str = "tests/fonts/rb_custom/PT_Sans-Caption-Web-Regular.ttf";
+
+fn shape(shaper: &Shaper, text: &str) -> String {
+    let mut buffer = UnicodeBuffer::new();
+    buffer.push_str(text);
+    buffer.guess_segment_properties();
+    shaper
+        .shape(buffer, &[])
+        .serialize(shaper, SerializeFlags::default())
+}
+
+#[test]
+fn matches_borrowed_shaper() {
+    let font_data: Arc<[u8]> = std::fs::read(PT_SANS).unwrap().into();
+    let owned = OwnedShaper::new(font_data.clone(), 0).unwrap();
+    let font = FontRef::new(&font_data).unwrap();
+    let data = ShaperData::new(&font);
+    let shaper = data.shaper(&font).build();
+    for text in ["Office", "AVATAR", "x\u{301}y"] {
+        assert_eq!(shape(&owned.shaper(), text), shape(&shaper, text));
+    }
+    assert_eq!(owned.index(), 0);
+    assert_eq!(owned.font_data().len(), font_data.len());
+}
+
+#[test]
+fn instances() {
+    let glyphs = |owned: &OwnedShaper<Vec<u8>>| {
+        let shaper = owned.shaper();
+        let mut buffer = UnicodeBuffer::new();
+        buffer.push_str("$");
+        buffer.guess_segment_properties();
+        shaper
+            .shape(buffer, &[])
+            .serialize(&shaper, SerializeFlags::NO_POSITIONS)
+    };
+    // The default instance is the heaviest.
+    let mut owned = OwnedShaper::new(std::fs::read(VARIABLE).unwrap(), 0).unwrap();
+    assert_eq!(glyphs(&owned), "dollar.nostroke=0");
+    owned.set_variations([Variation::from_str("wght=200").unwrap()]);
+    assert!(owned.instance().is_some());
+    assert!(!owned.shaper().coords().is_empty());
+    assert_eq!(glyphs(&owned), "dollar=0");
+    owned.set_instance(None);
+    assert_eq!(glyphs(&owned), "dollar.nostroke=0");
+}
+
+#[test]
+fn from_persisted_data() {
+    let font_data = std::fs::read(PT_SANS).unwrap();
+    let bytes = {
+        let font = FontRef::new(&font_data).unwrap();
+        ShaperData::new(&font).to_bytes(&font)
+    };
+    let data = {
+        let font = FontRef::new(&font_data).unwrap();
+        ShaperData::from_bytes(&font, &bytes).unwrap()
+    };
+    let owned = OwnedShaper::from_data(font_data.clone(), 0, data).unwrap();
+    let fresh = OwnedShaper::new(font_data, 0).unwrap();
+    assert_eq!(
+        shape(&owned.shaper(), "Office"),
+        shape(&fresh.shaper(), "Office")
+    );
+}
+
+#[test]
+fn invalid_font_data() {
+    assert!(OwnedShaper::new(vec![0u8; 16], 0).is_err());
+    assert!(OwnedShaper::new(std::fs::read(PT_SANS).unwrap(), 1).is_err());
+}
@@ -0,0 +1,126 @@
+use harfrust::{FontRef, SerializeFlags, ShaperData, UnicodeBuffer};
+
+fn shape(font: &FontRef, data: &ShaperData, text: &str) -> String {
+    let shaper = data.shaper(font).build();
+    let mut buffer = UnicodeBuffer::new();
+    buffer.push_str(text);
+    buffer.guess_segment_properties();
+    shaper
+        .shape(buffer, &[])
+        .serialize(&shaper, SerializeFlags::default())
+}
+
+/// Persists fresh shaper data for the font at `path`, restores it and
+/// checks that the restored data shapes `texts` like the original.
+fn check_round_trip(path: &str, texts: &[&str]) {
+    let font_data = std::fs::read(path).unwrap();
+    let font = FontRef::new(&font_data).unwrap();
+    let bytes = ShaperData::new(&font).to_bytes(&font);
+    let restored = ShaperData::from_bytes(&font, &bytes).unwrap();
+    assert_eq!(restored.to_bytes(&font), bytes);
+    let fresh = ShaperData::new(&font);
+    for text in texts {
+        assert_eq!(
+            shape(&font, &restored, text),
+            shape(&font, &fresh, text),
+            "{text:?}"
+        );
+    }
+}
+
+#[test]
+fn round_trip_ot() {
+    check_round_trip(
+        "tests/fonts/in-house/NotoNastaliqUrdu-Regular.ttf",
+        &["نستعلیق", "اردو زبان", "ہے"],
+    );
+    check_round_trip(
+        "tests/fonts/rb_custom/PT_Sans-Caption-Web-Regular.ttf",
+        &["Office affine", "AVATAR"],
+    );
+}
+
+#[test]
+fn round_trip_aat() {
+    check_round_trip("tests/fonts/in-house/MORXTwentyeight.ttf", &["ABCDE"]);
+    check_round_trip(
+        "tests/fonts/text-rendering-tests/TestMORXFourtyone.ttf",
+        &["ABC"],
+    );
+}
+
+#[test]
+fn font_mismatch() {
+    let read = |path| std::fs::read(path).unwrap();
+    let nastaliq = read("tests/fonts/in-house/NotoNastaliqUrdu-Regular.ttf");
+    let pt_sans = read("tests/fonts/rb_custom/PT_Sans-Caption-Web-Regular.ttf");
+    let nastaliq = FontRef::new(&nastaliq).unwrap();
+    let pt_sans = FontRef::new(&pt_sans).unwrap();
+    let bytes = ShaperData::new(&nastaliq).to_bytes(&nastaliq);
+    assert!(ShaperData::from_bytes(&pt_sans, &bytes).is_none());
+
+    // Same tables apart from GDEF.
+    let gpos = read("tests/fonts/text-rendering-tests/TestGPOSFour.ttf");
+    let carets = read("tests/fonts/rb_custom/TestGPOSFour-LigCarets.ttf");
+    let gpos = FontRef::new(&gpos).unwrap();
+    let carets = FontRef::new(&carets).unwrap();
+    let bytes = ShaperData::new(&gpos).to_bytes(&gpos);
+    assert!(ShaperData::from_bytes(&gpos, &bytes).is_some());
+    assert!(ShaperData::from_bytes(&carets, &bytes).is_none());
+}
+
+#[test]
+fn malformed_data() {
+    let font_data = std::fs::read("tests/fonts/in-house/NotoNastaliqUrdu-Regular.ttf").unwrap();
+    let font = FontRef::new(&font_data).unwrap();
+    let bytes = ShaperData::new(&font).to_bytes(&font);
+    assert!(ShaperData::from_bytes(&font, &[]).is_none());
+    assert!(ShaperData::from_bytes(&font, &bytes[..bytes.len() - 1]).is_none());
+    let mut extended = bytes.clone();
+    extended.push(0);
+    assert!(ShaperData::from_bytes(&font, &extended).is_none());
+    let mut bad_magic = bytes.clone();
+    bad_magic[0] ^= 0xff;
+    assert!(ShaperData::from_bytes(&font, &bad_magic).is_none());
+    let mut bad_version = bytes;
+    bad_version[4] ^= 0xff;
+    assert!(ShaperData::from_bytes(&font, &bad_version).is_none());
+}
+
+#[test]
+fn edited_table() {
+    let mut font_data = std::fs::read("tests/fonts/in-house/NotoNastaliqUrdu-Regular.ttf").unwrap();
+    let bytes = {
+        let font = FontRef::new(&font_data).unwrap();
+        ShaperData::new(&font).to_bytes(&font)
+    };
+    // Change the last byte of GPOS without updating the table directory.
+    let num_tables = usize::from(u16::from_be_bytes([font_data[4], font_data[5]]));
+    let record = (0..num_tables)
+        .map(|i| 12 + i * 16)
+        .find(|&record| &font_data[record..record + 4] == b"GPOS")
+        .unwrap();
+    let field = |offset: usize| {
+        u32::from_be_bytes(font_data[offset..offset + 4].try_into().unwrap()) as usize
+    };
+    let end = field(record + 8) + field(record + 12);
+    font_data[end - 1] ^= 0xff;
+    let font = FontRef::new(&font_data).unwrap();
+    assert!(ShaperData::from_bytes(&font, &bytes).is_none());
+}
+
+#[test]
+fn lookup_list_mismatch() {
+    let font_data = std::fs::read("tests/fonts/in-house/NotoNastaliqUrdu-Regular.ttf").unwrap();
+    let font = FontRef::new(&font_data).unwrap();
+    let bytes = ShaperData::new(&font).to_bytes(&font);
+    // The GSUB lookup count follows the 14-byte header, and the index of
+    // the first lookup follows its presence flag.
+    let mut bad_count = bytes.clone();
+    bad_count[14] += 1;
+    assert!(ShaperData::from_bytes(&font, &bad_count).is_none());
+    let mut bad_index = bytes;
+    assert_eq!(bad_index[18..21], [1, 0, 0]);
+    bad_index[19] = 1;
+    assert!(ShaperData::from_bytes(&font, &bad_index).is_none());
+}
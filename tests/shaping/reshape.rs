@@ -0,0 +1,121 @@
+use harfrust::{
+    BufferFlags, Direction, FontRef, GlyphBuffer, SerializeFlags, Shaper, ShaperData, UnicodeBuffer,
+};
+
+fn shape(shaper: &Shaper, text: &str, direction: Option<Direction>) -> GlyphBuffer {
+    let mut buffer = UnicodeBuffer::new();
+    buffer.push_str(text);
+    buffer.set_flags(BufferFlags::PRODUCE_UNSAFE_TO_CONCAT);
+    if let Some(direction) = direction {
+        buffer.set_direction(direction);
+    }
+    buffer.guess_segment_properties();
+    shaper.shape(buffer, &[])
+}
+
+/// Applies each edit to `text` and checks that reshaping the edit gives
+/// the same glyphs, clusters and flags as shaping the edited text.
+fn check_edits(
+    path: &str,
+    direction: Option<Direction>,
+    text: &str,
+    edits: &[(core::ops::Range<usize>, &str)],
+) {
+    let font_data = std::fs::read(path).unwrap();
+    let font = FontRef::new(&font_data).unwrap();
+    let data = ShaperData::new(&font);
+    let shaper = data.shaper(&font).build();
+    for (edit, replacement) in edits {
+        let mut new_text = text.to_string();
+        new_text.replace_range(edit.clone(), replacement);
+        let expected =
+            shape(&shaper, &new_text, direction).serialize(&shaper, SerializeFlags::GLYPH_FLAGS);
+        let previous = shape(&shaper, text, direction);
+        let reshaped = shaper.reshape_edit(previous, text, edit.clone(), replacement, &[]);
+        assert_eq!(
+            reshaped.serialize(&shaper, SerializeFlags::GLYPH_FLAGS),
+            expected,
+            "{edit:?} -> {replacement:?}"
+        );
+    }
+}
+
+const LATIN_TEXT: &str = "To be or not to be, AVA WAVE office";
+
+fn latin_edits() -> Vec<(core::ops::Range<usize>, &'static str)> {
+    let len = LATIN_TEXT.len();
+    vec![
+        // Replacing and deleting words, shifting the clusters after them.
+        (3..5, "see"),
+        (6..8, ""),
+        (9..12, "n"),
+        // At the edges of the text.
+        (0..0, "So "),
+        (len..len, " now"),
+        (0..3, ""),
+        // Between kerned pairs.
+        (21..22, "W"),
+        (20..21, "T"),
+        (24..25, "A"),
+        // Forming a ligature.
+        (29..30, "ffi"),
+        // Everything.
+        (0..len, "Tea"),
+    ]
+}
+
+// Without kerning every cluster is safe to concatenate, so edits are
+// spliced between the old glyphs.
+#[test]
+fn ltr_safe_edges() {
+    check_edits(
+        "tests/fonts/rb_custom/LaBelleAurore.ttf",
+        None,
+        LATIN_TEXT,
+        &latin_edits(),
+    );
+}
+
+#[test]
+fn rtl_safe_edges() {
+    check_edits(
+        "tests/fonts/rb_custom/LaBelleAurore.ttf",
+        Some(Direction::RightToLeft),
+        LATIN_TEXT,
+        &latin_edits(),
+    );
+}
+
+// Kerning leaves few or no clusters safe to concatenate, so windows are
+// widened or the whole text is reshaped.
+#[test]
+fn ltr_unsafe_edges() {
+    for path in [
+        "tests/fonts/rb_custom/PT_Sans-Caption-Web-Regular.ttf",
+        "tests/fonts/rb_custom/Linefont.ttf",
+    ] {
+        check_edits(path, None, LATIN_TEXT, &latin_edits());
+    }
+}
+
+// Arabic joining makes every cluster unsafe to concatenate.
+#[test]
+fn rtl_unsafe_edges() {
+    let text = "\u{0644}\u{0627} \u{0628}\u{0633}\u{0645} \u{0627}\u{0644}\u{0644}\u{0647} \u{0643}\u{062A}\u{0627}\u{0628}";
+    let offset = |i: usize| text.char_indices().nth(i).unwrap().0;
+    check_edits(
+        "tests/fonts/in-house/NotoNastaliqUrdu-Regular.ttf",
+        None,
+        text,
+        &[
+            // Replacing a whole word between spaces.
+            (offset(3)..offset(6), "\u{0633}\u{0644}\u{0627}\u{0645}"),
+            // Inside a word, changing the joining of its neighbors.
+            (offset(4)..offset(5), "\u{0627}"),
+            (offset(8)..offset(8), "\u{0628}"),
+            // At the edges of the text.
+            (0..offset(2), ""),
+            (text.len()..text.len(), " \u{0628}"),
+        ],
+    );
+}
@@ -0,0 +1,148 @@
+use harfrust::{
+    script, Direction, Feature, FontRef, Language, SerializeFlags, ShapePlan, Shaper, ShaperData,
+    ShaperInstance, Tag, UnicodeBuffer, Variation,
+};
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// Substitutes `dollar.nostroke` for `dollar` from `wght=800` on with
+/// GSUB FeatureVariations.
+const PATH: &str = "tests/fonts/text-rendering-tests/AdobeVFPrototype-Subset.otf";
+
+fn buffer() -> UnicodeBuffer {
+    let mut buffer = UnicodeBuffer::new();
+    buffer.push_str("$");
+    buffer.guess_segment_properties();
+    buffer
+}
+
+fn plan(shaper: &Shaper) -> ShapePlan {
+    let buffer = buffer();
+    ShapePlan::new(
+        shaper,
+        buffer.direction(),
+        Some(buffer.script()),
+        buffer.language().as_ref(),
+        &[],
+    )
+}
+
+fn shape(shaper: &Shaper, plan: &ShapePlan) -> String {
+    shaper.shape_with_plan(plan, buffer(), &[]).serialize(
+        shaper,
+        SerializeFlags::NO_POSITIONS | SerializeFlags::NO_CLUSTERS,
+    )
+}
+
+#[test]
+fn plan_for_other_instance() {
+    let font_data = std::fs::read(PATH).unwrap();
+    let font = FontRef::new(&font_data).unwrap();
+    let data = ShaperData::new(&font);
+    let instance =
+        |wght: &str| ShaperInstance::from_variations(&font, [Variation::from_str(wght).unwrap()]);
+    let (light, regular, black) = (
+        instance("wght=200"),
+        instance("wght=400"),
+        instance("wght=800"),
+    );
+    let light = data.shaper(&font).instance(Some(&light)).build();
+    let regular = data.shaper(&font).instance(Some(&regular)).build();
+    let black = data.shaper(&font).instance(Some(&black)).build();
+
+    let light_plan = plan(&light);
+    let black_plan = plan(&black);
+    assert!(light_plan.matches_instance(&regular));
+    assert!(!light_plan.matches_instance(&black));
+    assert!(!black_plan.matches_instance(&light));
+
+    // A plan for an instance that selects other feature variations is
+    // replaced by one for the shaper's instance.
+    assert_eq!(shape(&light, &light_plan), "dollar");
+    assert_eq!(shape(&regular, &light_plan), "dollar");
+    assert_eq!(shape(&black, &light_plan), "dollar.nostroke");
+    assert_eq!(shape(&black, &black_plan), "dollar.nostroke");
+    assert_eq!(shape(&light, &black_plan), "dollar");
+}
+
+#[test]
+fn cached_plans() {
+    let font_data = std::fs::read(PATH).unwrap();
+    let font = FontRef::new(&font_data).unwrap();
+    let data = ShaperData::new(&font);
+    let instance =
+        |wght: &str| ShaperInstance::from_variations(&font, [Variation::from_str(wght).unwrap()]);
+    let (light, regular, black) = (
+        instance("wght=200"),
+        instance("wght=400"),
+        instance("wght=800"),
+    );
+    let light = data.shaper(&font).instance(Some(&light)).build();
+    let regular = data.shaper(&font).instance(Some(&regular)).build();
+    let black = data.shaper(&font).instance(Some(&black)).build();
+
+    let ltr = Direction::LeftToRight;
+    let latin = Some(script::LATIN);
+    let english = Language::from_str("en").unwrap();
+    let liga = Tag::new(b"liga");
+    let plan = |shaper: &Shaper, direction, language: Option<&Language>, features: &[Feature]| {
+        shaper.cached_plan(direction, latin, language, features)
+    };
+
+    let base = plan(&light, ltr, None, &[]);
+    assert!(Arc::ptr_eq(&base, &plan(&light, ltr, None, &[])));
+    // Instances that select the same feature variations share plans.
+    assert!(Arc::ptr_eq(&base, &plan(&regular, ltr, None, &[])));
+    let black_plan = plan(&black, ltr, None, &[]);
+    assert!(!Arc::ptr_eq(&base, &black_plan));
+    assert!(black_plan.matches_instance(&black));
+
+    assert!(!Arc::ptr_eq(
+        &base,
+        &plan(&light, Direction::RightToLeft, None, &[])
+    ));
+    assert!(!Arc::ptr_eq(&base, &plan(&light, ltr, Some(&english), &[])));
+    assert!(!Arc::ptr_eq(
+        &base,
+        &light.cached_plan(ltr, Some(script::GREEK), None, &[])
+    ));
+
+    // Only the globalness of features matters, not their ranges.
+    let ranged = plan(&light, ltr, None, &[Feature::new(liga, 0, 0..2)]);
+    assert!(Arc::ptr_eq(
+        &ranged,
+        &plan(&light, ltr, None, &[Feature::new(liga, 0, 3..5)])
+    ));
+    let global = plan(&light, ltr, None, &[Feature::new(liga, 0, ..)]);
+    assert!(!Arc::ptr_eq(&ranged, &global));
+    assert!(!Arc::ptr_eq(
+        &global,
+        &plan(&light, ltr, None, &[Feature::new(liga, 1, ..)])
+    ));
+}
+
+#[test]
+fn cached_plan_eviction() {
+    let font_data = std::fs::read(PATH).unwrap();
+    let font = FontRef::new(&font_data).unwrap();
+    let data = ShaperData::new(&font);
+    let shaper = data.shaper(&font).build();
+    let plan = |value| {
+        let features = [Feature::new(Tag::new(b"salt"), value, ..)];
+        shaper.cached_plan(Direction::LeftToRight, Some(script::LATIN), None, &features)
+    };
+
+    let first = plan(0);
+    for value in 1..32 {
+        plan(value);
+    }
+    // Using the first plan keeps it in the cache.
+    assert!(Arc::ptr_eq(&first, &plan(0)));
+    plan(32);
+    assert!(Arc::ptr_eq(&first, &plan(0)));
+    // It is evicted once it is the least recently used of too many plans.
+    for value in 33..65 {
+        plan(value);
+    }
+    assert!(!Arc::ptr_eq(&first, &plan(0)));
+}
@@ -0,0 +1,106 @@
+use harfrust::{FontRef, SerializeFlags, Shaper, ShaperData, UnicodeBuffer};
+
+fn shape(shaper: &Shaper, text: &str) -> String {
+    let mut buffer = UnicodeBuffer::new();
+    buffer.push_str(text);
+    buffer.guess_segment_properties();
+    shaper
+        .shape(buffer, &[])
+        .serialize(shaper, SerializeFlags::default())
+}
+
+/// Shapes every `(text, expected)` case several times with one
+/// `ShaperData`, sequentially and from concurrent threads.
+fn check_shared(path: &str, cases: &[(&str, &str)]) {
+    let font_data = std::fs::read(path).unwrap();
+    let font = FontRef::new(&font_data).unwrap();
+    let data = ShaperData::new(&font);
+    let check = || {
+        let shaper = data.shaper(&font).build();
+        for _ in 0..2 {
+            for (text, expected) in cases {
+                assert_eq!(shape(&shaper, text), *expected, "{text:?}");
+            }
+        }
+    };
+    check();
+    std::thread::scope(|scope| {
+        for _ in 0..4 {
+            scope.spawn(check);
+        }
+    });
+}
+
+#[test]
+fn glyph_props_mark_filtering_sets() {
+    check_shared(
+        "tests/fonts/in-house/f22416c692720a7d46fadf4af99f4c9e094f00b9.ttf",
+        &[(
+            "\u{062A}\u{062E}\u{062A}\u{0629}",
+            "glyph837=6@299,1170+0|uni06C1.1=6+502|glyph837=4@149,690+0|uni0628.8=4+532|glyph836=2@-51,1259+0|glyph514=2+196|glyph837=0@655,1751+0|glyph112=0@0,-358+905",
+        )],
+    );
+}
+
+#[test]
+fn glyph_props_mark_attachment() {
+    check_shared(
+        "tests/fonts/in-house/98b7887cff91f722b92a8ff800120954606354f9.ttf",
+        &[(
+            "\u{100F}\u{103C}\u{102F}\u{1036}",
+            "uni103C102F=0+150|uni100F=0+550|uni1036=0@-150,0+0",
+        )],
+    );
+}
+
+/// Shapes `texts` with fresh `ShaperData` for each, then with one shared
+/// `ShaperData` in reverse order and from a thread per text, so that
+/// lookups are first used by different texts.
+fn check_lazy_lookups(path: &str, texts: &[&str]) {
+    let font_data = std::fs::read(path).unwrap();
+    let font = FontRef::new(&font_data).unwrap();
+    let expected: Vec<String> = texts
+        .iter()
+        .map(|text| shape(&ShaperData::new(&font).shaper(&font).build(), text))
+        .collect();
+
+    let data = ShaperData::new(&font);
+    let shaper = data.shaper(&font).build();
+    for (text, expected) in texts.iter().zip(&expected).rev() {
+        assert_eq!(shape(&shaper, text), *expected, "{text:?}");
+    }
+
+    let data = ShaperData::new(&font);
+    std::thread::scope(|scope| {
+        for (text, expected) in texts.iter().zip(&expected) {
+            let data = &data;
+            let font = &font;
+            scope.spawn(move || {
+                let shaper = data.shaper(font).build();
+                assert_eq!(shape(&shaper, text), *expected, "{text:?}");
+            });
+        }
+    });
+    // The lookups used so far are the same as if created eagerly.
+    assert_eq!(data.to_bytes(&font), ShaperData::new(&font).to_bytes(&font));
+}
+
+#[test]
+fn lazy_lookups() {
+    check_lazy_lookups(
+        "tests/fonts/in-house/NotoNastaliqUrdu-Regular.ttf",
+        &["نستعلیق", "اردو زبان", "ہے", "بِسم", "123"],
+    );
+    check_lazy_lookups(
+        "tests/fonts/text-rendering-tests/FDArrayTest257.otf",
+        &[
+            "\u{915}\u{93F}",
+            "\u{915}\u{94D}\u{937}",
+            "\u{930}\u{94D}\u{915}",
+        ],
+    );
+    check_lazy_lookups(
+        "tests/fonts/rb_custom/PT_Sans-Caption-Web-Regular.ttf",
+        &["Office", "AVATAR", "x\u{301}y"],
+    );
+}
@@ -0,0 +1,112 @@
+use harfrust::{
+    BufferFlags, FontRef, SerializeFlags, ShapePlan, Shaper, ShaperData, UnicodeBuffer, WordCache,
+};
+
+fn buffer(text: &str, flags: BufferFlags) -> UnicodeBuffer {
+    let mut buffer = UnicodeBuffer::new();
+    buffer.push_str(text);
+    buffer.set_flags(flags);
+    buffer.guess_segment_properties();
+    buffer
+}
+
+/// Shapes `text` with and without the word cache, asserting that the
+/// results are the same, and returns the number of cached words.
+fn check(shaper: &Shaper, text: &str, flags: BufferFlags, cache: &mut WordCache) -> usize {
+    let props = buffer(text, flags);
+    let plan = ShapePlan::new(
+        shaper,
+        props.direction(),
+        Some(props.script()),
+        props.language().as_ref(),
+        &[],
+    );
+    let expected = shaper
+        .shape_with_plan(&plan, buffer(text, flags), &[])
+        .serialize(shaper, SerializeFlags::GLYPH_FLAGS);
+    // Once to fill the cache and once to use it.
+    for _ in 0..2 {
+        let cached = shaper
+            .shape_with_word_cache(&plan, buffer(text, flags), &[], cache)
+            .serialize(shaper, SerializeFlags::GLYPH_FLAGS);
+        assert_eq!(cached, expected, "{text:?}");
+    }
+    cache.len()
+}
+
+fn with_shaper(path: &str, f: impl FnOnce(&Shaper)) {
+    let font_data = std::fs::read(path).unwrap();
+    let font = FontRef::new(&font_data).unwrap();
+    let data = ShaperData::new(&font);
+    let shaper = data.shaper(&font).build();
+    f(&shaper);
+}
+
+#[test]
+fn matches_shape_with_plan() {
+    let fonts = [
+        "tests/fonts/rb_custom/PT_Sans-Caption-Web-Regular.ttf",
+        "tests/fonts/text-rendering-tests/TestGPOSOne.ttf",
+        "tests/fonts/in-house/NotoNastaliqUrdu-Regular.ttf",
+    ];
+    let texts = [
+        "To be or not to be",
+        "AVA WAVE Tea Tu",
+        "fi fl ffi office",
+        "a  b   c",
+        "\u{0644}\u{0627} \u{0628}\u{0650}\u{0633}\u{0652}\u{0645}\u{0650} \u{0627}\u{0644}\u{0644}\u{0651}\u{064E}\u{0647}\u{0650}",
+    ];
+    for path in fonts {
+        with_shaper(path, |shaper| {
+            for flags in [
+                BufferFlags::default(),
+                BufferFlags::PRODUCE_UNSAFE_TO_CONCAT,
+            ] {
+                let mut cache = WordCache::default();
+                for text in texts {
+                    check(shaper, text, flags, &mut cache);
+                }
+            }
+        });
+    }
+}
+
+#[test]
+fn caches_words() {
+    with_shaper(
+        "tests/fonts/rb_custom/PT_Sans-Caption-Web-Regular.ttf",
+        |shaper| {
+            let mut cache = WordCache::default();
+            assert!(check(shaper, "AVA WAVE Tea", BufferFlags::default(), &mut cache) > 0);
+        },
+    );
+}
+
+// Random alternates depend on all preceding glyphs, so a word shaped on its
+// own would pick different alternates.
+#[test]
+fn random_alternates_bypass_cache() {
+    with_shaper(
+        "tests/fonts/in-house/5bb74492f5e0ffa1fbb72e4c881be035120b6513.ttf",
+        |shaper| {
+            let mut cache = WordCache::default();
+            let text = "TUV TUV TUV TUV TUV";
+            assert_eq!(check(shaper, text, BufferFlags::default(), &mut cache), 0);
+        },
+    );
+}
+
+// The `kern` table only flags the pairs it kerns.
+#[test]
+fn kern_table_bypasses_cache() {
+    for path in [
+        "tests/fonts/text-rendering-tests/TestKERNOne.otf",
+        "tests/fonts/in-house/e39391c77a6321c2ac7a2d644de0396470cd4bfe.ttf",
+    ] {
+        with_shaper(path, |shaper| {
+            let mut cache = WordCache::default();
+            let text = "\u{0131}TuT uTu AV ck ck";
+            assert_eq!(check(shaper, text, BufferFlags::default(), &mut cache), 0);
+        });
+    }
+}